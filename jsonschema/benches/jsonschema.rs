@@ -65,6 +65,41 @@ fn fast_schema(c: &mut Criterion) {
     });
 }
 
+fn limits(c: &mut Criterion) {
+    // Validations without limits should not pay for the accounting
+    bench_citm(&mut |name, schema, instance| {
+        let default = JSONSchema::compile(&schema).expect("Valid schema");
+        let limited = JSONSchema::options()
+            .with_max_evaluated_nodes(usize::MAX)
+            .compile(&schema)
+            .expect("Valid schema");
+        assert!(default.is_valid(&instance));
+        assert!(limited.is_valid(&instance));
+        c.bench_function(&format!("jsonschema-rs {} is_valid default", name), |b| {
+            b.iter(|| default.is_valid(&instance))
+        });
+        c.bench_function(&format!("jsonschema-rs {} is_valid limited", name), |b| {
+            b.iter(|| limited.is_valid(&instance))
+        });
+    });
+}
+
+fn artifacts(c: &mut Criterion) {
+    // Loading an artifact compiles the schema again, without meta-schema validation
+    bench_openapi(&mut |name, schema, instance| {
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        let bytes = compiled.to_bytes();
+        let loaded = JSONSchema::from_bytes(&bytes).expect("Valid artifact");
+        assert!(loaded.is_valid(&instance));
+        c.bench_function(&format!("jsonschema-rs {} compile", name), |b| {
+            b.iter(|| JSONSchema::compile(&schema).expect("Valid schema"))
+        });
+        c.bench_function(&format!("jsonschema-rs {} from_bytes", name), |b| {
+            b.iter(|| JSONSchema::from_bytes(&bytes).expect("Valid artifact"))
+        });
+    });
+}
+
 fn keywords(c: &mut Criterion) {
     bench_keywords(
         c,
@@ -136,5 +171,12 @@ fn validate_invalid(c: &mut Criterion, name: &str, schema: &Value, instance: &Va
     );
 }
 
-criterion_group!(arbitrary, large_schemas, fast_schema, keywords);
+criterion_group!(
+    arbitrary,
+    large_schemas,
+    fast_schema,
+    limits,
+    artifacts,
+    keywords
+);
 criterion_main!(arbitrary);
@@ -0,0 +1,167 @@
+//! Validation of many independent instances at once.
+//!
+//! With the `rayon` feature enabled, instances are validated in parallel. Reports are always
+//! produced in the input order.
+use crate::{JSONSchema, ValidationError};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde_json::Value;
+use std::io::{self, BufRead};
+
+/// How many JSON Lines records are read into memory before they are validated.
+const JSON_LINES_CHUNK_SIZE: usize = 4096;
+
+/// Validation result for a single record of a batch.
+#[derive(Debug)]
+pub struct RecordReport {
+    /// 1-based line number of the record in a JSON Lines input.
+    /// For in-memory batches it is the position of the instance in the batch, starting from 1.
+    pub line: usize,
+    /// Validation errors. Empty if the record is valid.
+    /// Records that are not valid JSON contain a single `ValidationErrorKind::JSONParse` error.
+    pub errors: Vec<ValidationError<'static>>,
+}
+
+impl RecordReport {
+    /// Whether the record is valid.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)] // `Vec::is_empty` is not `const` on older compilers
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+fn validate_record(schema: &JSONSchema, line: usize, instance: &Value) -> RecordReport {
+    let errors = match schema.validate_owned(instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors,
+    };
+    RecordReport { line, errors }
+}
+
+fn validate_parsed(
+    schema: &JSONSchema,
+    line: usize,
+    record: Result<Value, serde_json::Error>,
+) -> RecordReport {
+    match record {
+        Ok(instance) => validate_record(schema, line, &instance),
+        Err(error) => RecordReport {
+            line,
+            errors: vec![error.into()],
+        },
+    }
+}
+
+pub(crate) fn validate_batch<'a, I>(schema: &JSONSchema, instances: I) -> Vec<RecordReport>
+where
+    I: IntoIterator<Item = &'a Value>,
+{
+    #[cfg(feature = "rayon")]
+    {
+        let instances: Vec<&Value> = instances.into_iter().collect();
+        instances
+            .par_iter()
+            .enumerate()
+            .map(|(idx, instance)| validate_record(schema, idx + 1, instance))
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        instances
+            .into_iter()
+            .enumerate()
+            .map(|(idx, instance)| validate_record(schema, idx + 1, instance))
+            .collect()
+    }
+}
+
+fn validate_chunk<F>(
+    schema: &JSONSchema,
+    chunk: &mut Vec<(usize, Result<Value, serde_json::Error>)>,
+    on_report: &mut F,
+) where
+    F: FnMut(RecordReport),
+{
+    #[cfg(feature = "rayon")]
+    let reports: Vec<_> = chunk
+        .par_drain(..)
+        .map(|(line, record)| validate_parsed(schema, line, record))
+        .collect();
+    #[cfg(not(feature = "rayon"))]
+    let reports: Vec<_> = chunk
+        .drain(..)
+        .map(|(line, record)| validate_parsed(schema, line, record))
+        .collect();
+    reports.into_iter().for_each(on_report);
+}
+
+pub(crate) fn validate_json_lines<R, F>(
+    schema: &JSONSchema,
+    reader: R,
+    mut on_report: F,
+) -> io::Result<()>
+where
+    R: BufRead,
+    F: FnMut(RecordReport),
+{
+    let mut chunk = Vec::with_capacity(JSON_LINES_CHUNK_SIZE);
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let record = line.trim();
+        // Blank lines are allowed, e.g. a trailing new line at the end of the file
+        if record.is_empty() {
+            continue;
+        }
+        chunk.push((idx + 1, serde_json::from_str(record)));
+        if chunk.len() == JSON_LINES_CHUNK_SIZE {
+            validate_chunk(schema, &mut chunk, &mut on_report);
+        }
+    }
+    validate_chunk(schema, &mut chunk, &mut on_report);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::ValidationErrorKind, JSONSchema};
+    use serde_json::json;
+
+    #[test]
+    fn batch() {
+        let schema = JSONSchema::compile(&json!({"type": "integer", "minimum": 3})).unwrap();
+        let instances = vec![json!(3), json!("a"), json!(1), json!(5)];
+        let reports = schema.validate_batch(&instances);
+        let summary: Vec<_> = reports
+            .iter()
+            .map(|report| (report.line, report.is_valid(), report.errors.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(1, true, 0), (2, false, 1), (3, false, 1), (4, true, 0)]
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let schema = JSONSchema::compile(&json!({"required": ["id"]})).unwrap();
+        let input = "{\"id\": 1}\n\n{\"name\": \"foo\"}\n{\"id\": \n{\"id\": 2}\n";
+        let mut reports = Vec::new();
+        schema
+            .validate_json_lines(input.as_bytes(), |report| reports.push(report))
+            .unwrap();
+        let summary: Vec<_> = reports
+            .iter()
+            .map(|report| (report.line, report.is_valid()))
+            .collect();
+        assert_eq!(summary, vec![(1, true), (3, false), (4, false), (5, true)]);
+        assert_eq!(
+            reports[1].errors[0].to_string(),
+            r#""id" is a required property"#
+        );
+        assert!(matches!(
+            reports[2].errors[0].kind,
+            ValidationErrorKind::JSONParse { .. }
+        ));
+    }
+}
@@ -0,0 +1,385 @@
+//! Schema bundling. Inlines all external documents referenced via `$ref` into a single document.
+//!
+//! External documents are embedded into `definitions` (`$defs` for Draft 2019-09) with their
+//! `$id` set to the URL they were loaded from. References from the root document are rewritten
+//! to local JSON pointers; references inside embedded documents keep resolving via their `$id`.
+//!
+//! Before Draft 2019-09, keywords next to `$ref` are ignored, including `$id`. Documents with
+//! `$ref` at the top level are therefore embedded as `{"$id": ..., "allOf": [<document>]}`, and
+//! JSON pointers into them are prefixed with `/allOf/0`.
+use crate::{
+    compilation::{options::CompilationOptions, DEFAULT_SCOPE},
+    error::ValidationError,
+    resolver::{find_schemas, Resolver},
+    schemas::{self, id_keyword, is_data_keyword, is_schema_map, Draft},
+};
+use ahash::{AHashMap, AHashSet};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use url::Url;
+
+struct Bundler {
+    draft: Draft,
+    resolver: Resolver,
+    root: Url,
+    // Resources defined inside the root document via `$id`
+    internal: AHashSet<String>,
+    // Resource URL -> key in the definitions keyword
+    keys: AHashMap<String, String>,
+    taken: AHashSet<String>,
+    embedded: Vec<(String, Url, Value)>,
+    // Resources embedded inside `allOf`
+    wrapped: AHashSet<String>,
+}
+
+pub(crate) fn bundle(
+    config: &CompilationOptions,
+    schema: &Value,
+) -> Result<Value, ValidationError<'static>> {
+    let draft = config.draft_for(schema);
+    let scope = match schemas::id_of(draft, schema) {
+        Some(url) => Url::parse(url)?,
+        None => DEFAULT_SCOPE.clone(),
+    };
+    let mut internal = AHashSet::new();
+    find_schemas(draft, schema, &scope, &mut |id, _| {
+        internal.insert(without_fragment(&id));
+        None
+    })?;
+    let resolver = Resolver::new(draft, &scope, Arc::new(schema.clone()), config.store())
+        .map_err(ValidationError::into_owned)?;
+    let definitions = definitions_keyword(draft);
+    let taken = schema
+        .get(definitions)
+        .and_then(Value::as_object)
+        .map(|existing| existing.keys().cloned().collect())
+        .unwrap_or_default();
+    let mut bundler = Bundler {
+        draft,
+        resolver,
+        root: resource_of(&scope),
+        internal,
+        keys: AHashMap::new(),
+        taken,
+        embedded: Vec::new(),
+        wrapped: AHashSet::new(),
+    };
+    let mut bundled = schema.clone();
+    bundler.walk(&mut bundled, &scope)?;
+    // Embedded documents may reference other external documents
+    let mut idx = 0;
+    while idx < bundler.embedded.len() {
+        let (_, resource, mut document) = bundler.embedded[idx].clone();
+        bundler.walk(&mut document, &resource)?;
+        bundler.embedded[idx].2 = document;
+        idx += 1;
+    }
+    if bundler.embedded.is_empty() {
+        return Ok(bundled);
+    }
+    if let Value::Object(object) = &mut bundled {
+        let entry = object
+            .entry(definitions)
+            .or_insert_with(|| Value::Object(Map::new()));
+        match entry {
+            Value::Object(existing) => {
+                for (key, _, document) in bundler.embedded {
+                    existing.insert(key, document);
+                }
+            }
+            _ => return Err(ValidationError::schema(entry).into_owned()),
+        }
+    }
+    Ok(bundled)
+}
+
+impl Bundler {
+    /// Rewrite references in `schema` & collect external documents.
+    fn walk(&mut self, schema: &mut Value, base: &Url) -> Result<(), ValidationError<'static>> {
+        match schema {
+            Value::Object(object) => {
+                let mut base = base.clone();
+                if let Some(id) = object.get(id_keyword(self.draft)).and_then(Value::as_str) {
+                    base = base.join(id)?;
+                }
+                for (key, value) in object.iter_mut() {
+                    match (key.as_str(), value) {
+                        (keyword, _) if is_data_keyword(keyword) => {}
+                        ("$ref", Value::String(reference)) => {
+                            if let Some(rewritten) = self.reference(reference, &base)? {
+                                *reference = rewritten;
+                            }
+                        }
+                        // Keys of these objects are names, not keywords
+                        (keyword, Value::Object(members)) if is_schema_map(keyword) => {
+                            for member in members.values_mut() {
+                                self.walk(member, &base)?;
+                            }
+                        }
+                        (_, value) => self.walk(value, &base)?,
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.walk(item, base)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Embed the document behind `reference` if it is external and return the local reference
+    /// that should replace it.
+    fn reference(
+        &mut self,
+        reference: &str,
+        base: &Url,
+    ) -> Result<Option<String>, ValidationError<'static>> {
+        let url = base.join(reference)?;
+        let resource = resource_of(&url);
+        if resource == self.root || self.internal.contains(resource.as_str()) {
+            return Ok(None);
+        }
+        let key = match self.keys.get(resource.as_str()) {
+            Some(key) => key.clone(),
+            None => self.embed(&resource)?,
+        };
+        let fragment = url.fragment().unwrap_or("");
+        let prefix = if fragment.starts_with('/') && self.wrapped.contains(resource.as_str()) {
+            "/allOf/0"
+        } else {
+            ""
+        };
+        // Only references resolved against the root document can point to its definitions
+        if resource_of(base) != self.root {
+            return Ok(if prefix.is_empty() {
+                None
+            } else if resource_of(base) == resource {
+                Some(format!("#{}{}", prefix, fragment))
+            } else {
+                Some(format!("{}#{}{}", resource, prefix, fragment))
+            });
+        }
+        if fragment.is_empty() || fragment.starts_with('/') {
+            Ok(Some(format!(
+                "#/{}/{}{}{}",
+                definitions_keyword(self.draft),
+                escape(&key),
+                prefix,
+                fragment
+            )))
+        } else {
+            // Location-independent identifiers are resolved via the embedded `$id`
+            Ok(None)
+        }
+    }
+
+    fn embed(&mut self, resource: &Url) -> Result<String, ValidationError<'static>> {
+        let document = self
+            .resolver
+            .resolve_url(resource)
+            .map_err(ValidationError::into_owned)?;
+        let mut document = document.as_ref().clone();
+        let id = Value::String(resource.to_string());
+        if let Value::Object(object) = &mut document {
+            if object.contains_key("$ref") && !supports_ref_siblings(self.draft) {
+                // `$id` would be ignored next to `$ref`, as well as the original one
+                object.remove(id_keyword(self.draft));
+                let mut wrapper = Map::new();
+                wrapper.insert(id_keyword(self.draft).to_string(), id);
+                wrapper.insert("allOf".to_string(), Value::Array(vec![document]));
+                document = Value::Object(wrapper);
+                self.wrapped.insert(resource.to_string());
+            } else {
+                object.insert(id_keyword(self.draft).to_string(), id);
+            }
+        }
+        let mut key = resource.to_string();
+        let mut suffix = 1;
+        while self.taken.contains(&key) {
+            key = format!("{}-{}", resource, suffix);
+            suffix += 1;
+        }
+        self.taken.insert(key.clone());
+        self.keys.insert(resource.to_string(), key.clone());
+        self.embedded
+            .push((key.clone(), resource.clone(), document));
+        Ok(key)
+    }
+}
+
+const fn definitions_keyword(draft: Draft) -> &'static str {
+    match draft {
+        #[cfg(feature = "draft201909")]
+        Draft::Draft201909 => "$defs",
+        _ => "definitions",
+    }
+}
+
+/// Whether keywords next to `$ref` are applied.
+const fn supports_ref_siblings(draft: Draft) -> bool {
+    match draft {
+        #[cfg(feature = "draft201909")]
+        Draft::Draft201909 => true,
+        _ => false,
+    }
+}
+
+fn resource_of(url: &Url) -> Url {
+    let mut resource = url.clone();
+    resource.set_fragment(None);
+    resource
+}
+
+fn without_fragment(url: &str) -> String {
+    url.split('#').next().unwrap_or(url).to_string()
+}
+
+/// Escape a JSON pointer token & make it usable inside a URI fragment.
+fn escape(token: &str) -> String {
+    token
+        .replace('~', "~0")
+        .replace('/', "~1")
+        .replace('%', "%25")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn options() -> crate::CompilationOptions {
+        let mut options = JSONSchema::options();
+        options
+            .with_document(
+                "http://example.com/person.json".to_string(),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"$ref": "#/definitions/name"},
+                        "address": {"$ref": "address.json"}
+                    },
+                    "definitions": {"name": {"type": "string"}}
+                }),
+            )
+            .with_document(
+                "http://example.com/address.json".to_string(),
+                json!({"type": "object", "required": ["city"]}),
+            )
+            .with_document(
+                "http://example.com/alias.json".to_string(),
+                json!({
+                    "$ref": "#/definitions/name",
+                    "definitions": {
+                        "name": {"type": "string"},
+                        "names": {"items": {"$ref": "#/definitions/name"}}
+                    }
+                }),
+            );
+        options
+    }
+
+    #[test_case(&json!({"$ref": "http://example.com/person.json"}), &json!({"name": "Alice"}), true)]
+    #[test_case(&json!({"$ref": "http://example.com/person.json"}), &json!({"name": 42}), false)]
+    #[test_case(&json!({"$ref": "http://example.com/person.json"}), &json!({"address": {}}), false)]
+    #[test_case(&json!({"items": {"$ref": "http://example.com/person.json#/definitions/name"}}), &json!(["Alice"]), true)]
+    #[test_case(&json!({"items": {"$ref": "http://example.com/person.json#/definitions/name"}}), &json!([42]), false)]
+    #[test_case(&json!({"items": {"$ref": "http://example.com/person.json#/properties/address"}}), &json!([{}]), false)]
+    #[test_case(&json!({"items": {"$ref": "http://example.com/person.json#/properties/address"}}), &json!([{"city": "Prague"}]), true)]
+    #[test_case(&json!({"$id": "http://example.com/root.json", "items": {"$ref": "address.json"}}), &json!([{}]), false)]
+    #[test_case(&json!({"$ref": "http://example.com/alias.json"}), &json!("Alice"), true)]
+    #[test_case(&json!({"$ref": "http://example.com/alias.json"}), &json!(42), false)]
+    #[test_case(&json!({"$ref": "http://example.com/alias.json#/definitions/names"}), &json!(["Alice"]), true)]
+    #[test_case(&json!({"$ref": "http://example.com/alias.json#/definitions/names"}), &json!([42]), false)]
+    fn validates_like_original(schema: &Value, instance: &Value, expected: bool) {
+        let options = options();
+        let bundled = options.bundle(schema).expect("Bundled");
+        let original = options.compile(schema).expect("Valid schema");
+        assert_eq!(original.is_valid(instance), expected);
+        // Bundled schema does not need the documents store
+        let compiled = JSONSchema::compile(&bundled).expect("Valid schema");
+        assert_eq!(compiled.is_valid(instance), expected);
+    }
+
+    #[test]
+    fn rewrites_references() {
+        let schema = json!({
+            "properties": {"person": {"$ref": "http://example.com/person.json"}},
+            "definitions": {"local": {"type": "integer"}}
+        });
+        let bundled = options().bundle(&schema).expect("Bundled");
+        assert_eq!(
+            bundled.pointer("/properties/person/$ref"),
+            Some(&json!("#/definitions/http:~1~1example.com~1person.json"))
+        );
+        let definitions = bundled["definitions"].as_object().expect("Object");
+        assert_eq!(definitions.len(), 3);
+        assert_eq!(definitions["local"], json!({"type": "integer"}));
+        assert_eq!(
+            definitions["http://example.com/address.json"]["$id"],
+            json!("http://example.com/address.json")
+        );
+    }
+
+    #[test]
+    fn skips_data_keywords() {
+        let schema = json!({
+            "default": {"$ref": "http://example.com/person.json"},
+            "examples": [{"$ref": "http://example.com/address.json"}],
+            "properties": {
+                "default": {"$ref": "http://example.com/address.json"},
+                "enum": {"enum": [{"$ref": "http://example.com/person.json"}]}
+            }
+        });
+        let bundled = options().bundle(&schema).expect("Bundled");
+        assert_eq!(bundled["default"], schema["default"]);
+        assert_eq!(bundled["examples"], schema["examples"]);
+        assert_eq!(bundled["properties"]["enum"], schema["properties"]["enum"]);
+        assert_eq!(
+            bundled.pointer("/properties/default/$ref"),
+            Some(&json!("#/definitions/http:~1~1example.com~1address.json"))
+        );
+        let definitions = bundled["definitions"].as_object().expect("Object");
+        assert_eq!(definitions.len(), 1);
+    }
+
+    /// Whether `$id` occurs next to `$ref` anywhere in `schema`.
+    fn has_id_next_to_ref(schema: &Value) -> bool {
+        match schema {
+            Value::Object(object) => {
+                (object.contains_key("$id") && object.contains_key("$ref"))
+                    || object.values().any(has_id_next_to_ref)
+            }
+            Value::Array(items) => items.iter().any(has_id_next_to_ref),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn wraps_documents_with_ref() {
+        let schema = json!({"$ref": "http://example.com/alias.json#/definitions/names"});
+        let bundled = options().bundle(&schema).expect("Bundled");
+        assert!(!has_id_next_to_ref(&bundled));
+        let key = "http:~1~1example.com~1alias.json";
+        assert_eq!(
+            bundled["$ref"],
+            json!(format!("#/definitions/{}/allOf/0/definitions/names", key))
+        );
+        let embedded = &bundled["definitions"]["http://example.com/alias.json"];
+        assert_eq!(embedded["$id"], json!("http://example.com/alias.json"));
+        assert_eq!(
+            embedded["allOf"][0]["definitions"]["names"]["items"]["$ref"],
+            json!("#/allOf/0/definitions/name")
+        );
+    }
+
+    #[test]
+    fn no_external_references() {
+        let schema =
+            json!({"properties": {"a": {"$ref": "#/definitions/a"}}, "definitions": {"a": {}}});
+        assert_eq!(options().bundle(&schema).expect("Bundled"), schema);
+    }
+}
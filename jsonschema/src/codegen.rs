@@ -0,0 +1,1001 @@
+//! Generation of Rust types that mirror a schema.
+//!
+//! The output is Rust source code with `serde` attributes:
+//!   - `properties` & `required` become struct fields. Optional fields are wrapped in `Option`;
+//!   - `additionalProperties: false` becomes `#[serde(deny_unknown_fields)]`, and schemas in
+//!     `additionalProperties` become a flattened map;
+//!   - `enum` of strings becomes a Rust enum;
+//!   - `oneOf` & `anyOf` become internally tagged enums if all variants have a required property
+//!     with a distinct constant string value, and untagged enums otherwise. A `null` variant makes
+//!     the other variant optional instead;
+//!   - `allOf` of objects is merged into a single struct;
+//!   - `$ref` targets become named types. Recursive references are boxed.
+//!
+//! Schemas that can't be expressed (e.g. `not` or mixed `type` arrays) are represented as
+//! `serde_json::Value`.
+//!
+//! ```rust
+//! use jsonschema::{codegen, JSONSchema};
+//! use serde_json::json;
+//!
+//! let schema = json!({
+//!     "properties": {
+//!         "name": {"type": "string"},
+//!         "tags": {"type": "array", "items": {"$ref": "#/definitions/tag"}}
+//!     },
+//!     "required": ["name"],
+//!     "definitions": {"tag": {"enum": ["new", "used"]}}
+//! });
+//! let code = codegen::rust_types(&schema, &JSONSchema::options(), "Item")
+//!     .expect("All references are resolvable");
+//! assert!(code.contains("pub struct Item {"));
+//! assert!(code.contains("pub name: String,"));
+//! assert!(code.contains("pub tags: Option<Vec<Tag>>,"));
+//! assert!(code.contains("pub enum Tag {"));
+//! ```
+use crate::{
+    compilation::{options::CompilationOptions, DEFAULT_SCOPE},
+    error::ValidationError,
+    resolver::Resolver,
+    schemas::{self, id_keyword, Draft},
+};
+use ahash::{AHashMap, AHashSet};
+use serde_json::{Map, Value};
+use std::{fmt::Write, sync::Arc};
+use url::Url;
+
+const HEADER: &str = "// Generated from a JSON Schema. Do not edit manually.\n\
+                      use serde::{Deserialize, Serialize};\n";
+const DERIVE: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n";
+const ANY: &str = "serde_json::Value";
+// Names the output refers to, generated types must not shadow them
+const RESERVED: &[&str] = &[
+    "Box",
+    "Deserialize",
+    "Option",
+    "Self",
+    "Serialize",
+    "String",
+    "Vec",
+];
+
+/// Generate Rust types for `schema`. The root type is called `root`, other names are derived from
+/// `title`s, definition names & property names. References are resolved with the documents store
+/// & draft from `options`.
+pub fn rust_types(
+    schema: &Value,
+    options: &CompilationOptions,
+    root: &str,
+) -> Result<String, ValidationError<'static>> {
+    let draft = options.draft_for(schema);
+    let scope = match schemas::id_of(draft, schema) {
+        Some(url) => Url::parse(url)?,
+        None => DEFAULT_SCOPE.clone(),
+    };
+    let resolver = Resolver::new(draft, &scope, Arc::new(schema.clone()), options.store())
+        .map_err(ValidationError::into_owned)?;
+    let mut generator = Generator {
+        draft,
+        resolver,
+        names: AHashMap::new(),
+        taken: RESERVED.iter().map(|name| (*name).to_string()).collect(),
+        stack: Vec::new(),
+        items: Vec::new(),
+    };
+    let name = generator.unique_name(&pascal_case(root));
+    let key = normalize(scope.clone());
+    generator.names.insert(key.clone(), name.clone());
+    generator.stack.push(key);
+    generator.define(schema, &scope, &name)?;
+    let mut output = HEADER.to_string();
+    for item in generator.items {
+        output.push('\n');
+        output.push_str(&item);
+    }
+    Ok(output)
+}
+
+struct Generator {
+    draft: Draft,
+    resolver: Resolver,
+    // Reference target URL -> type name
+    names: AHashMap<String, String>,
+    taken: AHashSet<String>,
+    // Reference targets that are being generated
+    stack: Vec<String>,
+    // Rendered type definitions
+    items: Vec<String>,
+}
+
+type GenerationResult<T> = Result<T, ValidationError<'static>>;
+
+impl Generator {
+    /// Define a type called `name` for `schema`.
+    fn define(&mut self, schema: &Value, base: &Url, name: &str) -> GenerationResult<()> {
+        let object = match schema {
+            Value::Object(object) => object,
+            _ => return self.alias(schema, base, name),
+        };
+        let base = self.base_of(object, base)?;
+        if object.contains_key("$ref") {
+            return self.alias(schema, &base, name);
+        }
+        if let Some(variants) = string_enum(object) {
+            self.string_enum(object, &variants, name);
+            return Ok(());
+        }
+        for keyword in &["oneOf", "anyOf"] {
+            if let Some(Value::Array(variants)) = object.get(*keyword) {
+                if nullable(variants).is_none() {
+                    return self.union(object, variants, &base, name);
+                }
+            }
+        }
+        if is_struct(object) {
+            return self.structure(object, &base, name);
+        }
+        self.alias(schema, &base, name)
+    }
+
+    fn alias(&mut self, schema: &Value, base: &Url, name: &str) -> GenerationResult<()> {
+        let index = self.reserve();
+        let type_ = self.type_of(schema, base, &format!("{}Inner", name))?;
+        let mut definition = String::new();
+        document(&mut definition, schema, "");
+        let _ = writeln!(definition, "pub type {} = {};", name, unbox(&type_));
+        self.items[index] = definition;
+        Ok(())
+    }
+
+    /// Rust type for `schema`. Types that need a definition are named after `hint`.
+    fn type_of(
+        &mut self,
+        schema: &Value,
+        parent_base: &Url,
+        hint: &str,
+    ) -> GenerationResult<String> {
+        let object = match schema {
+            Value::Object(object) => object,
+            _ => return Ok(ANY.to_string()),
+        };
+        let base = self.base_of(object, parent_base)?;
+        if let Some(Value::String(reference)) = object.get("$ref") {
+            if ignores_ref_siblings(self.draft) || object.len() == 1 {
+                return self.reference(reference, &base, hint);
+            }
+        }
+        if let Some(value) = object.get("const") {
+            return Ok(type_of_value(value).to_string());
+        }
+        if let Some(Value::Array(values)) = object.get("enum") {
+            if string_enum(object).is_none() {
+                return Ok(common_type(values).to_string());
+            }
+        }
+        for keyword in &["oneOf", "anyOf"] {
+            if let Some(Value::Array(variants)) = object.get(*keyword) {
+                if let Some(variant) = nullable(variants) {
+                    let inner = self.type_of(variant, &base, hint)?;
+                    return Ok(optional(&inner));
+                }
+            }
+        }
+        if let Some(Value::Array(subschemas)) = object.get("allOf") {
+            if subschemas.len() == 1 && object.len() == 1 {
+                return self.type_of(&subschemas[0], &base, hint);
+            }
+        }
+        if string_enum(object).is_some()
+            || object.contains_key("oneOf")
+            || object.contains_key("anyOf")
+            || is_struct(object)
+        {
+            let name = self.unique_name(&type_name(object, hint));
+            self.define(schema, parent_base, &name)?;
+            return Ok(name);
+        }
+        let types = match object.get("type") {
+            Some(Value::String(type_)) => vec![type_.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ if object.contains_key("items") => vec!["array"],
+            _ if object.contains_key("additionalProperties") => vec!["object"],
+            _ => return Ok(ANY.to_string()),
+        };
+        match types.as_slice() {
+            [type_] => self.primitive(object, type_, &base, hint),
+            ["null", type_] | [type_, "null"] => {
+                let inner = self.primitive(object, type_, &base, hint)?;
+                Ok(optional(unbox(&inner)))
+            }
+            _ => Ok(ANY.to_string()),
+        }
+    }
+
+    fn primitive(
+        &mut self,
+        object: &Map<String, Value>,
+        type_: &str,
+        base: &Url,
+        hint: &str,
+    ) -> GenerationResult<String> {
+        let type_ = match type_ {
+            "string" => "String".to_string(),
+            "integer" => "i64".to_string(),
+            "number" => "f64".to_string(),
+            "boolean" => "bool".to_string(),
+            "null" => "()".to_string(),
+            "array" => match object.get("items") {
+                Some(Value::Array(items)) => {
+                    let mut types = Vec::with_capacity(items.len());
+                    for (idx, item) in items.iter().enumerate() {
+                        let type_ = self.type_of(item, base, &format!("{}{}", hint, idx))?;
+                        types.push(unbox(&type_).to_string());
+                    }
+                    match types.as_slice() {
+                        [single] => format!("({},)", single),
+                        _ => format!("({})", types.join(", ")),
+                    }
+                }
+                Some(items) => {
+                    let item = self.type_of(items, base, &format!("{}Item", hint))?;
+                    format!("Vec<{}>", unbox(&item))
+                }
+                None => format!("Vec<{}>", ANY),
+            },
+            "object" => match object.get("additionalProperties") {
+                Some(additional @ Value::Object(_)) => {
+                    let value = self.type_of(additional, base, &format!("{}Value", hint))?;
+                    format!("std::collections::BTreeMap<String, {}>", unbox(&value))
+                }
+                _ => format!("serde_json::Map<String, {}>", ANY),
+            },
+            _ => ANY.to_string(),
+        };
+        Ok(type_)
+    }
+
+    fn reference(&mut self, reference: &str, base: &Url, hint: &str) -> GenerationResult<String> {
+        let url = base.join(reference)?;
+        let key = normalize(url.clone());
+        if let Some(name) = self.names.get(&key) {
+            if self.stack.contains(&key) {
+                return Ok(format!("Box<{}>", name));
+            }
+            return Ok(name.clone());
+        }
+        let (scope, target) = self
+            .resolver
+            .resolve_fragment(self.draft, &url)
+            .map_err(ValidationError::into_owned)?;
+        let hint = url
+            .fragment()
+            .and_then(|fragment| fragment.rsplit('/').next())
+            .filter(|segment| !segment.is_empty())
+            .or_else(|| {
+                url.path_segments()
+                    .and_then(Iterator::last)
+                    .and_then(|segment| segment.split('.').next())
+            })
+            .map(|segment| percent_encoding::percent_decode_str(segment).decode_utf8_lossy())
+            .map_or_else(|| hint.to_string(), |segment| pascal_case(&segment));
+        let name = match target.as_object() {
+            Some(object) => self.unique_name(&type_name(object, &hint)),
+            None => self.unique_name(&hint),
+        };
+        self.names.insert(key.clone(), name.clone());
+        self.stack.push(key);
+        self.define(&target, &scope, &name)?;
+        self.stack.pop();
+        Ok(name)
+    }
+
+    fn structure(
+        &mut self,
+        object: &Map<String, Value>,
+        base: &Url,
+        name: &str,
+    ) -> GenerationResult<()> {
+        let index = self.reserve();
+        let mut properties = Vec::new();
+        let mut required = Vec::new();
+        self.collect_properties(object, base, &mut properties, &mut required)?;
+        let mut definition = String::new();
+        document(&mut definition, &Value::Object(object.clone()), "");
+        definition.push_str(DERIVE);
+        if object.get("additionalProperties") == Some(&Value::Bool(false)) {
+            definition.push_str("#[serde(deny_unknown_fields)]\n");
+        }
+        let _ = writeln!(definition, "pub struct {} {{", name);
+        let mut fields = AHashSet::new();
+        for (property, subschema, base) in &properties {
+            let type_ = self.type_of(
+                subschema,
+                base,
+                &format!("{}{}", name, pascal_case(property)),
+            )?;
+            let mut field = snake_case(property);
+            while !fields.insert(field.clone()) {
+                field.push('_');
+            }
+            document(&mut definition, subschema, "    ");
+            let is_required = required.contains(property);
+            let mut attributes = Vec::new();
+            if field.trim_start_matches("r#") != property {
+                attributes.push(format!("rename = {:?}", property));
+            }
+            if !is_required {
+                attributes.push("default".to_string());
+                attributes.push("skip_serializing_if = \"Option::is_none\"".to_string());
+            }
+            if !attributes.is_empty() {
+                let _ = writeln!(definition, "    #[serde({})]", attributes.join(", "));
+            }
+            let type_ = if is_required || type_.starts_with("Option<") {
+                type_
+            } else {
+                optional(&type_)
+            };
+            let _ = writeln!(definition, "    pub {}: {},", field, type_);
+        }
+        if let Some(additional @ Value::Object(_)) = object.get("additionalProperties") {
+            let value = self.type_of(additional, base, &format!("{}Value", name))?;
+            let mut field = "additional_properties".to_string();
+            while fields.contains(&field) {
+                field.push('_');
+            }
+            definition.push_str("    #[serde(flatten)]\n");
+            let _ = writeln!(
+                definition,
+                "    pub {}: std::collections::BTreeMap<String, {}>,",
+                field,
+                unbox(&value)
+            );
+        }
+        definition.push_str("}\n");
+        self.items[index] = definition;
+        Ok(())
+    }
+
+    /// Properties of `object` and of all its `allOf` sub-schemas.
+    fn collect_properties(
+        &mut self,
+        object: &Map<String, Value>,
+        base: &Url,
+        properties: &mut Vec<(String, Value, Url)>,
+        required: &mut Vec<String>,
+    ) -> GenerationResult<()> {
+        if let Some(Value::Object(own)) = object.get("properties") {
+            for (property, subschema) in own {
+                if !properties
+                    .iter()
+                    .any(|(existing, _, _)| existing == property)
+                {
+                    properties.push((property.clone(), subschema.clone(), base.clone()));
+                }
+            }
+        }
+        if let Some(Value::Array(names)) = object.get("required") {
+            for name in names.iter().filter_map(Value::as_str) {
+                if !required.iter().any(|existing| existing == name) {
+                    required.push(name.to_string());
+                }
+            }
+        }
+        if let Some(Value::Array(subschemas)) = object.get("allOf") {
+            for subschema in subschemas {
+                let (scope, subschema) = self.flatten(subschema, base)?;
+                if let Value::Object(subschema) = subschema {
+                    let scope = self.base_of(&subschema, &scope)?;
+                    self.collect_properties(&subschema, &scope, properties, required)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Follow top-level references of `schema`.
+    fn flatten(&self, schema: &Value, base: &Url) -> GenerationResult<(Url, Value)> {
+        let mut base = base.clone();
+        let mut schema = schema.clone();
+        let mut visited = Vec::new();
+        while let Some(Value::String(reference)) = schema.get("$ref") {
+            let url = base.join(reference)?;
+            let key = normalize(url.clone());
+            if visited.contains(&key) {
+                break;
+            }
+            visited.push(key);
+            let (scope, target) = self
+                .resolver
+                .resolve_fragment(self.draft, &url)
+                .map_err(ValidationError::into_owned)?;
+            base = scope;
+            schema = (*target).clone();
+        }
+        Ok((base, schema))
+    }
+
+    fn string_enum(&mut self, object: &Map<String, Value>, values: &[&str], name: &str) {
+        let mut definition = String::new();
+        document(&mut definition, &Value::Object(object.clone()), "");
+        definition.push_str(
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]\n",
+        );
+        let _ = writeln!(definition, "pub enum {} {{", name);
+        let mut variants = AHashSet::new();
+        for value in values {
+            let variant = unique(&mut variants, &variant_name(value));
+            if variant != *value {
+                let _ = writeln!(definition, "    #[serde(rename = {:?})]", value);
+            }
+            let _ = writeln!(definition, "    {},", variant);
+        }
+        definition.push_str("}\n");
+        self.items.push(definition);
+    }
+
+    fn union(
+        &mut self,
+        object: &Map<String, Value>,
+        variants: &[Value],
+        base: &Url,
+        name: &str,
+    ) -> GenerationResult<()> {
+        let index = self.reserve();
+        let mut flattened = Vec::with_capacity(variants.len());
+        for variant in variants {
+            flattened.push(self.flatten(variant, base)?);
+        }
+        let mut definition = String::new();
+        document(&mut definition, &Value::Object(object.clone()), "");
+        definition.push_str(DERIVE);
+        let mut names = AHashSet::new();
+        if let Some(tag) = tag_of(&flattened) {
+            let _ = writeln!(definition, "#[serde(tag = {:?})]", tag);
+            let _ = writeln!(definition, "pub enum {} {{", name);
+            for (scope, variant) in &flattened {
+                let mut variant = variant.as_object().cloned().unwrap_or_default();
+                let value = tag_value(&variant, &tag).unwrap_or_default().to_string();
+                if let Some(Value::Object(properties)) = variant.get_mut("properties") {
+                    properties.remove(&tag);
+                }
+                if let Some(Value::Array(required)) = variant.get_mut("required") {
+                    required.retain(|name| name.as_str() != Some(tag.as_str()));
+                }
+                let variant_name = unique(&mut names, &variant_name(&value));
+                let content =
+                    self.unique_name(&format!("{}{}", name, variant_name.replace('_', "")));
+                self.structure(&variant, scope, &content)?;
+                if variant_name != value {
+                    let _ = writeln!(definition, "    #[serde(rename = {:?})]", value);
+                }
+                let _ = writeln!(definition, "    {}({}),", variant_name, content);
+            }
+        } else {
+            definition.push_str("#[serde(untagged)]\n");
+            let _ = writeln!(definition, "pub enum {} {{", name);
+            for (idx, variant) in variants.iter().enumerate() {
+                let type_ = self.type_of(variant, base, &format!("{}Variant{}", name, idx))?;
+                let variant_name = unique(&mut names, &variant_of_type(unbox(&type_)));
+                let _ = writeln!(definition, "    {}({}),", variant_name, type_);
+            }
+        }
+        definition.push_str("}\n");
+        self.items[index] = definition;
+        Ok(())
+    }
+
+    fn base_of(&self, object: &Map<String, Value>, base: &Url) -> GenerationResult<Url> {
+        match object.get(id_keyword(self.draft)).and_then(Value::as_str) {
+            Some(id) => Ok(base.join(id)?),
+            None => Ok(base.clone()),
+        }
+    }
+
+    /// Reserve a slot for a definition, so parents are rendered before their children.
+    fn reserve(&mut self) -> usize {
+        self.items.push(String::new());
+        self.items.len() - 1
+    }
+
+    fn unique_name(&mut self, name: &str) -> String {
+        unique(&mut self.taken, name)
+    }
+}
+
+const fn ignores_ref_siblings(draft: Draft) -> bool {
+    match draft {
+        #[cfg(feature = "draft201909")]
+        Draft::Draft201909 => false,
+        _ => true,
+    }
+}
+
+fn is_struct(object: &Map<String, Value>) -> bool {
+    match object.get("allOf") {
+        Some(Value::Array(subschemas)) => subschemas.len() > 1 || object.len() > 1,
+        _ => object.contains_key("properties"),
+    }
+}
+
+/// Values of an `enum` that consists only of strings.
+fn string_enum(object: &Map<String, Value>) -> Option<Vec<&str>> {
+    match object.get("enum") {
+        Some(Value::Array(values)) if !values.is_empty() => {
+            values.iter().map(Value::as_str).collect()
+        }
+        _ => None,
+    }
+}
+
+/// The non-null variant of a `[null, T]` pair of variants.
+fn nullable(variants: &[Value]) -> Option<&Value> {
+    let is_null = |variant: &Value| {
+        variant.get("type") == Some(&Value::String("null".to_string()))
+            || variant.get("const") == Some(&Value::Null)
+    };
+    match variants {
+        [first, second] if is_null(first) => Some(second),
+        [first, second] if is_null(second) => Some(first),
+        _ => None,
+    }
+}
+
+/// A required property with a distinct constant string value in every variant.
+fn tag_of(variants: &[(Url, Value)]) -> Option<String> {
+    let (_, first) = variants.first()?;
+    let candidates = first.get("properties")?.as_object()?;
+    candidates
+        .keys()
+        .find(|candidate| {
+            let mut values = AHashSet::new();
+            variants.iter().all(|(_, variant)| {
+                let is_required = match variant.get("required") {
+                    Some(Value::Array(required)) => {
+                        required.iter().any(|name| name.as_str() == Some(candidate))
+                    }
+                    _ => false,
+                };
+                let value = variant
+                    .as_object()
+                    .and_then(|variant| tag_value(variant, candidate));
+                match value {
+                    Some(value) => is_required && values.insert(value),
+                    None => false,
+                }
+            })
+        })
+        .cloned()
+}
+
+fn tag_value<'a>(variant: &'a Map<String, Value>, tag: &str) -> Option<&'a str> {
+    let property = variant.get("properties")?.get(tag)?;
+    match property.get("const") {
+        Some(value) => value.as_str(),
+        None => match property.get("enum")?.as_array()?.as_slice() {
+            [value] => value.as_str(),
+            _ => None,
+        },
+    }
+}
+
+fn type_of_value(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "()",
+        Value::Bool(_) => "bool",
+        Value::Number(number) if number.is_i64() || number.is_u64() => "i64",
+        Value::Number(_) => "f64",
+        Value::String(_) => "String",
+        Value::Array(_) | Value::Object(_) => ANY,
+    }
+}
+
+fn common_type(values: &[Value]) -> &'static str {
+    let mut types = values.iter().map(type_of_value);
+    match types.next() {
+        Some(first) if types.all(|type_| type_ == first) => first,
+        _ => ANY,
+    }
+}
+
+fn type_name(object: &Map<String, Value>, hint: &str) -> String {
+    match object.get("title").and_then(Value::as_str) {
+        Some(title) => pascal_case(title),
+        None => hint.to_string(),
+    }
+}
+
+fn variant_of_type(type_: &str) -> String {
+    let name = match type_ {
+        "String" => "String",
+        "i64" => "Integer",
+        "f64" => "Number",
+        "bool" => "Boolean",
+        "()" => "Null",
+        ANY => "Value",
+        _ if type_.starts_with("Vec<") || type_.starts_with('(') => "Array",
+        _ if type_.contains("Map<") => "Object",
+        _ if type_.starts_with("Option<") => "Optional",
+        _ => type_,
+    };
+    name.to_string()
+}
+
+fn variant_name(value: &str) -> String {
+    match pascal_case(value).as_str() {
+        "" | "Type" => "Empty".to_string(),
+        // A keyword that can't be a raw identifier
+        "Self" => "Self_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+fn unbox(type_: &str) -> &str {
+    type_
+        .strip_prefix("Box<")
+        .and_then(|inner| inner.strip_suffix('>'))
+        .unwrap_or(type_)
+}
+
+fn optional(type_: &str) -> String {
+    format!("Option<{}>", type_)
+}
+
+fn unique(taken: &mut AHashSet<String>, name: &str) -> String {
+    let mut candidate = name.to_string();
+    let mut suffix = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{}{}", name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+/// Render `description` as a doc comment.
+fn document(output: &mut String, schema: &Value, indent: &str) {
+    if let Some(description) = schema.get("description").and_then(Value::as_str) {
+        for line in description.lines() {
+            let _ = writeln!(output, "{}/// {}", indent, line);
+        }
+    }
+}
+
+/// Empty fragments are not distinguishable from absent ones.
+fn normalize(mut url: Url) -> String {
+    if let Some("") = url.fragment() {
+        url.set_fragment(None);
+    }
+    url.to_string()
+}
+
+fn words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lowercase = false;
+    for c in value.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lowercase = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lowercase && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lowercase = c.is_lowercase() || c.is_numeric();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn pascal_case(value: &str) -> String {
+    let name: String = words(value)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+        })
+        .collect();
+    match name.chars().next() {
+        None => "Type".to_string(),
+        Some(first) if first.is_numeric() => format!("T{}", name),
+        Some(_) => name,
+    }
+}
+
+fn snake_case(value: &str) -> String {
+    let name = words(value)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    match name.as_str() {
+        "" => "field".to_string(),
+        "self" | "super" | "crate" => format!("{}_", name),
+        _ if name.starts_with(char::is_numeric) => format!("_{}", name),
+        _ if KEYWORDS.contains(&name.as_str()) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{pascal_case, rust_types, snake_case};
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn generate(schema: &Value) -> String {
+        rust_types(schema, &JSONSchema::options(), "Root").expect("Resolvable")
+    }
+
+    #[test]
+    fn structure() {
+        let code = generate(&json!({
+            "description": "A user",
+            "properties": {
+                "firstName": {"type": "string", "description": "Given name"},
+                "age": {"type": "integer"},
+                "score": {"type": ["number", "null"]},
+                "type": {"type": "boolean"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "extra": {"additionalProperties": {"type": "integer"}},
+                "address": {"properties": {"city": {"type": "string"}}, "required": ["city"]}
+            },
+            "required": ["firstName", "type"],
+            "additionalProperties": false
+        }));
+        assert_eq!(
+            code,
+            r#"// Generated from a JSON Schema. Do not edit manually.
+use serde::{Deserialize, Serialize};
+
+/// A user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Root {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<RootAddress>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<std::collections::BTreeMap<String, i64>>,
+    /// Given name
+    #[serde(rename = "firstName")]
+    pub first_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    pub r#type: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RootAddress {
+    pub city: String,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn enums() {
+        let code = generate(&json!({
+            "properties": {
+                "status": {"enum": ["active", "in-review", "Active2"]},
+                "level": {"enum": [1, 2]}
+            }
+        }));
+        assert!(code.contains(
+            r#"pub enum RootStatus {
+    #[serde(rename = "active")]
+    Active,
+    #[serde(rename = "in-review")]
+    InReview,
+    Active2,
+}"#
+        ));
+        assert!(code.contains("pub level: Option<i64>,"));
+    }
+
+    #[test]
+    fn self_variants() {
+        let code = generate(&json!({
+            "properties": {
+                "target": {"enum": ["self", "Self", "other"]},
+                "event": {
+                    "oneOf": [
+                        {"properties": {"kind": {"const": "self"}}, "required": ["kind"]},
+                        {"properties": {"kind": {"const": "other"}}, "required": ["kind"]}
+                    ]
+                }
+            }
+        }));
+        assert!(
+            code.contains(
+                r#"pub enum RootTarget {
+    #[serde(rename = "self")]
+    Self_,
+    #[serde(rename = "Self")]
+    Self_2,
+    #[serde(rename = "other")]
+    Other,
+}"#
+            ),
+            "{}",
+            code
+        );
+        assert!(code.contains("    Self_(RootEventSelf),"), "{}", code);
+        syn::parse_file(&code).expect("Valid Rust code");
+    }
+
+    #[test]
+    fn tagged_union() {
+        let code = generate(&json!({
+            "oneOf": [
+                {"properties": {"kind": {"const": "circle"}, "radius": {"type": "number"}}, "required": ["kind", "radius"]},
+                {"properties": {"kind": {"enum": ["square"]}, "side": {"type": "number"}}, "required": ["kind"]}
+            ]
+        }));
+        assert!(code.contains(
+            r#"#[serde(tag = "kind")]
+pub enum Root {
+    #[serde(rename = "circle")]
+    Circle(RootCircle),
+    #[serde(rename = "square")]
+    Square(RootSquare),
+}"#
+        ));
+        assert!(code.contains("pub struct RootCircle {\n    pub radius: f64,\n}"));
+    }
+
+    #[test]
+    fn untagged_union() {
+        let code = generate(&json!({
+            "anyOf": [{"type": "string"}, {"type": "integer"}, {"$ref": "#/definitions/point"}],
+            "definitions": {"point": {"properties": {"x": {"type": "number"}}}}
+        }));
+        assert!(code.contains(
+            r#"#[serde(untagged)]
+pub enum Root {
+    String(String),
+    Integer(i64),
+    Point(Point),
+}"#
+        ));
+    }
+
+    #[test]
+    fn nullable_union() {
+        let code = generate(&json!({
+            "properties": {"a": {"oneOf": [{"type": "null"}, {"type": "string"}]}},
+            "required": ["a"]
+        }));
+        assert!(code.contains("pub a: Option<String>,"));
+    }
+
+    #[test]
+    fn all_of() {
+        let code = generate(&json!({
+            "allOf": [
+                {"$ref": "#/definitions/base"},
+                {"properties": {"b": {"type": "string"}}, "required": ["b"]}
+            ],
+            "definitions": {"base": {"properties": {"a": {"type": "integer"}}, "required": ["a"]}}
+        }));
+        assert!(code.contains("pub struct Root {\n    pub a: i64,\n    pub b: String,\n}"));
+    }
+
+    #[test]
+    fn references() {
+        let mut options = JSONSchema::options();
+        options.with_document(
+            "http://example.com/address.json".to_string(),
+            json!({"title": "Postal address", "properties": {"city": {"type": "string"}}}),
+        );
+        let code = rust_types(
+            &json!({
+                "properties": {
+                    "home": {"$ref": "http://example.com/address.json"},
+                    "work": {"$ref": "http://example.com/address.json"},
+                    "children": {"type": "array", "items": {"$ref": "#"}},
+                    "parent": {"$ref": "#"}
+                }
+            }),
+            &options,
+            "person",
+        )
+        .expect("Resolvable");
+        assert!(code.contains("pub children: Option<Vec<Person>>,"));
+        assert!(code.contains("pub home: Option<PostalAddress>,"));
+        assert!(code.contains("pub parent: Option<Box<Person>>,"));
+        assert!(code.contains("pub work: Option<PostalAddress>,"));
+        assert_eq!(code.matches("pub struct PostalAddress").count(), 1);
+    }
+
+    #[test]
+    fn alias() {
+        let code = generate(&json!({"type": "array", "items": {"type": "integer"}}));
+        assert!(code.ends_with("pub type Root = Vec<i64>;\n"));
+    }
+
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/definitions/String"}}, "definitions": {"String": {"type": "string"}}}), "pub type String2 = String;"; "string_type")]
+    #[test_case(&json!({"properties": {"a": {"title": "Vec", "properties": {"b": {"type": "integer"}}}}}), "pub a: Option<Vec2>,"; "vec_type")]
+    #[test_case(&json!({"properties": {"a": {"title": "Option", "enum": ["x"]}}}), "pub enum Option2 {"; "option_type")]
+    #[test_case(&json!({"properties": {"a": {"title": "Box", "properties": {"b": {"$ref": "#"}}}}}), "pub b: Option<Box<Root>>,"; "box_type")]
+    #[test_case(&json!({"properties": {"a": {"title": "Self", "enum": ["x"]}}}), "pub enum Self2 {"; "self_type")]
+    fn reserved_names(schema: &Value, expected: &str) {
+        let code = generate(schema);
+        assert!(code.contains(expected), "{}", code);
+        syn::parse_file(&code).expect("Valid Rust code");
+    }
+
+    #[test]
+    fn parses() {
+        let schema = json!({
+            "properties": {
+                "name": {"type": "string"},
+                "self": {"$ref": "#"},
+                "kind": {"enum": ["a", "b-c"]},
+                "value": {"oneOf": [{"type": "integer"}, {"type": "string"}]},
+                "event": {
+                    "oneOf": [
+                        {"properties": {"type": {"const": "click"}, "x": {"type": "number"}}, "required": ["type"]},
+                        {"properties": {"type": {"const": "key"}, "code": {"type": "integer"}}, "required": ["type"]}
+                    ]
+                },
+                "extra": {"additionalProperties": {"type": "array"}}
+            },
+            "required": ["name"]
+        });
+        let code = rust_types(&schema, &JSONSchema::options(), "Serialize").expect("Resolvable");
+        assert!(code.contains("pub struct Serialize2 {"), "{}", code);
+        assert!(
+            code.contains("pub self_: Option<Box<Serialize2>>,"),
+            "{}",
+            code
+        );
+        syn::parse_file(&code).expect("Valid Rust code");
+    }
+
+    #[test]
+    fn unresolvable() {
+        let schema = json!({"$ref": "#/definitions/missing"});
+        assert!(rust_types(&schema, &JSONSchema::options(), "Root").is_err());
+    }
+
+    #[test_case("user_id", "UserId")]
+    #[test_case("HTTPServer", "Httpserver")]
+    #[test_case("postal address", "PostalAddress")]
+    #[test_case("2fa", "T2fa")]
+    #[test_case("", "Type")]
+    fn pascal_case_names(value: &str, expected: &str) {
+        assert_eq!(pascal_case(value), expected)
+    }
+
+    #[test_case("firstName", "first_name")]
+    #[test_case("type", "r#type")]
+    #[test_case("self", "self_")]
+    #[test_case("$id", "id")]
+    #[test_case("1st", "_1st")]
+    fn snake_case_names(value: &str, expected: &str) {
+        assert_eq!(snake_case(value), expected)
+    }
+}
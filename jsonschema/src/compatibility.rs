@@ -0,0 +1,1009 @@
+//! Compatibility checking between two versions of a schema.
+//!
+//! Every difference that affects validation is reported as a [`Change`] with an [`Effect`]:
+//!   - [`Effect::Narrowed`] - some instances valid against the old schema are invalid against the
+//!     new one, e.g. a new required property or a higher `minimum`. It breaks backward
+//!     compatibility: data produced for the old schema may be rejected by the new one;
+//!   - [`Effect::Widened`] - the new schema accepts more instances, e.g. a new `enum` value. It
+//!     breaks forward compatibility: data produced for the new schema may be rejected by the old
+//!     one;
+//!   - [`Effect::Changed`] - the change can't be classified, e.g. a different `pattern`. It is
+//!     treated as breaking in both directions. Changes inside `oneOf` branches are always `Changed`,
+//!     since an instance may start matching several branches or none.
+//!
+//! The check is structural: sub-schemas are compared keyword by keyword at the same locations, and
+//! `$ref`s are followed in both schemas. Annotations like `title` are ignored.
+//!
+//! ```rust
+//! use jsonschema::compatibility::{self, Effect};
+//! use serde_json::json;
+//!
+//! let old = json!({"properties": {"age": {"type": "integer", "minimum": 0}}});
+//! let new = json!({
+//!     "properties": {"age": {"type": "integer", "minimum": 18}},
+//!     "required": ["age"]
+//! });
+//! let report = compatibility::check(&old, &new).expect("All references are resolvable");
+//! assert!(!report.is_backward_compatible());
+//! assert!(report.is_forward_compatible());
+//! let paths: Vec<_> = report.changes.iter().map(|change| change.schema_path.to_string()).collect();
+//! assert_eq!(paths, vec!["/properties/age/minimum", "/required"]);
+//! assert_eq!(report.changes[0].effect, Effect::Narrowed);
+//! ```
+use crate::{
+    compilation::{options::CompilationOptions, DEFAULT_SCOPE},
+    error::ValidationError,
+    paths::{JSONPointer, PathChunk},
+    resolver::Resolver,
+    schemas::{self, id_keyword, Draft},
+};
+use serde_json::{Map, Value};
+use std::{fmt, sync::Arc};
+use url::Url;
+
+/// How a change affects the set of valid instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// Fewer instances are valid. Breaks backward compatibility.
+    Narrowed,
+    /// More instances are valid. Breaks forward compatibility.
+    Widened,
+    /// The effect is unknown. Breaks both backward & forward compatibility.
+    Changed,
+}
+
+impl Effect {
+    const fn inverted(self) -> Effect {
+        match self {
+            Effect::Narrowed => Effect::Widened,
+            Effect::Widened => Effect::Narrowed,
+            Effect::Changed => Effect::Changed,
+        }
+    }
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Effect::Narrowed => f.write_str("narrowed"),
+            Effect::Widened => f.write_str("widened"),
+            Effect::Changed => f.write_str("changed"),
+        }
+    }
+}
+
+/// A single difference between two schema versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Location of the changed keyword. Locations in the new schema are used, unless the keyword
+    /// only exists in the old one.
+    pub schema_path: JSONPointer,
+    /// How the change affects the set of valid instances.
+    pub effect: Effect,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.effect, self.schema_path, self.message)
+    }
+}
+
+/// All changes between two schema versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Changes sorted by their locations.
+    pub changes: Vec<Change>,
+}
+
+impl Report {
+    /// Whether all instances valid against the old schema are valid against the new one.
+    #[must_use]
+    pub fn is_backward_compatible(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|change| change.effect == Effect::Widened)
+    }
+
+    /// Whether all instances valid against the new schema are valid against the old one.
+    #[must_use]
+    pub fn is_forward_compatible(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|change| change.effect == Effect::Narrowed)
+    }
+}
+
+/// Compare two versions of a schema with the default options.
+pub fn check(old: &Value, new: &Value) -> Result<Report, ValidationError<'static>> {
+    check_with_options(old, new, &CompilationOptions::default())
+}
+
+/// Compare two versions of a schema. References are resolved with the documents store & draft
+/// from `options`.
+pub fn check_with_options(
+    old: &Value,
+    new: &Value,
+    options: &CompilationOptions,
+) -> Result<Report, ValidationError<'static>> {
+    let mut checker = Checker {
+        old: Document::new(old, options)?,
+        new: Document::new(new, options)?,
+        visited: Vec::new(),
+        changes: Vec::new(),
+    };
+    let (old_scope, new_scope) = (checker.old.scope.clone(), checker.new.scope.clone());
+    let mut path = Vec::new();
+    checker.compare(
+        old,
+        new,
+        &old_scope,
+        &new_scope,
+        &mut path,
+        Effect::Narrowed,
+    )?;
+    let mut changes = checker.changes;
+    changes.sort_by_cached_key(|change| change.schema_path.to_string());
+    Ok(Report { changes })
+}
+
+struct Document {
+    draft: Draft,
+    scope: Url,
+    resolver: Resolver,
+}
+
+impl Document {
+    fn new(
+        schema: &Value,
+        options: &CompilationOptions,
+    ) -> Result<Document, ValidationError<'static>> {
+        let draft = options.draft_for(schema);
+        let scope = match schemas::id_of(draft, schema) {
+            Some(url) => Url::parse(url)?,
+            None => DEFAULT_SCOPE.clone(),
+        };
+        let resolver = Resolver::new(draft, &scope, Arc::new(schema.clone()), options.store())
+            .map_err(ValidationError::into_owned)?;
+        Ok(Document {
+            draft,
+            scope,
+            resolver,
+        })
+    }
+
+    /// Follow top-level references of `schema`.
+    fn resolve(
+        &self,
+        schema: &Value,
+        base: &Url,
+    ) -> Result<(Value, Url, Vec<String>), ValidationError<'static>> {
+        let mut schema = schema.clone();
+        let mut base = base.clone();
+        let mut references = Vec::new();
+        loop {
+            if let Some(id) = schema.get(id_keyword(self.draft)).and_then(Value::as_str) {
+                base = base.join(id)?;
+            }
+            let reference = match schema.get("$ref") {
+                Some(Value::String(reference)) => reference,
+                _ => break,
+            };
+            let url = base.join(reference)?;
+            let key = url.to_string();
+            if references.contains(&key) {
+                break;
+            }
+            let (scope, target) = self
+                .resolver
+                .resolve_fragment(self.draft, &url)
+                .map_err(ValidationError::into_owned)?;
+            references.push(key);
+            schema = (*target).clone();
+            base = scope;
+        }
+        Ok((schema, base, references))
+    }
+}
+
+struct Checker {
+    old: Document,
+    new: Document,
+    // Pairs of compared references, to stop on recursive schemas
+    visited: Vec<(Vec<String>, Vec<String>)>,
+    changes: Vec<Change>,
+}
+
+/// Keywords that restrict instances from below. Higher values narrow the set of valid instances.
+const LOWER_BOUNDS: &[&str] = &[
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+    "minContains",
+];
+/// Keywords that restrict instances from above. Lower values narrow the set of valid instances.
+const UPPER_BOUNDS: &[&str] = &[
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+    "maxContains",
+];
+/// Keywords whose changes can't be classified.
+const OPAQUE: &[&str] = &[
+    "pattern",
+    "format",
+    "contentMediaType",
+    "contentEncoding",
+    "if",
+    "then",
+    "else",
+];
+const TYPES: &[&str] = &[
+    "null", "boolean", "integer", "number", "string", "array", "object",
+];
+
+impl Checker {
+    /// Compare two sub-schemas. `narrowed` is the effect of `new` accepting fewer instances than
+    /// `old` - it is inverted inside `not`.
+    fn compare(
+        &mut self,
+        old: &Value,
+        new: &Value,
+        old_base: &Url,
+        new_base: &Url,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) -> Result<(), ValidationError<'static>> {
+        let (old, old_base, old_references) = self.old.resolve(old, old_base)?;
+        let (new, new_base, new_references) = self.new.resolve(new, new_base)?;
+        if !old_references.is_empty() || !new_references.is_empty() {
+            let pair = (old_references, new_references);
+            if self.visited.contains(&pair) {
+                return Ok(());
+            }
+            self.visited.push(pair);
+        }
+        let (old, new) = match (as_object(&old), as_object(&new)) {
+            (Some(old), Some(new)) => (old, new),
+            (None, Some(_)) => {
+                self.report(
+                    path,
+                    narrowed.inverted(),
+                    "the schema no longer rejects everything",
+                );
+                return Ok(());
+            }
+            (Some(_), None) => {
+                self.report(path, narrowed, "the schema rejects everything");
+                return Ok(());
+            }
+            (None, None) => return Ok(()),
+        };
+        if old.is_empty() && new.is_empty() {
+            return Ok(());
+        }
+        self.compare_types(&old, &new, path, narrowed);
+        self.compare_required(&old, &new, path, narrowed);
+        self.compare_enum(&old, &new, path, narrowed);
+        self.compare_const(&old, &new, path, narrowed);
+        self.compare_bounds(&old, &new, path, narrowed);
+        self.compare_multiple_of(&old, &new, path, narrowed);
+        self.compare_unique_items(&old, &new, path, narrowed);
+        self.compare_opaque(&old, &new, path, narrowed);
+        self.compare_properties(&old, &new, &old_base, &new_base, path, narrowed)?;
+        self.compare_items(&old, &new, &old_base, &new_base, path, narrowed)?;
+        for keyword in &["propertyNames", "contains", "additionalItems"] {
+            self.compare_subschema(keyword, &old, &new, &old_base, &new_base, path, narrowed)?;
+        }
+        path.push("not".into());
+        match (old.get("not"), new.get("not")) {
+            (Some(old_not), Some(new_not)) => {
+                self.compare(
+                    old_not,
+                    new_not,
+                    &old_base,
+                    &new_base,
+                    path,
+                    narrowed.inverted(),
+                )?;
+            }
+            (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+            (Some(_), None) => self.report(path, narrowed.inverted(), "the keyword is removed"),
+            (None, None) => {}
+        }
+        path.pop();
+        self.compare_combinators(&old, &new, &old_base, &new_base, path, narrowed)?;
+        self.compare_dependencies(&old, &new, &old_base, &new_base, path, narrowed)
+    }
+
+    fn compare_types(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        let (old_types, new_types) = (types_of(old), types_of(new));
+        path.push("type".into());
+        for type_ in TYPES {
+            match (
+                allows_type(&old_types, type_),
+                allows_type(&new_types, type_),
+            ) {
+                (true, false) => self.report(
+                    path,
+                    narrowed,
+                    &format!("type `{}` is no longer allowed", type_),
+                ),
+                (false, true) => self.report(
+                    path,
+                    narrowed.inverted(),
+                    &format!("type `{}` is now allowed", type_),
+                ),
+                _ => {}
+            }
+        }
+        path.pop();
+    }
+
+    fn compare_required(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        let (old_required, new_required) =
+            (strings(old.get("required")), strings(new.get("required")));
+        path.push("required".into());
+        for property in &new_required {
+            if !old_required.contains(property) {
+                self.report(
+                    path,
+                    narrowed,
+                    &format!("property `{}` is now required", property),
+                );
+            }
+        }
+        for property in &old_required {
+            if !new_required.contains(property) {
+                self.report(
+                    path,
+                    narrowed.inverted(),
+                    &format!("property `{}` is no longer required", property),
+                );
+            }
+        }
+        path.pop();
+    }
+
+    fn compare_enum(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        path.push("enum".into());
+        match (old.get("enum"), new.get("enum")) {
+            (Some(Value::Array(old_values)), Some(Value::Array(new_values))) => {
+                for value in old_values {
+                    if !new_values.contains(value) {
+                        self.report(path, narrowed, &format!("value {} is removed", value));
+                    }
+                }
+                for value in new_values {
+                    if !old_values.contains(value) {
+                        self.report(
+                            path,
+                            narrowed.inverted(),
+                            &format!("value {} is added", value),
+                        );
+                    }
+                }
+            }
+            (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+            (Some(_), None) => self.report(path, narrowed.inverted(), "the keyword is removed"),
+            _ => {}
+        }
+        path.pop();
+    }
+
+    fn compare_const(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        path.push("const".into());
+        match (old.get("const"), new.get("const")) {
+            (Some(old_value), Some(new_value)) if old_value != new_value => self.report(
+                path,
+                Effect::Changed,
+                &format!("the value is changed from {} to {}", old_value, new_value),
+            ),
+            (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+            (Some(_), None) => self.report(path, narrowed.inverted(), "the keyword is removed"),
+            _ => {}
+        }
+        path.pop();
+    }
+
+    fn compare_bounds(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        for (keywords, is_lower) in &[(LOWER_BOUNDS, true), (UPPER_BOUNDS, false)] {
+            for keyword in *keywords {
+                path.push((*keyword).into());
+                match (old.get(*keyword), new.get(*keyword)) {
+                    // Draft 4 `exclusiveMinimum` & `exclusiveMaximum`
+                    (old_value, Some(Value::Bool(new_value)))
+                        if matches!(old_value, None | Some(Value::Bool(_))) =>
+                    {
+                        let old_value = old_value.and_then(Value::as_bool).unwrap_or(false);
+                        if old_value != *new_value {
+                            let effect = if *new_value {
+                                narrowed
+                            } else {
+                                narrowed.inverted()
+                            };
+                            self.report(path, effect, "the bound exclusivity is changed")
+                        }
+                    }
+                    // A Draft 4 boolean replaced by a number or vice versa
+                    (Some(Value::Bool(false)), Some(Value::Number(_))) => {
+                        self.report(path, narrowed, "the keyword is added")
+                    }
+                    (Some(Value::Number(_)), Some(Value::Bool(false))) => {
+                        self.report(path, narrowed.inverted(), "the keyword is removed")
+                    }
+                    (Some(old_value @ Value::Bool(_)), Some(new_value @ Value::Number(_)))
+                    | (Some(old_value @ Value::Number(_)), Some(new_value @ Value::Bool(_))) => {
+                        self.report(
+                            path,
+                            Effect::Changed,
+                            &format!("the value is changed from {} to {}", old_value, new_value),
+                        )
+                    }
+                    (Some(old_value), Some(new_value)) => {
+                        if let (Some(old_number), Some(new_number)) =
+                            (old_value.as_f64(), new_value.as_f64())
+                        {
+                            if (old_number - new_number).abs() > f64::EPSILON {
+                                let effect = if (new_number > old_number) == *is_lower {
+                                    narrowed
+                                } else {
+                                    narrowed.inverted()
+                                };
+                                self.report(
+                                    path,
+                                    effect,
+                                    &format!(
+                                        "the value is changed from {} to {}",
+                                        old_value, new_value
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                    (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+                    (Some(Value::Bool(false)), None) | (None, None) => {}
+                    (Some(_), None) => {
+                        self.report(path, narrowed.inverted(), "the keyword is removed")
+                    }
+                }
+                path.pop();
+            }
+        }
+    }
+
+    fn compare_multiple_of(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        path.push("multipleOf".into());
+        match (
+            old.get("multipleOf").and_then(Value::as_f64),
+            new.get("multipleOf").and_then(Value::as_f64),
+        ) {
+            (Some(old_value), Some(new_value)) if (old_value - new_value).abs() > f64::EPSILON => {
+                let effect = if is_multiple(new_value, old_value) {
+                    narrowed
+                } else if is_multiple(old_value, new_value) {
+                    narrowed.inverted()
+                } else {
+                    Effect::Changed
+                };
+                self.report(
+                    path,
+                    effect,
+                    &format!("the value is changed from {} to {}", old_value, new_value),
+                );
+            }
+            (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+            (Some(_), None) => self.report(path, narrowed.inverted(), "the keyword is removed"),
+            _ => {}
+        }
+        path.pop();
+    }
+
+    fn compare_unique_items(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        let is_unique =
+            |schema: &Map<String, Value>| schema.get("uniqueItems") == Some(&Value::Bool(true));
+        path.push("uniqueItems".into());
+        match (is_unique(old), is_unique(new)) {
+            (false, true) => self.report(path, narrowed, "items must be unique"),
+            (true, false) => self.report(path, narrowed.inverted(), "items may be duplicated"),
+            _ => {}
+        }
+        path.pop();
+    }
+
+    fn compare_opaque(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) {
+        for keyword in OPAQUE {
+            path.push((*keyword).into());
+            match (old.get(*keyword), new.get(*keyword)) {
+                (Some(old_value), Some(new_value)) if old_value != new_value => {
+                    self.report(path, Effect::Changed, "the value is changed")
+                }
+                (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+                (Some(_), None) => self.report(path, narrowed.inverted(), "the keyword is removed"),
+                _ => {}
+            }
+            path.pop();
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compare_properties(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        old_base: &Url,
+        new_base: &Url,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) -> Result<(), ValidationError<'static>> {
+        let empty = Map::new();
+        let old_properties = as_map(old.get("properties")).unwrap_or(&empty);
+        let new_properties = as_map(new.get("properties")).unwrap_or(&empty);
+        let old_additional = old
+            .get("additionalProperties")
+            .unwrap_or(&Value::Bool(true));
+        let new_additional = new
+            .get("additionalProperties")
+            .unwrap_or(&Value::Bool(true));
+        let mut names: Vec<_> = old_properties.keys().chain(new_properties.keys()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            // A property that is not listed is validated by `additionalProperties`
+            let old_property = old_properties.get(name).unwrap_or(old_additional);
+            let new_property = new_properties.get(name).unwrap_or(new_additional);
+            let keyword = if new_properties.contains_key(name) || !old_properties.contains_key(name)
+            {
+                "properties"
+            } else {
+                "additionalProperties"
+            };
+            path.push(keyword.into());
+            if keyword == "properties" {
+                path.push(name.clone().into());
+            }
+            self.compare(
+                old_property,
+                new_property,
+                old_base,
+                new_base,
+                path,
+                narrowed,
+            )?;
+            if keyword == "properties" {
+                path.pop();
+            }
+            path.pop();
+        }
+        path.push("additionalProperties".into());
+        self.compare(
+            old_additional,
+            new_additional,
+            old_base,
+            new_base,
+            path,
+            narrowed,
+        )?;
+        path.pop();
+        let old_patterns = as_map(old.get("patternProperties")).unwrap_or(&empty);
+        let new_patterns = as_map(new.get("patternProperties")).unwrap_or(&empty);
+        path.push("patternProperties".into());
+        for (pattern, new_schema) in new_patterns {
+            path.push(pattern.clone().into());
+            match old_patterns.get(pattern) {
+                Some(old_schema) => {
+                    self.compare(old_schema, new_schema, old_base, new_base, path, narrowed)?
+                }
+                None => self.report(path, narrowed, "the pattern is added"),
+            }
+            path.pop();
+        }
+        for pattern in old_patterns.keys() {
+            if !new_patterns.contains_key(pattern) {
+                path.push(pattern.clone().into());
+                self.report(path, narrowed.inverted(), "the pattern is removed");
+                path.pop();
+            }
+        }
+        path.pop();
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compare_items(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        old_base: &Url,
+        new_base: &Url,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) -> Result<(), ValidationError<'static>> {
+        path.push("items".into());
+        match (old.get("items"), new.get("items")) {
+            (Some(Value::Array(old_items)), Some(Value::Array(new_items))) => {
+                for (idx, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                    path.push(idx.into());
+                    self.compare(old_item, new_item, old_base, new_base, path, narrowed)?;
+                    path.pop();
+                }
+                if old_items.len() != new_items.len() {
+                    self.report(path, Effect::Changed, "the number of items is changed");
+                }
+            }
+            (Some(Value::Array(_)), Some(_)) | (Some(_), Some(Value::Array(_))) => {
+                self.report(path, Effect::Changed, "the keyword form is changed")
+            }
+            (old_items, new_items) => {
+                let old_items = old_items.unwrap_or(&Value::Bool(true));
+                let new_items = new_items.unwrap_or(&Value::Bool(true));
+                self.compare(old_items, new_items, old_base, new_base, path, narrowed)?;
+            }
+        }
+        path.pop();
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compare_subschema(
+        &mut self,
+        keyword: &'static str,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        old_base: &Url,
+        new_base: &Url,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) -> Result<(), ValidationError<'static>> {
+        path.push(keyword.into());
+        match (old.get(keyword), new.get(keyword)) {
+            (Some(old_schema), Some(new_schema)) => {
+                self.compare(old_schema, new_schema, old_base, new_base, path, narrowed)?
+            }
+            (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+            (Some(_), None) => self.report(path, narrowed.inverted(), "the keyword is removed"),
+            (None, None) => {}
+        }
+        path.pop();
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compare_combinators(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        old_base: &Url,
+        new_base: &Url,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) -> Result<(), ValidationError<'static>> {
+        // Adding a sub-schema to `allOf` narrows, and to `anyOf` widens
+        for (keyword, added) in &[
+            ("allOf", narrowed),
+            ("anyOf", narrowed.inverted()),
+            ("oneOf", Effect::Changed),
+        ] {
+            path.push((*keyword).into());
+            let old_schemas = as_array(old.get(*keyword));
+            let new_schemas = as_array(new.get(*keyword));
+            match (old_schemas, new_schemas) {
+                (Some(old_schemas), Some(new_schemas)) => {
+                    for (idx, (old_schema, new_schema)) in
+                        old_schemas.iter().zip(new_schemas).enumerate()
+                    {
+                        path.push(idx.into());
+                        let start = self.changes.len();
+                        self.compare(old_schema, new_schema, old_base, new_base, path, narrowed)?;
+                        if *keyword == "oneOf" {
+                            // Any change may make an instance match none or several branches
+                            for change in &mut self.changes[start..] {
+                                change.effect = Effect::Changed;
+                            }
+                        }
+                        path.pop();
+                    }
+                    if new_schemas.len() > old_schemas.len() {
+                        self.report(path, *added, "sub-schemas are added");
+                    } else if new_schemas.len() < old_schemas.len() {
+                        self.report(path, added.inverted(), "sub-schemas are removed");
+                    }
+                }
+                (None, Some(_)) => self.report(path, narrowed, "the keyword is added"),
+                (Some(_), None) => self.report(path, narrowed.inverted(), "the keyword is removed"),
+                (None, None) => {}
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compare_dependencies(
+        &mut self,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        old_base: &Url,
+        new_base: &Url,
+        path: &mut Vec<PathChunk>,
+        narrowed: Effect,
+    ) -> Result<(), ValidationError<'static>> {
+        let empty = Map::new();
+        let old_dependencies = as_map(old.get("dependencies")).unwrap_or(&empty);
+        let new_dependencies = as_map(new.get("dependencies")).unwrap_or(&empty);
+        path.push("dependencies".into());
+        for (property, new_dependency) in new_dependencies {
+            path.push(property.clone().into());
+            match (old_dependencies.get(property), new_dependency) {
+                (Some(Value::Array(old_names)), Value::Array(new_names)) => {
+                    for name in new_names {
+                        if !old_names.contains(name) {
+                            self.report(
+                                path,
+                                narrowed,
+                                &format!("property {} is now required", name),
+                            );
+                        }
+                    }
+                    for name in old_names {
+                        if !new_names.contains(name) {
+                            self.report(
+                                path,
+                                narrowed.inverted(),
+                                &format!("property {} is no longer required", name),
+                            );
+                        }
+                    }
+                }
+                (Some(old_dependency), new_dependency)
+                    if !old_dependency.is_array() && !new_dependency.is_array() =>
+                {
+                    self.compare(
+                        old_dependency,
+                        new_dependency,
+                        old_base,
+                        new_base,
+                        path,
+                        narrowed,
+                    )?
+                }
+                (Some(_), _) => {
+                    self.report(path, Effect::Changed, "the dependency form is changed")
+                }
+                (None, _) => self.report(path, narrowed, "the dependency is added"),
+            }
+            path.pop();
+        }
+        for property in old_dependencies.keys() {
+            if !new_dependencies.contains_key(property) {
+                path.push(property.clone().into());
+                self.report(path, narrowed.inverted(), "the dependency is removed");
+                path.pop();
+            }
+        }
+        path.pop();
+        Ok(())
+    }
+
+    fn report(&mut self, path: &[PathChunk], effect: Effect, message: &str) {
+        self.changes.push(Change {
+            schema_path: JSONPointer::from(path),
+            effect,
+            message: message.to_string(),
+        })
+    }
+}
+
+/// Boolean schemas as keyword maps. `false` is `None`.
+fn as_object(schema: &Value) -> Option<Map<String, Value>> {
+    match schema {
+        Value::Object(object) => Some(object.clone()),
+        Value::Bool(false) => None,
+        _ => Some(Map::new()),
+    }
+}
+
+fn as_map(value: Option<&Value>) -> Option<&Map<String, Value>> {
+    value.and_then(Value::as_object)
+}
+
+fn as_array(value: Option<&Value>) -> Option<&Vec<Value>> {
+    value.and_then(Value::as_array)
+}
+
+fn strings(value: Option<&Value>) -> Vec<&str> {
+    match value {
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Allowed types. `None` if `type` is absent.
+fn types_of(schema: &Map<String, Value>) -> Option<Vec<&str>> {
+    match schema.get("type") {
+        Some(Value::String(type_)) => Some(vec![type_.as_str()]),
+        Some(Value::Array(types)) => Some(types.iter().filter_map(Value::as_str).collect()),
+        _ => None,
+    }
+}
+
+fn allows_type(types: &Option<Vec<&str>>, type_: &str) -> bool {
+    match types {
+        None => true,
+        Some(types) => types.contains(&type_) || (type_ == "integer" && types.contains(&"number")),
+    }
+}
+
+fn is_multiple(value: f64, of: f64) -> bool {
+    let ratio = value / of;
+    (ratio - ratio.round()).abs() < f64::EPSILON * ratio.abs().max(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, Effect};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn changes(old: &Value, new: &Value) -> Vec<(String, Effect)> {
+        check(old, new)
+            .expect("Resolvable")
+            .changes
+            .into_iter()
+            .map(|change| (change.schema_path.to_string(), change.effect))
+            .collect()
+    }
+
+    #[test_case(&json!({"type": "string"}), &json!({"type": "string", "title": "Name"}))]
+    #[test_case(&json!({"minimum": 1}), &json!({"minimum": 1.0}))]
+    #[test_case(&json!({"properties": {"a": {"$ref": "#/definitions/a"}}, "definitions": {"a": {"type": "string"}}}), &json!({"properties": {"a": {"type": "string"}}}))]
+    #[test_case(&json!({"type": "number"}), &json!({"type": ["integer", "number"]}))]
+    #[test_case(&json!(true), &json!({}))]
+    fn compatible(old: &Value, new: &Value) {
+        assert_eq!(changes(old, new), vec![]);
+        assert_eq!(changes(new, old), vec![]);
+    }
+
+    #[test_case(&json!({}), &json!({"required": ["a"]}), "/required")]
+    #[test_case(&json!({"type": ["string", "null"]}), &json!({"type": "string"}), "/type")]
+    #[test_case(&json!({"type": "number"}), &json!({"type": "integer"}), "/type"; "integer")]
+    #[test_case(&json!({"minimum": 1}), &json!({"minimum": 2}), "/minimum")]
+    #[test_case(&json!({"maxLength": 10}), &json!({"maxLength": 5}), "/maxLength")]
+    #[test_case(&json!({}), &json!({"minItems": 1}), "/minItems")]
+    #[test_case(&json!({"exclusiveMaximum": 10}), &json!({"exclusiveMaximum": 5}), "/exclusiveMaximum")]
+    #[test_case(&json!({"maximum": 10}), &json!({"maximum": 10, "exclusiveMaximum": true}), "/exclusiveMaximum"; "draft 4 exclusive")]
+    #[test_case(&json!({"exclusiveMinimum": false}), &json!({"exclusiveMinimum": 5}), "/exclusiveMinimum"; "draft 4 to draft 6 exclusive")]
+    #[test_case(&json!({"multipleOf": 2}), &json!({"multipleOf": 4}), "/multipleOf")]
+    #[test_case(&json!({"enum": [1, 2]}), &json!({"enum": [1]}), "/enum")]
+    #[test_case(&json!({}), &json!({"const": 1}), "/const")]
+    #[test_case(&json!({}), &json!({"uniqueItems": true}), "/uniqueItems")]
+    #[test_case(&json!({}), &json!({"additionalProperties": false}), "/additionalProperties")]
+    #[test_case(&json!({"properties": {"a": {}}}), &json!({"properties": {"a": false}}), "/properties/a")]
+    #[test_case(&json!({}), &json!({"properties": {"a": {"type": "string"}}}), "/properties/a/type")]
+    #[test_case(&json!({"properties": {"a": {}}, "additionalProperties": false}), &json!({"additionalProperties": false}), "/additionalProperties"; "property removed")]
+    #[test_case(&json!({"items": {"type": "string"}}), &json!({"items": {"type": "string", "minLength": 1}}), "/items/minLength")]
+    #[test_case(&json!({"items": [{}, {"type": "string"}]}), &json!({"items": [{}, {"type": "string", "minLength": 1}]}), "/items/1/minLength")]
+    #[test_case(&json!({"allOf": [{}]}), &json!({"allOf": [{}, {"type": "string"}]}), "/allOf")]
+    #[test_case(&json!({"anyOf": [{"type": "string"}, {"type": "null"}]}), &json!({"anyOf": [{"type": "string"}]}), "/anyOf")]
+    #[test_case(&json!({"not": {"type": "string"}}), &json!({"not": {}}), "/not/type")]
+    #[test_case(&json!({"dependencies": {"a": ["b"]}}), &json!({"dependencies": {"a": ["b", "c"]}}), "/dependencies/a")]
+    #[test_case(&json!({"contains": {"minimum": 1}}), &json!({"contains": {"minimum": 2}}), "/contains/minimum")]
+    #[test_case(&json!({"propertyNames": {}}), &json!({"propertyNames": {"maxLength": 3}}), "/propertyNames/maxLength")]
+    fn narrowed(old: &Value, new: &Value, path: &str) {
+        let narrowed = check(old, new).expect("Resolvable");
+        assert!(!narrowed.is_backward_compatible());
+        assert!(narrowed.is_forward_compatible());
+        assert!(
+            narrowed
+                .changes
+                .iter()
+                .any(|change| change.schema_path.to_string() == path),
+            "{:?}",
+            narrowed.changes
+        );
+        // The reverse change widens
+        let widened = check(new, old).expect("Resolvable");
+        assert!(widened.is_backward_compatible());
+        assert!(!widened.is_forward_compatible());
+    }
+
+    #[test_case(&json!({"pattern": "^a"}), &json!({"pattern": "^b"}), "/pattern")]
+    #[test_case(&json!({"format": "date"}), &json!({"format": "email"}), "/format")]
+    #[test_case(&json!({"const": 1}), &json!({"const": 2}), "/const")]
+    #[test_case(&json!({"multipleOf": 2}), &json!({"multipleOf": 3}), "/multipleOf")]
+    #[test_case(&json!({"items": {}}), &json!({"items": [{}]}), "/items")]
+    #[test_case(&json!({"minimum": 5, "exclusiveMinimum": true}), &json!({"minimum": 5, "exclusiveMinimum": 5}), "/exclusiveMinimum"; "draft 4 to draft 6 exclusive")]
+    #[test_case(&json!({"maximum": 5, "exclusiveMaximum": 5}), &json!({"maximum": 5, "exclusiveMaximum": true}), "/exclusiveMaximum"; "draft 6 to draft 4 exclusive")]
+    #[test_case(&json!({"oneOf": [{"type": "string"}, {"type": "integer"}]}), &json!({"oneOf": [{"type": "string"}, {"type": ["integer", "string"]}]}), "/oneOf/1/type"; "one of widened")]
+    #[test_case(&json!({"oneOf": [{"type": "string"}, {}]}), &json!({"oneOf": [{"type": "string"}, {"minLength": 1}]}), "/oneOf/1/minLength"; "one of narrowed")]
+    fn changed(old: &Value, new: &Value, path: &str) {
+        assert_eq!(changes(old, new), vec![(path.to_string(), Effect::Changed)]);
+    }
+
+    #[test]
+    fn recursive() {
+        let old = json!({"properties": {"children": {"items": {"$ref": "#"}}}});
+        let new = json!({
+            "properties": {"children": {"items": {"$ref": "#"}}},
+            "required": ["children"]
+        });
+        // The root is compared once more via the reference, and then the recursion stops
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                (
+                    "/properties/children/items/required".to_string(),
+                    Effect::Narrowed
+                ),
+                ("/required".to_string(), Effect::Narrowed)
+            ]
+        );
+    }
+
+    #[test]
+    fn unresolvable() {
+        assert!(check(&json!({"$ref": "#/definitions/missing"}), &json!({})).is_err());
+    }
+
+    #[test]
+    fn display() {
+        let report = check(&json!({}), &json!({"required": ["a"]})).expect("Resolvable");
+        assert_eq!(
+            report.changes[0].to_string(),
+            "narrowed /required: property `a` is now required"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Compiled `$ref` targets.
+//!
+//! Every distinct reference URL is resolved & compiled once, while the schema that contains it is
+//! compiled. `$ref` keywords only store an index into the arena, so following a reference during
+//! validation needs neither locking nor allocations. A recursive reference gets the index of the
+//! slot that is still being compiled, which is filled before the compilation finishes.
+//!
+//! Targets in other documents are loaded at this point too, so remote documents are fetched when
+//! the schema is compiled. A reference that can't be resolved or compiled doesn't fail the
+//! compilation: its error is stored in the slot and reported whenever the reference is evaluated.
+use crate::{
+    compilation::{compile_validators, context::CompilationContext, options::CompilationOptions},
+    error::ValidationError,
+    resolver::Resolver,
+    schema_node::SchemaNode,
+};
+use ahash::AHashMap;
+use serde_json::Value;
+use std::{cell::RefCell, sync::Arc};
+use url::Url;
+
+type Target = Result<(SchemaNode, Arc<Value>), ValidationError<'static>>;
+
+/// Compiled targets of all `$ref` keywords of a schema.
+#[derive(Debug, Default)]
+pub(crate) struct Arena {
+    /// Compiled targets & the raw schemas they were compiled from, or why the reference can't be
+    /// resolved or compiled. `None` while the target is being compiled.
+    nodes: Vec<Option<Target>>,
+    /// Slots of the reference URLs.
+    indices: AHashMap<String, usize>,
+}
+
+impl Arena {
+    /// The compiled target at `index`.
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> Option<&SchemaNode> {
+        self.target(index).map(|(node, _)| node)
+    }
+
+    /// The compiled target at `index` & its raw schema.
+    pub(crate) fn target(&self, index: usize) -> Option<(&SchemaNode, &Value)> {
+        match self.nodes.get(index) {
+            Some(Some(Ok((node, value)))) => Some((node, value.as_ref())),
+            _ => None,
+        }
+    }
+
+    /// Why the target at `index` was not compiled.
+    pub(crate) fn error(&self, index: usize) -> Option<&ValidationError<'static>> {
+        match self.nodes.get(index) {
+            Some(Some(Err(error))) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// An arena that is being filled during compilation.
+#[derive(Debug)]
+pub(crate) struct ArenaBuilder<'a> {
+    resolver: &'a Resolver,
+    arena: RefCell<Arena>,
+}
+
+impl<'a> ArenaBuilder<'a> {
+    /// Continue filling `arena` with targets resolved by `resolver`.
+    pub(crate) const fn new(resolver: &'a Resolver, arena: Arena) -> ArenaBuilder<'a> {
+        ArenaBuilder {
+            resolver,
+            arena: RefCell::new(arena),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Arena {
+        self.arena.into_inner()
+    }
+
+    /// Compile the target of `reference` unless it is already compiled or being compiled.
+    /// Returns the index of its slot.
+    pub(crate) fn compile(&self, reference: &Url, config: &CompilationOptions) -> usize {
+        let index = {
+            let mut arena = self.arena.borrow_mut();
+            if let Some(index) = arena.indices.get(reference.as_str()) {
+                return *index;
+            }
+            let index = arena.nodes.len();
+            arena.nodes.push(None);
+            arena.indices.insert(reference.to_string(), index);
+            index
+        };
+        // Failures are reported during validation, as if the reference was compiled lazily
+        let target = match self.resolver.resolve_fragment(config.draft(), reference) {
+            Ok((scope, resolved)) => {
+                let context = CompilationContext::new(scope.into(), config, self);
+                match compile_validators(&resolved, &context) {
+                    Ok(node) => Ok((node, resolved)),
+                    Err(error) => Err(error.into_owned()),
+                }
+            }
+            Err(error) => Err(error.into_owned()),
+        };
+        self.arena.borrow_mut().nodes[index] = Some(target);
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::ValidationErrorKind, JSONSchema};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(&json!({"$ref": "#/definitions/a", "definitions": {"a": {"type": "integer"}}}), 1)]
+    #[test_case(&json!({
+        "properties": {"a": {"$ref": "#/definitions/a"}, "b": {"$ref": "#/definitions/a"}},
+        "definitions": {"a": {"type": "integer"}}
+    }), 1; "shared target")]
+    #[test_case(&json!({"properties": {"child": {"$ref": "#"}}}), 1; "recursive")]
+    #[test_case(&json!({
+        "$ref": "#/definitions/a",
+        "definitions": {"a": {"items": {"$ref": "#/definitions/b"}}, "b": {"$ref": "#/definitions/a"}}
+    }), 2; "mutually recursive")]
+    #[test_case(&json!({"$ref": "#/definitions/missing"}), 1; "unresolvable")]
+    fn compiled_once(schema: &Value, expected: usize) {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        assert_eq!(compiled.references.nodes.len(), expected);
+        assert_eq!(compiled.references.indices.len(), expected);
+    }
+
+    #[test]
+    fn recursive_validation() {
+        let schema = json!({
+            "properties": {"child": {"$ref": "#"}, "value": {"type": "integer"}}
+        });
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        assert!(compiled.is_valid(&json!({"child": {"child": {"value": 1}}})));
+        let instance = json!({"child": {"child": {"value": "a"}}});
+        let errors: Vec<_> = compiled
+            .validate(&instance)
+            .expect_err("Invalid")
+            .map(|error| error.schema_path.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec!["/properties/child/properties/child/properties/value/type"]
+        );
+    }
+
+    #[test]
+    fn schema_path_is_stable() {
+        let schema = json!({
+            "properties": {"a": {"$ref": "#/definitions/a"}},
+            "definitions": {"a": {"type": "integer"}}
+        });
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        let instance = json!({"a": "b"});
+        for _ in 0..2 {
+            let error = compiled
+                .validate(&instance)
+                .expect_err("Invalid")
+                .next()
+                .expect("An error");
+            assert_eq!(error.schema_path.to_string(), "/properties/a/type");
+        }
+    }
+
+    #[test]
+    fn unresolvable() {
+        let compiled =
+            JSONSchema::compile(&json!({"$ref": "#/definitions/missing"})).expect("Valid schema");
+        let instance = json!(1);
+        assert!(!compiled.is_valid(&instance));
+        let error = compiled
+            .validate(&instance)
+            .expect_err("Invalid")
+            .next()
+            .expect("An error");
+        assert_eq!(
+            error.to_string(),
+            "Invalid reference: json-schema:///#/definitions/missing"
+        );
+        assert!(compiled.references.error(0).is_some());
+    }
+
+    #[test]
+    fn invalid_target() {
+        let compiled = JSONSchema::options()
+            .with_document(
+                "http://example.com/invalid.json".to_string(),
+                json!({"pattern": "("}),
+            )
+            .compile(&json!({"items": {"$ref": "http://example.com/invalid.json"}}))
+            .expect("Valid schema");
+        // The stored error is reported on every evaluation
+        let instance = json!(["a", "b"]);
+        let errors: Vec<_> = compiled.validate(&instance).expect_err("Invalid").collect();
+        assert_eq!(errors.len(), 2);
+        for error in errors {
+            assert!(matches!(
+                error.kind,
+                ValidationErrorKind::InvalidRegex { .. }
+            ));
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! A serialized form of a compiled schema.
+//!
+//! Validators are trait objects and can't be serialized directly. Instead, an artifact caches the
+//! compilation inputs: the schema itself, all documents loaded while compiling it (including ones
+//! fetched from remote locations), custom meta-schemas and regex settings. Loading an artifact
+//! compiles the schema again from these inputs, but without meta-schema validation and network
+//! requests. Everything else, e.g. compiling regular expressions or resolving references, is done
+//! on every load (see the `artifacts` benchmark).
+use crate::{
+    compilation::JSONSchema,
+    ecma::RegexOptions,
+    error::ValidationError,
+    schemas::{self, Draft},
+};
+use ahash::AHashMap;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+/// Version of the artifact layout. Increment on any incompatible change.
+const FORMAT_VERSION: u64 = 1;
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A parsed artifact.
+#[derive(Debug)]
+pub(crate) struct Artifact {
+    pub(crate) draft: Draft,
+    pub(crate) schema: Value,
+    pub(crate) documents: AHashMap<String, Arc<Value>>,
+    pub(crate) meta_schemas: Vec<String>,
+    pub(crate) regex: RegexOptions,
+}
+
+pub(crate) fn serialize(schema: &JSONSchema) -> Vec<u8> {
+    // References are resolved during compilation, all their documents are already in the store
+    let config = schema.config();
+    let documents: Map<String, Value> = schema
+        .resolver
+        .documents()
+        .into_iter()
+        .map(|(id, document)| (id, document.as_ref().clone()))
+        .collect();
+    let regex = config.regex_options();
+    let artifact = json!({
+        "format": FORMAT_VERSION,
+        "version": CRATE_VERSION,
+        "draft": schemas::url_of(config.draft()),
+        "schema": schema.schema.as_ref(),
+        "documents": documents,
+        "metaSchemas": config.meta_schema_ids(),
+        "regex": {
+            "backtrackLimit": regex.backtrack_limit,
+            "sizeLimit": regex.size_limit,
+            "linearTimeOnly": regex.linear_time_only,
+        },
+    });
+    serde_json::to_vec(&artifact).expect("Serializing a `Value` never fails")
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn deserialize(bytes: &[u8]) -> Result<Artifact, ValidationError<'static>> {
+    let mut artifact: Value = serde_json::from_slice(bytes)?;
+    match artifact.get("format").and_then(Value::as_u64) {
+        Some(FORMAT_VERSION) => {}
+        Some(format) => {
+            return Err(ValidationError::incompatible_artifact(format!(
+                "format version {}, expected {}",
+                format, FORMAT_VERSION
+            )))
+        }
+        None => return Err(invalid("format")),
+    }
+    match artifact.get("version").and_then(Value::as_str) {
+        Some(CRATE_VERSION) => {}
+        Some(version) => {
+            return Err(ValidationError::incompatible_artifact(format!(
+                "produced by jsonschema {}, expected {}",
+                version, CRATE_VERSION
+            )))
+        }
+        None => return Err(invalid("version")),
+    }
+    let draft = artifact
+        .get("draft")
+        .and_then(Value::as_str)
+        .and_then(schemas::draft_from_url)
+        .ok_or_else(|| invalid("draft"))?;
+    let schema = artifact
+        .get_mut("schema")
+        .map(Value::take)
+        .ok_or_else(|| invalid("schema"))?;
+    let documents = match artifact.get_mut("documents").map(Value::take) {
+        Some(Value::Object(documents)) => documents
+            .into_iter()
+            .map(|(id, document)| (id, Arc::new(document)))
+            .collect(),
+        _ => return Err(invalid("documents")),
+    };
+    let meta_schemas = match artifact.get("metaSchemas") {
+        Some(Value::Array(ids)) => ids
+            .iter()
+            .map(|id| id.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid("metaSchemas"))?,
+        _ => return Err(invalid("metaSchemas")),
+    };
+    let regex = artifact.get("regex").ok_or_else(|| invalid("regex"))?;
+    let limit = |name| {
+        regex
+            .get(name)
+            .and_then(Value::as_u64)
+            .map(|limit| limit as usize)
+    };
+    let regex = RegexOptions {
+        backtrack_limit: limit("backtrackLimit"),
+        size_limit: limit("sizeLimit"),
+        linear_time_only: matches!(regex.get("linearTimeOnly"), Some(Value::Bool(true))),
+    };
+    Ok(Artifact {
+        draft,
+        schema,
+        documents,
+        meta_schemas,
+        regex,
+    })
+}
+
+fn invalid(field: &str) -> ValidationError<'static> {
+    ValidationError::incompatible_artifact(format!("missing or invalid `{}`", field))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::ValidationErrorKind, JSONSchema};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn artifact(schema: &Value) -> Value {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        serde_json::from_slice(&compiled.to_bytes()).expect("Valid JSON")
+    }
+
+    #[test]
+    fn round_trip() {
+        let schema = json!({
+            "properties": {
+                "name": {"type": "string", "pattern": "^[a-z]+$"},
+                "tags": {"items": {"$ref": "#/definitions/tag"}}
+            },
+            "definitions": {"tag": {"maxLength": 3}}
+        });
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        let loaded = JSONSchema::from_bytes(&compiled.to_bytes()).expect("Valid artifact");
+        for instance in &[
+            json!({"name": "abc", "tags": ["a"]}),
+            json!({"name": "ABC"}),
+            json!({"tags": ["abcd"]}),
+        ] {
+            assert_eq!(loaded.is_valid(instance), compiled.is_valid(instance));
+        }
+        assert_eq!(loaded.draft(), compiled.draft());
+    }
+
+    #[test]
+    fn documents_are_preserved() {
+        let schema = json!({"$ref": "http://example.com/name.json"});
+        let compiled = JSONSchema::options()
+            .with_document(
+                "http://example.com/name.json".to_string(),
+                json!({"type": "string"}),
+            )
+            .compile(&schema)
+            .expect("Valid schema");
+        // No documents are passed to the loading options
+        let loaded = JSONSchema::from_bytes(&compiled.to_bytes()).expect("Valid artifact");
+        assert!(loaded.is_valid(&json!("foo")));
+        assert!(!loaded.is_valid(&json!(42)));
+    }
+
+    #[test]
+    fn settings_are_preserved() {
+        let schema = json!({"exclusiveMinimum": true, "minimum": 5});
+        let compiled = JSONSchema::options()
+            .with_draft(crate::Draft::Draft4)
+            .with_regex_backtrack_limit(100)
+            .compile(&schema)
+            .expect("Valid schema");
+        let loaded = JSONSchema::from_bytes(&compiled.to_bytes()).expect("Valid artifact");
+        assert_eq!(loaded.draft(), crate::Draft::Draft4);
+        assert!(!loaded.is_valid(&json!(5)));
+        assert_eq!(loaded.config().regex_options().backtrack_limit, Some(100));
+    }
+
+    #[test]
+    fn custom_formats() {
+        fn custom(value: &str) -> bool {
+            value == "custom"
+        }
+        let schema = json!({"format": "custom"});
+        let compiled = JSONSchema::options()
+            .with_format("custom", custom)
+            .compile(&schema)
+            .expect("Valid schema");
+        // Format functions are not serialized and should be passed again
+        let loaded = JSONSchema::options()
+            .with_format("custom", custom)
+            .load(&compiled.to_bytes())
+            .expect("Valid artifact");
+        assert!(loaded.is_valid(&json!("custom")));
+        assert!(!loaded.is_valid(&json!("other")));
+    }
+
+    #[test_case("format", json!(2), "format version 2, expected 1")]
+    #[test_case("version", json!("0.1.0"), "produced by jsonschema 0.1.0, expected")]
+    #[test_case("draft", json!("unknown"), "missing or invalid `draft`")]
+    #[test_case("documents", json!([]), "missing or invalid `documents`")]
+    fn incompatible(field: &str, value: Value, expected: &str) {
+        let mut artifact = artifact(&json!({"type": "string"}));
+        artifact[field] = value;
+        let bytes = serde_json::to_vec(&artifact).expect("Valid JSON");
+        let error = JSONSchema::from_bytes(&bytes).expect_err("Incompatible artifact");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::IncompatibleArtifact { .. }
+        ));
+        assert!(error.to_string().contains(expected), "{}", error);
+    }
+
+    #[test]
+    fn malformed() {
+        let error = JSONSchema::from_bytes(b"{").expect_err("Invalid JSON");
+        assert!(matches!(error.kind, ValidationErrorKind::JSONParse { .. }));
+    }
+}
@@ -1,11 +1,12 @@
 use super::options::CompilationOptions;
 use crate::{
-    compilation::DEFAULT_SCOPE,
+    compilation::{arena::ArenaBuilder, DEFAULT_SCOPE},
+    diagnostics::{Warning, WarningKind},
     paths::{InstancePath, JSONPointer, PathChunk},
     schemas,
 };
 use serde_json::Value;
-use std::borrow::Cow;
+use std::{borrow::Cow, cell::RefCell};
 use url::{ParseError, Url};
 
 static DEFAULT_SCHEME: &str = "json-schema";
@@ -16,6 +17,10 @@ pub(crate) struct CompilationContext<'a> {
     base_uri: BaseUri<'a>,
     pub(crate) config: &'a CompilationOptions,
     pub(crate) schema_path: InstancePath<'a>,
+    // Collected warnings, if requested
+    diagnostics: Option<&'a RefCell<Vec<Warning>>>,
+    // Compiled `$ref` targets
+    references: &'a ArenaBuilder<'a>,
 }
 
 #[derive(Debug, Clone)]
@@ -73,11 +78,37 @@ impl<'a> From<&'a BaseUri<'a>> for Cow<'a, Url> {
 }
 
 impl<'a> CompilationContext<'a> {
-    pub(crate) const fn new(scope: BaseUri<'a>, config: &'a CompilationOptions) -> Self {
+    pub(crate) const fn new(
+        scope: BaseUri<'a>,
+        config: &'a CompilationOptions,
+        references: &'a ArenaBuilder<'a>,
+    ) -> Self {
         CompilationContext {
             base_uri: scope,
             config,
             schema_path: InstancePath::new(),
+            diagnostics: None,
+            references,
+        }
+    }
+
+    /// Collect warnings about ignored keywords into `diagnostics`.
+    pub(crate) const fn with_diagnostics(mut self, diagnostics: &'a RefCell<Vec<Warning>>) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    pub(crate) const fn collects_warnings(&self) -> bool {
+        self.diagnostics.is_some()
+    }
+
+    /// Report that `keyword` of the current schema is ignored.
+    pub(crate) fn warn(&self, keyword: &str, kind: WarningKind) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.borrow_mut().push(Warning {
+                schema_path: self.as_pointer_with(keyword.to_string()),
+                kind,
+            });
         }
     }
 
@@ -98,12 +129,16 @@ impl<'a> CompilationContext<'a> {
                 base_uri: self.base_uri.with_new_scope(id)?,
                 config: self.config,
                 schema_path: self.schema_path.clone(),
+                diagnostics: self.diagnostics,
+                references: self.references,
             })
         } else {
             Ok(CompilationContext {
                 base_uri: self.base_uri.clone(),
                 config: self.config,
                 schema_path: self.schema_path.clone(),
+                diagnostics: self.diagnostics,
+                references: self.references,
             })
         }
     }
@@ -115,6 +150,8 @@ impl<'a> CompilationContext<'a> {
             base_uri: self.base_uri.clone(),
             config: self.config,
             schema_path,
+            diagnostics: self.diagnostics,
+            references: self.references,
         }
     }
 
@@ -138,6 +175,11 @@ impl<'a> CompilationContext<'a> {
             .parse(reference)
     }
 
+    /// Compile the target of `reference` into the shared arena and return its index.
+    pub(crate) fn compile_reference(&self, reference: &Url) -> usize {
+        self.references.compile(reference, self.config)
+    }
+
     pub(crate) fn base_uri(&self) -> Option<Url> {
         match &self.base_uri {
             BaseUri::Known(u) => Some(u.as_ref().clone()),
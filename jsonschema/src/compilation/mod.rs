@@ -1,18 +1,33 @@
 //! Schema compilation.
 //! The main idea is to compile the input JSON Schema to a validators tree that will contain
 //! everything needed to perform such validation in runtime.
+pub(crate) mod arena;
+pub(crate) mod artifact;
 pub(crate) mod context;
 pub(crate) mod options;
 
 use crate::{
-    error::ErrorIterator, keywords, paths::InstancePath, resolver::Resolver,
-    schema_node::SchemaNode, validator::Validate, Draft, Output, ValidationError,
+    batch::{self, RecordReport},
+    diagnostics,
+    error::{self, ErrorIterator},
+    generate::Generator,
+    interrupt::Interrupt,
+    introspection::{self, Node, Visitor},
+    keywords, limits,
+    paths::InstancePath,
+    profile::Profiler,
+    references::{self, ReferenceGraph},
+    resolver::Resolver,
+    schema_node::SchemaNode,
+    validator::Validate,
+    Draft, Output, ValidationError,
 };
 use ahash::AHashMap;
+use arena::Arena;
 use context::CompilationContext;
 use options::CompilationOptions;
-use serde_json::Value;
-use std::sync::Arc;
+use serde_json::{Map, Value};
+use std::{io, sync::Arc};
 use url::Url;
 
 pub(crate) const DEFAULT_ROOT_URL: &str = "json-schema:///";
@@ -23,6 +38,7 @@ pub struct JSONSchema {
     pub(crate) schema: Arc<Value>,
     pub(crate) node: SchemaNode,
     pub(crate) resolver: Resolver,
+    pub(crate) references: Arena,
     config: CompilationOptions,
 }
 
@@ -57,10 +73,83 @@ impl JSONSchema {
         Self::options().compile(schema)
     }
 
+    /// Compile the input schema into a validation tree, taking ownership of it.
+    ///
+    /// The method is equivalent to `JSONSchema::options().compile_owned(schema)`
+    pub fn compile_owned(schema: Value) -> Result<JSONSchema, ValidationError<'static>> {
+        Self::options().compile_owned(schema)
+    }
+
+    /// Load a schema serialized with [`JSONSchema::to_bytes`].
+    ///
+    /// The method is equivalent to `JSONSchema::options().load(bytes)`
+    pub fn from_bytes(bytes: &[u8]) -> Result<JSONSchema, ValidationError<'static>> {
+        Self::options().load(bytes)
+    }
+
+    /// Serialize the inputs of this schema's compilation together with all documents it
+    /// references, so it can be compiled again without network access. See
+    /// [`CompilationOptions::load`] for details.
+    ///
+    /// Only documents loaded during compilation are included, serializing never loads documents.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// let schema = json!({"items": {"$ref": "http://example.com/name.json"}});
+    /// let compiled = JSONSchema::options()
+    ///     .with_document(
+    ///         "http://example.com/name.json".to_string(),
+    ///         json!({"type": "string"}),
+    ///     )
+    ///     .compile(&schema)
+    ///     .expect("Valid schema");
+    /// let bytes = compiled.to_bytes();
+    /// let loaded = JSONSchema::from_bytes(&bytes).expect("Valid artifact");
+    /// assert!(!loaded.is_valid(&json!([42])));
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        artifact::serialize(self)
+    }
+
     /// Run validation against `instance` and return an iterator over `ValidationError` in the error case.
     #[inline]
     pub fn validate<'a>(&'a self, instance: &'a Value) -> Result<(), ErrorIterator<'a>> {
+        self.validate_impl(instance, None)
+    }
+
+    /// Run validation against `instance`, but stop early once `interrupt` fires. In this case,
+    /// the only error is [`ValidationErrorKind::Interrupted`](crate::error::ValidationErrorKind::Interrupted).
+    /// See the [`interrupt`](crate::interrupt) module for details.
+    #[inline]
+    pub fn validate_with_interrupt<'a>(
+        &'a self,
+        instance: &'a Value,
+        interrupt: &Interrupt,
+    ) -> Result<(), ErrorIterator<'a>> {
+        self.validate_impl(instance, Some(interrupt))
+    }
+
+    fn validate_impl<'a>(
+        &'a self,
+        instance: &'a Value,
+        interrupt: Option<&Interrupt>,
+    ) -> Result<(), ErrorIterator<'a>> {
         let instance_path = InstancePath::new();
+        let limits = self.config.limits();
+        if limits.is_set() || interrupt.is_some() {
+            // Errors are collected eagerly, so the limits apply to the whole validation
+            return match limits::enforce(limits, interrupt, instance, || {
+                self.node
+                    .validate(self, instance, &instance_path)
+                    .collect::<Vec<_>>()
+            }) {
+                Ok(errors) if errors.is_empty() => Ok(()),
+                Ok(errors) => Err(Box::new(errors.into_iter())),
+                Err(abort) => Err(error::error(abort.into_error(instance))),
+            };
+        }
         let mut errors = self
             .node
             .validate(self, instance, &instance_path)
@@ -72,15 +161,95 @@ impl JSONSchema {
         }
     }
 
+    /// Run validation against `instance` and collect all errors. Unlike `validate`, errors don't
+    /// borrow the instance, and can be stored or returned from functions freely.
+    ///
+    /// How much of the instance is copied into errors is controlled by
+    /// [`CompilationOptions::with_instance_payload`].
+    ///
+    /// ```rust
+    /// # use jsonschema::{error::OwnedValidationError, JSONSchema};
+    /// # use serde_json::{json, Value};
+    /// fn check(schema: &JSONSchema, body: &str) -> Result<Value, Vec<OwnedValidationError>> {
+    ///     let instance: Value = serde_json::from_str(body).expect("Valid JSON");
+    ///     schema.validate_owned(&instance)?;
+    ///     Ok(instance)
+    /// }
+    ///
+    /// let schema = JSONSchema::compile(&json!({"required": ["id"]})).expect("Valid schema");
+    /// assert!(check(&schema, r#"{"id": 1}"#).is_ok());
+    /// assert_eq!(check(&schema, "{}").unwrap_err().len(), 1);
+    /// ```
+    pub fn validate_owned(&self, instance: &Value) -> Result<(), Vec<ValidationError<'static>>> {
+        let payload = self.config.instance_payload();
+        match self.validate(instance) {
+            Ok(()) => Ok(()),
+            Err(errors) => Err(errors.map(|error| error.into_owned_with(payload)).collect()),
+        }
+    }
+
     /// Run validation against `instance` but return a boolean result instead of an iterator.
     /// It is useful for cases, where it is important to only know the fact if the data is valid or not.
     /// This approach is much faster, than `validate`.
     #[must_use]
     #[inline]
     pub fn is_valid(&self, instance: &Value) -> bool {
+        self.is_valid_impl(instance, None)
+    }
+
+    /// Like `is_valid`, but stop early once `interrupt` fires. An interrupted validation
+    /// is considered failed.
+    #[must_use]
+    #[inline]
+    pub fn is_valid_with_interrupt(&self, instance: &Value, interrupt: &Interrupt) -> bool {
+        self.is_valid_impl(instance, Some(interrupt))
+    }
+
+    pub(crate) fn is_valid_impl(&self, instance: &Value, interrupt: Option<&Interrupt>) -> bool {
+        let limits = self.config.limits();
+        if limits.is_set() || interrupt.is_some() {
+            return limits::enforce(limits, interrupt, instance, || {
+                self.node.is_valid(self, instance)
+            })
+            .unwrap_or(false);
+        }
         self.node.is_valid(self, instance)
     }
 
+    /// Validate many independent instances and return a report for each of them, in the input
+    /// order. With the `rayon` feature enabled, instances are validated in parallel.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// let schema = JSONSchema::compile(&json!({"type": "integer"})).expect("A valid schema");
+    /// let instances = vec![json!(1), json!("foo")];
+    /// let reports = schema.validate_batch(&instances);
+    /// assert!(reports[0].is_valid());
+    /// assert!(!reports[1].is_valid());
+    /// ```
+    pub fn validate_batch<'a, I>(&self, instances: I) -> Vec<RecordReport>
+    where
+        I: IntoIterator<Item = &'a Value>,
+    {
+        batch::validate_batch(self, instances)
+    }
+
+    /// Validate a [JSON Lines](https://jsonlines.org/) input, where every non-blank line is an
+    /// independent instance. A report for each record is passed to `on_report` in the input
+    /// order, so arbitrary large inputs can be processed. With the `rayon` feature enabled,
+    /// records are validated in parallel.
+    ///
+    /// Lines that are not valid JSON are reported as invalid records; only I/O errors abort the
+    /// validation.
+    pub fn validate_json_lines<R, F>(&self, reader: R, on_report: F) -> io::Result<()>
+    where
+        R: io::BufRead,
+        F: FnMut(RecordReport),
+    {
+        batch::validate_json_lines(self, reader, on_report)
+    }
+
     /// Apply the schema and return an `Output`. No actual work is done at this point, the
     /// evaluation of the schema is deferred until a method is called on the `Output`. This is
     /// because different output formats will have different performance characteristics.
@@ -113,7 +282,17 @@ impl JSONSchema {
     /// }));
     /// ```
     pub const fn apply<'a, 'b>(&'a self, instance: &'b Value) -> Output<'a, 'b> {
-        Output::new(self, &self.node, instance)
+        Output::new(self, &self.node, instance, None)
+    }
+
+    /// Like `apply`, but the evaluation stops early once `interrupt` fires.
+    #[must_use]
+    pub fn apply_with_interrupt<'a, 'b>(
+        &'a self,
+        instance: &'b Value,
+        interrupt: &Interrupt,
+    ) -> Output<'a, 'b> {
+        Output::new(self, &self.node, instance, Some(interrupt.clone()))
     }
 
     /// The [`Draft`] which this schema was compiled against
@@ -121,6 +300,59 @@ impl JSONSchema {
         self.config.draft()
     }
 
+    /// The root node of the compiled validation tree.
+    #[must_use]
+    pub fn root(&self) -> Node<'_> {
+        Node::new(&self.node, &self.schema, &self.references)
+    }
+
+    /// Visit every node & keyword of the compiled validation tree.
+    /// See the [`introspection`](crate::introspection) module for details.
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        introspection::walk(&self.root(), visitor)
+    }
+
+    /// Create a profiler that aggregates evaluation statistics per schema location across many
+    /// validations. See the [`profile`](crate::profile) module for details.
+    #[must_use]
+    pub fn profiler(&self) -> Profiler<'_> {
+        Profiler::new(self)
+    }
+
+    /// Create a generator of valid & invalid instances for this schema.
+    /// See the [`generate`](crate::generate) module for details.
+    #[must_use]
+    pub const fn generator(&self, seed: u64) -> Generator<'_> {
+        Generator::new(self, seed)
+    }
+
+    /// List all identifiers & references of this schema and of the documents it depends on,
+    /// together with the dependency graph between the documents.
+    ///
+    /// External documents are loaded the same way as during validation.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// let schema = json!({"$id": "http://example.com/root.json", "items": {"$ref": "item.json"}});
+    /// let compiled = JSONSchema::options()
+    ///     .with_document("http://example.com/item.json".to_string(), json!({}))
+    ///     .compile(&schema)
+    ///     .expect("Valid schema");
+    /// let graph = compiled.references();
+    /// let reference = &graph.references[0];
+    /// assert_eq!(reference.uri, "http://example.com/item.json");
+    /// assert_eq!(reference.location.to_string(), "/items/$ref");
+    /// assert!(reference.resolved);
+    /// assert!(graph
+    ///     .dependents("http://example.com/item.json")
+    ///     .contains("http://example.com/root.json"));
+    /// ```
+    #[must_use]
+    pub fn references(&self) -> ReferenceGraph {
+        references::collect(self)
+    }
+
     /// The [`CompilationOptions`] that were used to compile this schema
     pub const fn config(&self) -> &CompilationOptions {
         &self.config
@@ -158,6 +390,8 @@ pub(crate) fn compile_validators<'a, 'c>(
                         }
                     })
                     .collect();
+                diagnostics::check_unmatched(&context, object, object.keys().map(String::as_str));
+                check_definitions(object, &context);
                 let mut validators = Vec::new();
                 if let Value::String(reference) = reference {
                     let validator = keywords::ref_::compile(schema, reference, &context)
@@ -188,7 +422,6 @@ pub(crate) fn compile_validators<'a, 'c>(
                     }
                     if let Some(validator) = context
                         .config
-                        .draft()
                         .get_validator(keyword)
                         .and_then(|f| f(object, subschema, &context))
                     {
@@ -206,6 +439,12 @@ pub(crate) fn compile_validators<'a, 'c>(
                     unmatched_keywords.remove("patternProperties");
                     unmatched_keywords.remove("properties");
                 }
+                diagnostics::check_unmatched(
+                    &context,
+                    object,
+                    unmatched_keywords.keys().map(String::as_str),
+                );
+                check_definitions(object, &context);
                 let unmatched_keywords = if unmatched_keywords.is_empty() {
                     None
                 } else {
@@ -222,6 +461,24 @@ pub(crate) fn compile_validators<'a, 'c>(
     }
 }
 
+/// Compile definitions only to collect warnings in them. Definitions are compiled lazily
+/// during validation if they are referenced.
+fn check_definitions(object: &Map<String, Value>, context: &CompilationContext) {
+    if !context.collects_warnings() {
+        return;
+    }
+    for keyword in &["definitions", "$defs"] {
+        if let Some(Value::Object(definitions)) = object.get(*keyword) {
+            let keyword_context = context.with_path(*keyword);
+            for (name, definition) in definitions {
+                let definition_context = keyword_context.with_path(name.clone());
+                // Errors are reported when the definition is compiled during validation
+                let _ = compile_validators(definition, &definition_context);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::JSONSchema;
@@ -297,4 +554,27 @@ mod tests {
         );
         assert_eq!(errors[1].to_string(), r#""a" is shorter than 3 characters"#);
     }
+
+    #[test]
+    fn compile_owned() {
+        let compiled = std::thread::spawn(|| {
+            JSONSchema::compile_owned(json!({"items": {"$ref": "#/definitions/a"}, "definitions": {"a": {"type": "string"}}}))
+        })
+        .join()
+        .expect("Thread panicked")
+        .expect("Valid schema");
+        assert!(compiled.is_valid(&json!(["a"])));
+        assert!(!compiled.is_valid(&json!([1])));
+    }
+
+    #[test]
+    fn compile_owned_error() {
+        let error: ValidationError<'static> =
+            JSONSchema::compile_owned(json!({"minLength": -1})).expect_err("Invalid schema");
+        assert_eq!(*error.instance, json!(-1));
+        assert_eq!(
+            error.schema_path.to_string(),
+            "/properties/minLength/allOf/0/minimum"
+        );
+    }
 }
@@ -1,15 +1,27 @@
 use crate::{
-    compilation::{compile_validators, context::CompilationContext, JSONSchema, DEFAULT_SCOPE},
+    bundle,
+    compilation::{
+        arena::{Arena, ArenaBuilder},
+        artifact, compile_validators,
+        context::CompilationContext,
+        JSONSchema, DEFAULT_SCOPE,
+    },
     content_encoding::{
         ContentEncodingCheckType, ContentEncodingConverterType,
         DEFAULT_CONTENT_ENCODING_CHECKS_AND_CONVERTERS,
     },
     content_media_type::{ContentMediaTypeCheckType, DEFAULT_CONTENT_MEDIA_TYPE_CHECKS},
+    diagnostics::Warning,
+    ecma::RegexOptions,
+    error::InstancePayload,
+    limits::Limits,
     resolver::Resolver,
-    schemas, ValidationError,
+    schemas::{self, CompileFunc},
+    vocabularies::Vocabularies,
+    ValidationError,
 };
 use ahash::AHashMap;
-use std::{fmt, sync::Arc};
+use std::{cell::RefCell, fmt, sync::Arc};
 
 const EXPECT_MESSAGE: &str = "Valid meta-schema!";
 
@@ -73,7 +85,7 @@ lazy_static::lazy_static! {
         store
     };
 
-    static ref META_SCHEMA_VALIDATORS: AHashMap<schemas::Draft, JSONSchema> = {
+    pub(crate) static ref META_SCHEMA_VALIDATORS: AHashMap<schemas::Draft, JSONSchema> = {
         let mut store = AHashMap::with_capacity(3);
         store.insert(
             schemas::Draft::Draft4,
@@ -135,7 +147,13 @@ pub struct CompilationOptions {
         AHashMap<&'static str, Option<(ContentEncodingCheckType, ContentEncodingConverterType)>>,
     store: AHashMap<String, Arc<serde_json::Value>>,
     formats: AHashMap<&'static str, fn(&str) -> bool>,
+    regex: RegexOptions,
+    meta_schemas: AHashMap<String, Arc<serde_json::Value>>,
+    vocabularies: Option<Vocabularies>,
     validate_schema: bool,
+    instance_payload: InstancePayload,
+    warnings_as_errors: bool,
+    limits: Limits,
 }
 
 impl Default for CompilationOptions {
@@ -147,6 +165,12 @@ impl Default for CompilationOptions {
             content_encoding_checks_and_converters: AHashMap::default(),
             store: AHashMap::default(),
             formats: AHashMap::default(),
+            regex: RegexOptions::default(),
+            meta_schemas: AHashMap::default(),
+            vocabularies: None,
+            instance_payload: InstancePayload::Full,
+            warnings_as_errors: false,
+            limits: Limits::default(),
         }
     }
 }
@@ -156,11 +180,84 @@ impl CompilationOptions {
         self.draft.unwrap_or_default()
     }
 
+    /// Draft that would be used to compile `schema`.
+    pub(crate) fn draft_for(&self, schema: &serde_json::Value) -> schemas::Draft {
+        self.draft
+            .or_else(|| schemas::draft_from_schema(schema))
+            .or_else(|| {
+                self.custom_meta_schema(schema)
+                    .and_then(|meta_schema| self.dialect_draft(meta_schema))
+            })
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn store(&self) -> AHashMap<String, Arc<serde_json::Value>> {
+        self.store.clone()
+    }
+
+    /// Get the compilation function for `keyword` if it is supported by the draft and belongs
+    /// to one of the vocabularies used by the schema's dialect.
+    pub(crate) fn get_validator<'a>(&self, keyword: &'a str) -> Option<CompileFunc<'a>> {
+        match &self.vocabularies {
+            Some(vocabularies) if !vocabularies.contains_keyword(keyword) => None,
+            _ => self.draft().get_validator(keyword),
+        }
+    }
+
     /// Compile `schema` into `JSONSchema` using the currently defined options.
     pub fn compile<'a>(
         &self,
         schema: &'a serde_json::Value,
     ) -> Result<JSONSchema, ValidationError<'a>> {
+        self.compile_shared(schema, Arc::new(schema.clone()), false)
+            .map(|(compiled, _)| compiled)
+    }
+
+    /// Compile `schema` and collect warnings about keywords that are ignored, e.g. unknown
+    /// keywords & formats or keywords next to `$ref`. See the
+    /// [`diagnostics`](crate::diagnostics) module for details.
+    ///
+    /// Warnings are sorted by their schema paths.
+    pub fn compile_with_diagnostics<'a>(
+        &self,
+        schema: &'a serde_json::Value,
+    ) -> Result<(JSONSchema, Vec<Warning>), ValidationError<'a>> {
+        self.compile_shared(schema, Arc::new(schema.clone()), true)
+    }
+
+    /// Compile `schema` into `JSONSchema` using the currently defined options, taking ownership
+    /// of it. Unlike `compile`, the schema is not copied, and errors don't borrow it.
+    ///
+    /// ```rust
+    /// # use jsonschema::{JSONSchema, ValidationError};
+    /// # use serde_json::json;
+    /// fn load(body: &str) -> Result<JSONSchema, ValidationError<'static>> {
+    ///     let schema = serde_json::from_str(body)?;
+    ///     JSONSchema::options().compile_owned(schema)
+    /// }
+    ///
+    /// let compiled = load(r#"{"type": "string"}"#).expect("Valid schema");
+    /// assert!(compiled.is_valid(&json!("foo")));
+    /// assert!(load(r#"{"type": 42}"#).is_err());
+    /// ```
+    pub fn compile_owned(
+        &self,
+        schema: serde_json::Value,
+    ) -> Result<JSONSchema, ValidationError<'static>> {
+        let schema = Arc::new(schema);
+        self.compile_shared(&schema, schema.clone(), false)
+            .map(|(compiled, _)| compiled)
+            .map_err(ValidationError::into_owned)
+    }
+
+    // `schema` & `schema_json` are the same document.
+    // Warnings are collected if requested or if they should be reported as errors
+    fn compile_shared<'a>(
+        &self,
+        schema: &'a serde_json::Value,
+        schema_json: Arc<serde_json::Value>,
+        collect_warnings: bool,
+    ) -> Result<(JSONSchema, Vec<Warning>), ValidationError<'a>> {
         // Draft is detected in the following precedence order:
         //   - Explicitly specified;
         //   - $schema field in the document;
@@ -173,23 +270,53 @@ impl CompilationOptions {
         // options might contain heap-related objects (ie. an HashMap) and we want the
         // memory-related operations to be explicit
         let mut config = self.clone();
+        let meta_schema = self.custom_meta_schema(schema);
         if self.draft.is_none() {
             if let Some(draft) = schemas::draft_from_schema(schema) {
                 config.with_draft(draft);
+            } else if let Some(draft) = meta_schema.and_then(|meta| self.dialect_draft(meta)) {
+                config.with_draft(draft);
             }
         }
+        if let Some(meta_schema) = meta_schema {
+            config.vocabularies =
+                Vocabularies::from_meta_schema(meta_schema).map_err(|vocabulary| {
+                    ValidationError::unknown_vocabulary(vocabulary.to_string())
+                })?;
+        }
         let draft = config.draft();
 
         let scope = match schemas::id_of(draft, schema) {
             Some(url) => url::Url::parse(url)?,
             None => DEFAULT_SCOPE.clone(),
         };
-        let schema_json = Arc::new(schema.clone());
         let resolver = Resolver::new(draft, &scope, schema_json.clone(), self.store.clone())?;
-        let context = CompilationContext::new(scope.into(), &config);
+        let diagnostics = RefCell::new(Vec::new());
+        let references = ArenaBuilder::new(&resolver, Arena::default());
+        let mut context = CompilationContext::new(scope.into(), &config, &references);
+        if collect_warnings || self.warnings_as_errors {
+            context = context.with_diagnostics(&diagnostics);
+        }
 
         if self.validate_schema {
-            if let Some(mut errors) = META_SCHEMA_VALIDATORS
+            if let Some(meta_schema) = meta_schema {
+                let mut options = self.clone();
+                options.warnings_as_errors = false;
+                let validator = options
+                    .without_schema_validation()
+                    .with_meta_schemas()
+                    .compile(meta_schema)
+                    .map_err(ValidationError::into_owned)?;
+                let error = validator.validate(schema).err().map(|mut errors| {
+                    errors
+                        .next()
+                        .expect("Should have at least one element")
+                        .into_owned()
+                });
+                if let Some(error) = error {
+                    return Err(error);
+                }
+            } else if let Some(mut errors) = META_SCHEMA_VALIDATORS
                 .get(&draft)
                 .expect("Existing draft")
                 .validate(schema)
@@ -200,13 +327,138 @@ impl CompilationOptions {
         }
 
         let node = compile_validators(schema, &context)?;
+        let references = references.finish();
+        let mut warnings = diagnostics.into_inner();
+        warnings.sort_by_key(|warning| warning.schema_path.to_string());
+        if self.warnings_as_errors {
+            if let Some(warning) = warnings.first() {
+                return Err(ValidationError::compilation_warning(schema, warning));
+            }
+        }
+
+        Ok((
+            JSONSchema {
+                schema: schema_json,
+                node,
+                resolver,
+                references,
+                config,
+            },
+            warnings,
+        ))
+    }
+
+    /// Load a schema serialized with [`JSONSchema::to_bytes`].
+    ///
+    /// The schema is compiled again, with the draft, documents, custom meta-schemas and regex
+    /// settings taken from the artifact. Meta-schema validation is skipped, as the schema was
+    /// already validated when it was compiled, and no network requests are made. Custom formats,
+    /// content media types and encodings can't be serialized and are taken from these options.
+    ///
+    /// Regular expressions are compiled and references are resolved on every load, so loading
+    /// saves only meta-schema validation & fetching remote documents. For schemas without remote
+    /// references it takes about as long as compiling them.
+    ///
+    /// Artifacts produced by a different version of this crate are rejected with the
+    /// `IncompatibleArtifact` error kind.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// fn custom(value: &str) -> bool {
+    ///     value.ends_with("42!")
+    /// }
+    ///
+    /// let schema = json!({"type": "string", "format": "custom"});
+    /// let compiled = JSONSchema::options()
+    ///     .with_format("custom", custom)
+    ///     .compile(&schema)
+    ///     .expect("Valid schema");
+    /// let bytes = compiled.to_bytes();
+    /// let loaded = JSONSchema::options()
+    ///     .with_format("custom", custom)
+    ///     .load(&bytes)
+    ///     .expect("Valid artifact");
+    /// assert!(loaded.is_valid(&json!("foo42!")));
+    /// ```
+    pub fn load(&self, bytes: &[u8]) -> Result<JSONSchema, ValidationError<'static>> {
+        let artifact = artifact::deserialize(bytes)?;
+        let mut config = self.clone();
+        config.with_draft(artifact.draft);
+        config.without_schema_validation();
+        config.regex = artifact.regex;
+        config.store = artifact.documents;
+        for id in artifact.meta_schemas {
+            if let Some(meta_schema) = config.store.get(&id) {
+                let meta_schema = meta_schema.clone();
+                config.meta_schemas.insert(id, meta_schema);
+            }
+        }
+        config.compile_owned(artifact.schema)
+    }
+
+    /// Bundle `schema` and all external documents it references via `$ref` into a single
+    /// self-contained document that validates the same way as the original one.
+    ///
+    /// External documents are loaded from the documents store (see `with_document`) or remote
+    /// locations, and embedded into `definitions` (`$defs` for Draft 2019-09) under their URLs.
+    /// Their `$id` is set to that URL, and references from the root document are rewritten
+    /// to local JSON pointers.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// let schema = json!({"items": {"$ref": "http://example.com/name.json"}});
+    /// let bundled = JSONSchema::options()
+    ///     .with_document(
+    ///         "http://example.com/name.json".to_string(),
+    ///         json!({"type": "string"}),
+    ///     )
+    ///     .bundle(&schema)
+    ///     .expect("All references are resolvable");
+    /// assert_eq!(
+    ///     bundled,
+    ///     json!({
+    ///         "items": {"$ref": "#/definitions/http:~1~1example.com~1name.json"},
+    ///         "definitions": {
+    ///             "http://example.com/name.json": {
+    ///                 "$id": "http://example.com/name.json",
+    ///                 "type": "string"
+    ///             }
+    ///         }
+    ///     })
+    /// );
+    /// ```
+    pub fn bundle(
+        &self,
+        schema: &serde_json::Value,
+    ) -> Result<serde_json::Value, ValidationError<'static>> {
+        bundle::bundle(self, schema)
+    }
 
-        Ok(JSONSchema {
-            schema: schema_json,
-            node,
-            resolver,
-            config,
-        })
+    /// Custom meta-schema referenced by the `$schema` keyword of `schema`, if any.
+    fn custom_meta_schema(&self, schema: &serde_json::Value) -> Option<&serde_json::Value> {
+        let url = schema.get("$schema")?.as_str()?;
+        self.meta_schemas
+            .get(url.trim_end_matches('#'))
+            .map(AsRef::as_ref)
+    }
+
+    /// Find the draft a custom meta-schema is based on by following its `$schema` chain.
+    fn dialect_draft(&self, meta_schema: &serde_json::Value) -> Option<schemas::Draft> {
+        let mut current = meta_schema;
+        let mut visited = Vec::new();
+        loop {
+            if let Some(draft) = schemas::draft_from_schema(current) {
+                return Some(draft);
+            }
+            let url = current.get("$schema")?.as_str()?.trim_end_matches('#');
+            if visited.contains(&url) {
+                return None;
+            }
+            visited.push(url);
+            current = self.meta_schemas.get(url)?;
+        }
     }
 
     /// Ensure that the schema is going to be compiled using the defined Draft.
@@ -418,6 +670,51 @@ impl CompilationOptions {
         self.store.insert(id, Arc::new(document));
         self
     }
+
+    /// Register a custom meta-schema. Schemas that reference it via `$schema` are validated
+    /// against it during compilation, and only keywords from the vocabularies declared in its
+    /// `$vocabulary` keyword are applied. Compilation fails if the meta-schema requires an
+    /// unknown vocabulary.
+    ///
+    /// The draft is taken from the `$schema` keyword of the meta-schema.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// // A dialect without the validation vocabulary
+    /// let meta_schema = json!({
+    ///     "$schema": "https://json-schema.org/draft/2019-09/schema",
+    ///     "$id": "https://example.com/meta",
+    ///     "$vocabulary": {
+    ///         "https://json-schema.org/draft/2019-09/vocab/core": true,
+    ///         "https://json-schema.org/draft/2019-09/vocab/applicator": true
+    ///     }
+    /// });
+    /// let schema = json!({
+    ///     "$schema": "https://example.com/meta",
+    ///     "properties": {"name": {"type": "string"}}
+    /// });
+    /// let compiled = JSONSchema::options()
+    ///     .with_meta_schema("https://example.com/meta".to_string(), meta_schema)
+    ///     .compile(&schema)
+    ///     .expect("Valid schema");
+    /// // `type` belongs to the validation vocabulary and is not applied
+    /// assert!(compiled.is_valid(&json!({"name": 42})));
+    /// ```
+    #[inline]
+    pub fn with_meta_schema(
+        &mut self,
+        mut id: String,
+        meta_schema: serde_json::Value,
+    ) -> &mut Self {
+        if id.ends_with('#') {
+            id.pop();
+        }
+        let meta_schema = Arc::new(meta_schema);
+        self.store.insert(id.clone(), meta_schema.clone());
+        self.meta_schemas.insert(id, meta_schema);
+        self
+    }
     /// Register a custom "format" validator.
     ///
     /// ## Example
@@ -450,6 +747,177 @@ impl CompilationOptions {
     pub(crate) fn format(&self, format: &str) -> FormatKV<'_> {
         self.formats.get_key_value(format)
     }
+    /// Set the maximum number of backtracking steps for regular expressions in `pattern` and
+    /// `patternProperties`. If the limit is exceeded during validation, both keywords report a
+    /// `BacktrackLimitExceeded` error: for `patternProperties` the property is considered matched
+    /// and invalid.
+    ///
+    /// ```rust
+    /// # use jsonschema::CompilationOptions;
+    /// # let mut options = CompilationOptions::default();
+    /// options.with_regex_backtrack_limit(10_000);
+    /// ```
+    #[inline]
+    pub const fn with_regex_backtrack_limit(&mut self, limit: usize) -> &mut Self {
+        self.regex.backtrack_limit = Some(limit);
+        self
+    }
+    /// Set the approximate size limit (in bytes) of compiled regular expressions.
+    /// Schemas with larger regular expressions fail to compile, and larger instances of the `regex`
+    /// format are invalid.
+    ///
+    /// ```rust
+    /// # use jsonschema::{JSONSchema, error::ValidationErrorKind};
+    /// # use serde_json::json;
+    /// let schema = json!({"properties": {"name": {"pattern": "\\w{1000}"}}});
+    /// let error = JSONSchema::options()
+    ///     .with_regex_size_limit(1000)
+    ///     .compile(&schema)
+    ///     .expect_err("Too large regex");
+    /// assert!(matches!(error.kind, ValidationErrorKind::InvalidRegex { .. }));
+    /// assert_eq!(error.schema_path.to_string(), "/properties/name/pattern");
+    /// ```
+    #[inline]
+    pub const fn with_regex_size_limit(&mut self, limit: usize) -> &mut Self {
+        self.regex.size_limit = Some(limit);
+        self
+    }
+    /// Use only the linear-time `regex` engine for `pattern` and `patternProperties`.
+    /// Schemas with regular expressions that require backtracking (look-around assertions and
+    /// back-references) fail to compile. Instances of the `regex` format that require backtracking
+    /// are invalid.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// let schema = json!({"pattern": "^(?!foo)"});
+    /// assert!(JSONSchema::options()
+    ///     .with_linear_time_regex()
+    ///     .compile(&schema)
+    ///     .is_err());
+    /// ```
+    #[inline]
+    pub const fn with_linear_time_regex(&mut self) -> &mut Self {
+        self.regex.linear_time_only = true;
+        self
+    }
+    pub(crate) const fn regex_options(&self) -> &RegexOptions {
+        &self.regex
+    }
+    /// Set how much of the instance is copied into errors returned by
+    /// [`JSONSchema::validate_owned`], [`JSONSchema::validate_batch`] and
+    /// [`JSONSchema::validate_json_lines`]. Copying the whole instance is the default.
+    ///
+    /// ```rust
+    /// # use jsonschema::{error::InstancePayload, JSONSchema};
+    /// # use serde_json::json;
+    /// let schema = JSONSchema::options()
+    ///     .with_instance_payload(InstancePayload::Omitted)
+    ///     .compile(&json!({"type": "string"}))
+    ///     .expect("Valid schema");
+    /// let errors = schema.validate_owned(&json!([1, 2, 3])).expect_err("Not a string");
+    /// assert_eq!(*errors[0].instance, json!(null));
+    /// ```
+    #[inline]
+    pub const fn with_instance_payload(&mut self, payload: InstancePayload) -> &mut Self {
+        self.instance_payload = payload;
+        self
+    }
+    /// Fail compilation with the `CompilationWarning` error kind if any warning is reported.
+    /// See [`CompilationOptions::compile_with_diagnostics`] for details.
+    ///
+    /// ```rust
+    /// # use jsonschema::{error::ValidationErrorKind, JSONSchema};
+    /// # use serde_json::json;
+    /// let schema = json!({"$ref": "#/definitions/name", "minLength": 1});
+    /// let error = JSONSchema::options()
+    ///     .with_warnings_as_errors()
+    ///     .compile(&schema)
+    ///     .expect_err("`minLength` is ignored");
+    /// assert!(matches!(error.kind, ValidationErrorKind::CompilationWarning { .. }));
+    /// assert_eq!(error.schema_path.to_string(), "/minLength");
+    /// ```
+    #[inline]
+    pub const fn with_warnings_as_errors(&mut self) -> &mut Self {
+        self.warnings_as_errors = true;
+        self
+    }
+    /// Fail validation with the `LimitExceeded` error kind if arrays & objects in the instance
+    /// are nested deeper than `limit` levels. The instance is checked before validation.
+    ///
+    /// Limits apply to `validate`, `is_valid` & `apply` of [`JSONSchema`]. If any limit is
+    /// set, `validate` collects all errors eagerly.
+    ///
+    /// ```rust
+    /// # use jsonschema::{error::{Limit, ValidationErrorKind}, JSONSchema};
+    /// # use serde_json::json;
+    /// let schema = JSONSchema::options()
+    ///     .with_max_instance_depth(2)
+    ///     .compile(&json!({}))
+    ///     .expect("Valid schema");
+    /// assert!(schema.is_valid(&json!([[1]])));
+    /// let instance = json!([[[1]]]);
+    /// let error = schema
+    ///     .validate(&instance)
+    ///     .expect_err("Too deep")
+    ///     .next()
+    ///     .expect("One error");
+    /// assert!(matches!(
+    ///     error.kind,
+    ///     ValidationErrorKind::LimitExceeded { limit: Limit::InstanceDepth(2) }
+    /// ));
+    /// ```
+    #[inline]
+    pub const fn with_max_instance_depth(&mut self, limit: usize) -> &mut Self {
+        self.limits.instance_depth = Some(limit);
+        self
+    }
+    /// Fail validation with the `LimitExceeded` error kind once more than `limit`
+    /// (sub-)schemas are evaluated. Bounds the validation time of large instances and schemas
+    /// with many applicators (e.g. `anyOf`).
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// let schema = JSONSchema::options()
+    ///     .with_max_evaluated_nodes(100)
+    ///     .compile(&json!({"items": {"type": "integer"}}))
+    ///     .expect("Valid schema");
+    /// assert!(schema.is_valid(&json!([1, 2, 3])));
+    /// assert!(!schema.is_valid(&json!(vec![1; 1000])));
+    /// ```
+    #[inline]
+    pub const fn with_max_evaluated_nodes(&mut self, limit: usize) -> &mut Self {
+        self.limits.evaluated_nodes = Some(limit);
+        self
+    }
+    /// Fail validation with the `LimitExceeded` error kind if more than `limit` `$ref`s are
+    /// expanded within each other. Prevents stack overflows caused by recursive references.
+    ///
+    /// ```rust
+    /// # use jsonschema::JSONSchema;
+    /// # use serde_json::json;
+    /// // Expands `$ref` infinitely, regardless of the instance
+    /// let schema = JSONSchema::options()
+    ///     .with_max_ref_depth(50)
+    ///     .compile(&json!({"allOf": [{"$ref": "#"}]}))
+    ///     .expect("Valid schema");
+    /// assert!(schema.validate(&json!(1)).is_err());
+    /// ```
+    #[inline]
+    pub const fn with_max_ref_depth(&mut self, limit: usize) -> &mut Self {
+        self.limits.ref_depth = Some(limit);
+        self
+    }
+    pub(crate) const fn limits(&self) -> Limits {
+        self.limits
+    }
+    pub(crate) const fn instance_payload(&self) -> InstancePayload {
+        self.instance_payload
+    }
+    pub(crate) fn meta_schema_ids(&self) -> Vec<&str> {
+        self.meta_schemas.keys().map(String::as_str).collect()
+    }
     /// Do not perform schema validation during compilation.
     /// This method is only used to disable meta-schema validation for meta-schemas itself to avoid
     /// infinite recursion.
@@ -473,6 +941,8 @@ impl fmt::Debug for CompilationOptions {
                 "content_encoding",
                 &self.content_encoding_checks_and_converters.keys(),
             )
+            .field("regex", &self.regex)
+            .field("meta_schemas", &self.meta_schemas.keys())
             .finish()
     }
 }
@@ -480,7 +950,7 @@ impl fmt::Debug for CompilationOptions {
 #[cfg(test)]
 mod tests {
     use super::CompilationOptions;
-    use crate::{schemas::Draft, JSONSchema};
+    use crate::{error::ValidationErrorKind, schemas::Draft, JSONSchema};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -527,4 +997,66 @@ mod tests {
         assert!(!compiled.is_valid(&json!("foo")));
         assert!(compiled.is_valid(&json!("foo42!")));
     }
+
+    fn dialect(vocabulary: Value) -> CompilationOptions {
+        let mut options = JSONSchema::options();
+        options.with_meta_schema(
+            "https://example.com/meta#".to_string(),
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "$id": "https://example.com/meta",
+                "$vocabulary": vocabulary,
+                "properties": {"properties": {"type": "object"}}
+            }),
+        );
+        options
+    }
+
+    #[test_case("https://example.com/meta"; "without fragment")]
+    #[test_case("https://example.com/meta#"; "with empty fragment")]
+    fn custom_meta_schema_vocabularies(url: &str) {
+        let options = dialect(json!({
+            "https://json-schema.org/draft/2019-09/vocab/core": true,
+            "https://json-schema.org/draft/2019-09/vocab/applicator": true
+        }));
+        let schema = json!({
+            "$schema": url,
+            "properties": {"name": {"type": "string"}},
+            "minProperties": 5
+        });
+        let compiled = options.compile(&schema).expect("Valid schema");
+        assert_eq!(compiled.draft(), Draft::Draft7);
+        assert!(compiled.is_valid(&json!({"name": 42})));
+        // Keywords from the applicator vocabulary are applied
+        let schema = json!({"$schema": url, "properties": {"name": false}});
+        let compiled = options.compile(&schema).expect("Valid schema");
+        assert!(!compiled.is_valid(&json!({"name": 42})));
+    }
+
+    #[test]
+    fn custom_meta_schema_without_vocabularies() {
+        let options = dialect(Value::Null);
+        let schema = json!({"$schema": "https://example.com/meta", "minProperties": 1});
+        let compiled = options.compile(&schema).expect("Valid schema");
+        assert!(!compiled.is_valid(&json!({})));
+    }
+
+    #[test]
+    fn custom_meta_schema_validation() {
+        let options = dialect(Value::Null);
+        let schema = json!({"$schema": "https://example.com/meta", "properties": 42});
+        let error = options.compile(&schema).expect_err("Invalid schema");
+        assert_eq!(error.schema_path.to_string(), "/properties/properties/type");
+    }
+
+    #[test]
+    fn custom_meta_schema_unknown_vocabulary() {
+        let options = dialect(json!({"https://example.com/vocab/custom": true}));
+        let schema = json!({"$schema": "https://example.com/meta"});
+        let error = options.compile(&schema).expect_err("Unknown vocabulary");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::UnknownVocabulary { ref vocabulary } if vocabulary == "https://example.com/vocab/custom"
+        ));
+    }
 }
@@ -0,0 +1,429 @@
+//! Schema dereferencing. Replaces every `$ref` with the schema it points to.
+//!
+//! Recursive references can't be fully expanded. Instead, such a `$ref` is rewritten to a local
+//! JSON pointer to the place where its target was already expanded in the output document, and
+//! reported as a `CircularReference`.
+use crate::{
+    compilation::{options::CompilationOptions, DEFAULT_SCOPE},
+    error::ValidationError,
+    paths::{JSONPointer, PathChunk},
+    resolver::Resolver,
+    schemas::{self, id_keyword, is_data_keyword, is_schema_map, Draft},
+};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use url::Url;
+
+/// A fully dereferenced schema.
+#[derive(Debug)]
+pub struct Dereferenced {
+    /// The schema with all non-recursive references replaced by their targets.
+    pub schema: Value,
+    /// References that point to one of their own ancestors.
+    pub circular_references: Vec<CircularReference>,
+}
+
+/// A `$ref` that would expand infinitely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircularReference {
+    /// Absolute URI of the reference target.
+    pub reference: String,
+    /// Location of the reference within the dereferenced schema.
+    pub location: JSONPointer,
+    /// Location of the expanded target within the dereferenced schema.
+    /// The `$ref` at `location` is rewritten to point there.
+    pub target: JSONPointer,
+}
+
+/// Dereference `schema` using the documents store & draft from `options`.
+///
+/// ```rust
+/// # use jsonschema::JSONSchema;
+/// # use serde_json::json;
+/// let schema = json!({
+///     "properties": {
+///         "name": {"$ref": "#/definitions/name"},
+///         "children": {"items": {"$ref": "#"}}
+///     },
+///     "definitions": {"name": {"type": "string"}}
+/// });
+/// let dereferenced = jsonschema::dereference(&schema, &JSONSchema::options())
+///     .expect("All references are resolvable");
+/// assert_eq!(dereferenced.schema["properties"]["name"], json!({"type": "string"}));
+/// // The root schema references itself
+/// assert_eq!(dereferenced.schema["properties"]["children"]["items"], json!({"$ref": "#"}));
+/// assert_eq!(
+///     dereferenced.circular_references[0].location.to_string(),
+///     "/properties/children/items"
+/// );
+/// ```
+pub fn dereference(
+    schema: &Value,
+    options: &CompilationOptions,
+) -> Result<Dereferenced, ValidationError<'static>> {
+    let draft = options.draft_for(schema);
+    let scope = match schemas::id_of(draft, schema) {
+        Some(url) => Url::parse(url)?,
+        None => DEFAULT_SCOPE.clone(),
+    };
+    let resolver = Resolver::new(draft, &scope, Arc::new(schema.clone()), options.store())
+        .map_err(ValidationError::into_owned)?;
+    let mut dereferencer = Dereferencer {
+        draft,
+        resolver,
+        stack: Vec::new(),
+        circular_references: Vec::new(),
+    };
+    let mut path = Vec::new();
+    let mut dereferenced = dereferencer.expand(schema, &scope, &scope, &mut path)?;
+    // The root `$id` is kept, so local pointers in circular references resolve against it
+    if let (Some(id), Value::Object(object)) = (schemas::id_of(draft, schema), &mut dereferenced) {
+        object.insert(id_keyword(draft).to_string(), Value::String(id.to_string()));
+    }
+    Ok(Dereferenced {
+        schema: dereferenced,
+        circular_references: dereferencer.circular_references,
+    })
+}
+
+struct Dereferencer {
+    draft: Draft,
+    resolver: Resolver,
+    // Source locations of the schemas that are being expanded & their locations in the output
+    stack: Vec<(String, Vec<PathChunk>)>,
+    circular_references: Vec<CircularReference>,
+}
+
+impl Dereferencer {
+    /// Expand `schema` located at `source` in the original documents.
+    fn expand(
+        &mut self,
+        schema: &Value,
+        base: &Url,
+        source: &Url,
+        path: &mut Vec<PathChunk>,
+    ) -> Result<Value, ValidationError<'static>> {
+        match schema {
+            Value::Object(object) => {
+                let canonical = match object.get(id_keyword(self.draft)).and_then(Value::as_str) {
+                    Some(id) => Some(base.join(id)?),
+                    None => None,
+                };
+                // The schema is reachable both via the location it was reached from and via
+                // its own `$id`, so references to either of them are circular
+                let depth = self.stack.len();
+                self.stack.push((normalize(source.clone()), path.clone()));
+                if let Some(canonical) = &canonical {
+                    self.stack
+                        .push((normalize(canonical.clone()), path.clone()));
+                }
+                let (base, source) = match canonical {
+                    Some(canonical) => (canonical.clone(), canonical),
+                    None => (base.clone(), source.clone()),
+                };
+                let expanded = self.expand_object(object, &base, &source, path);
+                self.stack.truncate(depth);
+                expanded
+            }
+            Value::Array(items) => {
+                let mut expanded = Vec::with_capacity(items.len());
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(idx.into());
+                    expanded.push(self.expand(
+                        item,
+                        base,
+                        &child(source, &idx.to_string()),
+                        path,
+                    )?);
+                    path.pop();
+                }
+                Ok(Value::Array(expanded))
+            }
+            _ => Ok(schema.clone()),
+        }
+    }
+
+    fn expand_object(
+        &mut self,
+        object: &Map<String, Value>,
+        base: &Url,
+        source: &Url,
+        path: &mut Vec<PathChunk>,
+    ) -> Result<Value, ValidationError<'static>> {
+        if let Some(Value::String(reference)) = object.get("$ref") {
+            let target = self.reference(reference, base, path)?;
+            if self.ignores_ref_siblings() || object.len() == 1 {
+                return Ok(target);
+            }
+            // Since Draft 2019-09 keywords next to `$ref` are applied as well
+            let mut siblings = object.clone();
+            siblings.remove("$ref");
+            let siblings = self.expand_keywords(&siblings, base, source, path)?;
+            let mut combined = Map::with_capacity(1);
+            combined.insert("allOf".to_string(), Value::Array(vec![target, siblings]));
+            return Ok(Value::Object(combined));
+        }
+        self.expand_keywords(object, base, source, path)
+    }
+
+    fn expand_keywords(
+        &mut self,
+        object: &Map<String, Value>,
+        base: &Url,
+        source: &Url,
+        path: &mut Vec<PathChunk>,
+    ) -> Result<Value, ValidationError<'static>> {
+        let mut expanded = Map::with_capacity(object.len());
+        for (key, value) in object {
+            match key.as_str() {
+                // Identifiers are meaningless once all references are expanded
+                keyword if keyword == id_keyword(self.draft) && value.is_string() => {}
+                keyword if is_data_keyword(keyword) => {
+                    expanded.insert(key.clone(), value.clone());
+                }
+                keyword => {
+                    path.push(key.clone().into());
+                    let source = child(source, key);
+                    let value = match value {
+                        // Keys of these objects are names, not keywords
+                        Value::Object(members) if is_schema_map(keyword) => {
+                            self.expand_members(members, base, &source, path)?
+                        }
+                        _ => self.expand(value, base, &source, path)?,
+                    };
+                    expanded.insert(key.clone(), value);
+                    path.pop();
+                }
+            }
+        }
+        Ok(Value::Object(expanded))
+    }
+
+    fn expand_members(
+        &mut self,
+        members: &Map<String, Value>,
+        base: &Url,
+        source: &Url,
+        path: &mut Vec<PathChunk>,
+    ) -> Result<Value, ValidationError<'static>> {
+        let mut expanded = Map::with_capacity(members.len());
+        for (name, member) in members {
+            path.push(name.clone().into());
+            let member = self.expand(member, base, &child(source, name), path)?;
+            expanded.insert(name.clone(), member);
+            path.pop();
+        }
+        Ok(Value::Object(expanded))
+    }
+
+    fn reference(
+        &mut self,
+        reference: &str,
+        base: &Url,
+        path: &mut Vec<PathChunk>,
+    ) -> Result<Value, ValidationError<'static>> {
+        let url = base.join(reference)?;
+        let key = normalize(url.clone());
+        if let Some((_, target)) = self.stack.iter().find(|(seen, _)| seen == &key) {
+            let target = JSONPointer::from(target.as_slice());
+            let mut circular = Map::with_capacity(1);
+            circular.insert(
+                "$ref".to_string(),
+                Value::String(format!("#{}", target).replace('%', "%25")),
+            );
+            self.circular_references.push(CircularReference {
+                reference: key,
+                location: JSONPointer::from(path.as_slice()),
+                target,
+            });
+            return Ok(Value::Object(circular));
+        }
+        let (scope, resolved) = self
+            .resolver
+            .resolve_fragment(self.draft, &url)
+            .map_err(ValidationError::into_owned)?;
+        self.expand(&resolved, &scope, &url, path)
+    }
+
+    const fn ignores_ref_siblings(&self) -> bool {
+        match self.draft {
+            #[cfg(feature = "draft201909")]
+            Draft::Draft201909 => false,
+            _ => true,
+        }
+    }
+}
+
+/// Location of the `key` member of the value at `source`.
+fn child(source: &Url, key: &str) -> Url {
+    let token = key
+        .replace('~', "~0")
+        .replace('/', "~1")
+        .replace('%', "%25");
+    let mut child = source.clone();
+    child.set_fragment(Some(&format!(
+        "{}/{}",
+        source.fragment().unwrap_or(""),
+        token
+    )));
+    child
+}
+
+/// Empty fragments are not distinguishable from absent ones.
+fn normalize(mut url: Url) -> String {
+    if let Some("") = url.fragment() {
+        url.set_fragment(None);
+    }
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dereference;
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(
+        &json!({"items": {"$ref": "#/definitions/a"}, "definitions": {"a": {"type": "string"}}}),
+        &json!({"items": {"type": "string"}, "definitions": {"a": {"type": "string"}}})
+    )]
+    #[test_case(
+        &json!({"items": {"$ref": "#/definitions/a"}, "definitions": {"a": {"$ref": "#/definitions/b"}, "b": {"minimum": 1}}}),
+        &json!({"items": {"minimum": 1}, "definitions": {"a": {"minimum": 1}, "b": {"minimum": 1}}})
+    )]
+    #[test_case(
+        &json!({"items": {"$ref": "http://example.com/name.json"}}),
+        &json!({"items": {"type": "string"}})
+    )]
+    #[test_case(
+        &json!({"enum": [{"$ref": "#/definitions/a"}], "definitions": {"a": {}}}),
+        &json!({"enum": [{"$ref": "#/definitions/a"}], "definitions": {"a": {}}})
+    )]
+    #[test_case(
+        &json!({"default": {"$ref": "#/definitions/a"}, "examples": [{"$ref": "#/x"}], "definitions": {"a": {}}}),
+        &json!({"default": {"$ref": "#/definitions/a"}, "examples": [{"$ref": "#/x"}], "definitions": {"a": {}}})
+    )]
+    #[test_case(
+        &json!({"properties": {"default": {"$ref": "#/definitions/a"}}, "definitions": {"a": {"type": "string"}}}),
+        &json!({"properties": {"default": {"type": "string"}}, "definitions": {"a": {"type": "string"}}})
+    )]
+    fn expands_references(schema: &Value, expected: &Value) {
+        let mut options = JSONSchema::options();
+        options.with_document(
+            "http://example.com/name.json".to_string(),
+            json!({"$id": "http://example.com/name.json", "type": "string"}),
+        );
+        let dereferenced = dereference(schema, &options).expect("Resolvable");
+        assert_eq!(&dereferenced.schema, expected);
+        assert!(dereferenced.circular_references.is_empty());
+    }
+
+    #[test]
+    fn circular_references() {
+        let schema = json!({
+            "properties": {"tree": {"$ref": "#/definitions/node"}},
+            "definitions": {
+                "node": {
+                    "type": "object",
+                    "properties": {"children": {"items": {"$ref": "#/definitions/node"}}}
+                }
+            }
+        });
+        let dereferenced = dereference(&schema, &JSONSchema::options()).expect("Resolvable");
+        assert_eq!(
+            dereferenced.schema["properties"]["tree"],
+            json!({
+                "type": "object",
+                "properties": {"children": {"items": {"$ref": "#/properties/tree"}}}
+            })
+        );
+        assert_eq!(
+            dereferenced.schema["definitions"]["node"],
+            json!({
+                "type": "object",
+                "properties": {"children": {"items": {"$ref": "#/definitions/node"}}}
+            })
+        );
+        let locations: Vec<_> = dereferenced
+            .circular_references
+            .iter()
+            .map(|circular| (circular.location.to_string(), circular.target.to_string()))
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                (
+                    "/definitions/node/properties/children/items".to_string(),
+                    "/definitions/node".to_string()
+                ),
+                (
+                    "/properties/tree/properties/children/items".to_string(),
+                    "/properties/tree".to_string()
+                )
+            ]
+        );
+        assert_eq!(
+            dereferenced.circular_references[0].reference,
+            "json-schema:///#/definitions/node"
+        );
+        // The output is still a valid schema that validates the same way
+        let compiled = JSONSchema::compile(&dereferenced.schema).expect("Valid schema");
+        assert!(compiled.is_valid(&json!({"tree": {"children": [{"children": []}]}})));
+        assert!(!compiled.is_valid(&json!({"tree": {"children": [{"children": [1]}]}})));
+    }
+
+    #[test]
+    fn circular_references_through_ids() {
+        // The reference points back to the node via the root document, while the node itself is
+        // identified by its own `$id`
+        let schema = json!({
+            "$id": "http://x.com/root.json",
+            "items": {"$ref": "#/definitions/node"},
+            "definitions": {
+                "node": {
+                    "$id": "http://x.com/node.json",
+                    "items": {"$ref": "http://x.com/root.json#/definitions/node"}
+                }
+            }
+        });
+        let dereferenced = dereference(&schema, &JSONSchema::options()).expect("Resolvable");
+        assert_eq!(
+            dereferenced.schema,
+            json!({
+                "$id": "http://x.com/root.json",
+                "items": {"items": {"$ref": "#/items"}},
+                "definitions": {"node": {"items": {"$ref": "#/definitions/node"}}}
+            })
+        );
+        assert_eq!(dereferenced.circular_references.len(), 2);
+        let compiled = JSONSchema::compile(&dereferenced.schema).expect("Valid schema");
+        assert!(compiled.is_valid(&json!([[[]]])));
+    }
+
+    #[test_case(&json!({
+        "$schema": "http://json-schema.org/draft-04/schema#",
+        "properties": {"id": {"type": "integer"}, "name": {"$ref": "#/definitions/name"}},
+        "definitions": {"name": {"type": "string"}}
+    }), "id"; "draft 4")]
+    #[test_case(&json!({
+        "properties": {"$id": {"type": "integer"}, "name": {"$ref": "#/definitions/name"}},
+        "definitions": {"name": {"type": "string"}}
+    }), "$id"; "draft 7")]
+    fn keeps_properties_named_like_id(schema: &Value, name: &str) {
+        let dereferenced = dereference(schema, &JSONSchema::options()).expect("Resolvable");
+        assert_eq!(
+            dereferenced.schema["properties"],
+            json!({name: {"type": "integer"}, "name": {"type": "string"}})
+        );
+        let compiled = JSONSchema::compile(&dereferenced.schema).expect("Valid schema");
+        assert!(!compiled.is_valid(&json!({name: "a"})));
+        assert!(compiled.is_valid(&json!({name: 1, "name": "a"})));
+    }
+
+    #[test]
+    fn unresolvable() {
+        let schema = json!({"$ref": "#/definitions/missing"});
+        assert!(dereference(&schema, &JSONSchema::options()).is_err());
+    }
+}
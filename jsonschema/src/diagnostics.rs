@@ -0,0 +1,293 @@
+//! Warnings about parts of a schema that are silently ignored during compilation.
+//!
+//! Warnings are reported for sub-schemas that are a part of the compiled tree and for
+//! `definitions` (`$defs` in Draft 2019-09). External documents are not checked.
+//!
+//! ```rust
+//! use jsonschema::{diagnostics::WarningKind, JSONSchema};
+//! use serde_json::json;
+//!
+//! let schema = json!({
+//!     "properties": {"name": {"type": "string", "maxLenght": 5}}
+//! });
+//! let (compiled, warnings) = JSONSchema::options()
+//!     .compile_with_diagnostics(&schema)
+//!     .expect("Valid schema");
+//! assert_eq!(warnings[0].schema_path.to_string(), "/properties/name/maxLenght");
+//! assert_eq!(
+//!     warnings[0].kind,
+//!     WarningKind::UnknownKeyword { keyword: "maxLenght".to_string() }
+//! );
+//! ```
+use crate::{compilation::context::CompilationContext, paths::JSONPointer};
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Keywords that are never compiled into validators, but are not ignored by mistake.
+const ANNOTATIONS: &[&str] = &[
+    "$anchor",
+    "$comment",
+    "$defs",
+    "$id",
+    "$recursiveAnchor",
+    "$recursiveRef",
+    "$schema",
+    "$vocabulary",
+    "contentEncoding",
+    "contentMediaType",
+    "contentSchema",
+    "default",
+    "definitions",
+    "deprecated",
+    "description",
+    "examples",
+    "id",
+    "readOnly",
+    "title",
+    "writeOnly",
+];
+
+/// A part of a schema that is ignored during compilation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Location of the ignored keyword.
+    pub schema_path: JSONPointer,
+    /// What is ignored.
+    pub kind: WarningKind,
+}
+
+/// Kinds of compilation warnings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+    /// The keyword is not defined by the draft or by the vocabularies of the schema's dialect.
+    UnknownKeyword {
+        /// Keyword name.
+        keyword: String,
+    },
+    /// The format is neither built-in for the draft, nor registered via
+    /// [`CompilationOptions::with_format`](crate::CompilationOptions::with_format).
+    UnknownFormat {
+        /// Format name.
+        format: String,
+    },
+    /// Keywords next to `$ref` are ignored.
+    IgnoredRefSibling {
+        /// Keyword name.
+        keyword: String,
+    },
+    /// `then` or `else` are ignored without `if`.
+    MissingIf {
+        /// Keyword name.
+        keyword: String,
+    },
+    /// `additionalItems` is ignored unless `items` is an array.
+    AdditionalItemsWithoutItemsArray,
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningKind::UnknownKeyword { keyword } => {
+                write!(f, "Unknown keyword \"{}\" is ignored", keyword)
+            }
+            WarningKind::UnknownFormat { format } => {
+                write!(f, "Unknown format \"{}\" is ignored", format)
+            }
+            WarningKind::IgnoredRefSibling { keyword } => {
+                write!(f, "\"{}\" is ignored next to \"$ref\"", keyword)
+            }
+            WarningKind::MissingIf { keyword } => {
+                write!(f, "\"{}\" is ignored without \"if\"", keyword)
+            }
+            WarningKind::AdditionalItemsWithoutItemsArray => f.write_str(
+                "\"additionalItems\" is ignored unless \"items\" is an array of schemas",
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.schema_path, self.kind)
+    }
+}
+
+/// Report keywords of `object` that were not compiled into validators.
+/// If the object contains `$ref`, all other keywords are ignored.
+pub(crate) fn check_unmatched<'k>(
+    context: &CompilationContext,
+    object: &Map<String, Value>,
+    unmatched: impl Iterator<Item = &'k str>,
+) {
+    if !context.collects_warnings() {
+        return;
+    }
+    let config = context.config;
+    let is_reference = object.contains_key("$ref");
+    for keyword in unmatched {
+        let is_known = config.get_validator(keyword).is_some();
+        let kind = match keyword {
+            "$ref" => continue,
+            _ if is_reference && is_known => WarningKind::IgnoredRefSibling {
+                keyword: keyword.to_string(),
+            },
+            "format" if is_known => match object.get(keyword) {
+                Some(Value::String(format)) => WarningKind::UnknownFormat {
+                    format: format.clone(),
+                },
+                _ => continue,
+            },
+            "then" | "else" if config.get_validator("if").is_some() => {
+                if is_reference {
+                    WarningKind::IgnoredRefSibling {
+                        keyword: keyword.to_string(),
+                    }
+                } else {
+                    WarningKind::MissingIf {
+                        keyword: keyword.to_string(),
+                    }
+                }
+            }
+            "additionalItems" if is_known => WarningKind::AdditionalItemsWithoutItemsArray,
+            // Draft 4 boolean exclusive bounds are compiled together with `maximum` & `minimum`
+            "exclusiveMaximum" | "exclusiveMinimum" if !is_known => continue,
+            _ if is_known || ANNOTATIONS.contains(&keyword) => continue,
+            _ => WarningKind::UnknownKeyword {
+                keyword: keyword.to_string(),
+            },
+        };
+        context.warn(keyword, kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Warning, WarningKind};
+    use crate::{error::ValidationErrorKind, Draft, JSONSchema};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn warnings(schema: &Value, draft: Draft) -> Vec<(String, WarningKind)> {
+        let (_, warnings) = JSONSchema::options()
+            .with_draft(draft)
+            .compile_with_diagnostics(schema)
+            .expect("Valid schema");
+        warnings
+            .into_iter()
+            .map(|Warning { schema_path, kind }| (schema_path.to_string(), kind))
+            .collect()
+    }
+
+    fn keyword(keyword: &str) -> String {
+        keyword.to_string()
+    }
+
+    #[test_case(
+        &json!({"properties": {"a": {"minimum": 1, "foo": 1}}}),
+        "/properties/a/foo",
+        WarningKind::UnknownKeyword { keyword: keyword("foo") }
+    )]
+    #[test_case(
+        &json!({"items": {"format": "custom"}}),
+        "/items/format",
+        WarningKind::UnknownFormat { format: keyword("custom") }
+    )]
+    #[test_case(
+        &json!({"$ref": "#/definitions/a", "minLength": 1, "title": "A", "definitions": {"a": {}}}),
+        "/minLength",
+        WarningKind::IgnoredRefSibling { keyword: keyword("minLength") }
+    )]
+    #[test_case(
+        &json!({"$ref": "#/definitions/a", "definitions": {"a": {"bar": 1}}}),
+        "/definitions/a/bar",
+        WarningKind::UnknownKeyword { keyword: keyword("bar") };
+        "unknown keyword in definitions"
+    )]
+    #[test_case(
+        &json!({"not": {"then": {}}}),
+        "/not/then",
+        WarningKind::MissingIf { keyword: keyword("then") }
+    )]
+    #[test_case(
+        &json!({"items": {}, "additionalItems": false}),
+        "/additionalItems",
+        WarningKind::AdditionalItemsWithoutItemsArray
+    )]
+    #[test_case(
+        &json!({"additionalItems": {}}),
+        "/additionalItems",
+        WarningKind::AdditionalItemsWithoutItemsArray;
+        "without items"
+    )]
+    fn single_warning(schema: &Value, path: &str, expected: WarningKind) {
+        assert_eq!(
+            warnings(schema, Draft::Draft7),
+            vec![(path.to_string(), expected)]
+        );
+    }
+
+    #[test_case(&json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "$id": "http://example.com/root.json",
+        "$comment": "Comment",
+        "title": "Title",
+        "description": "Description",
+        "default": 1,
+        "examples": [1],
+        "readOnly": true,
+        "if": {}, "then": {}, "else": {},
+        "items": [{}], "additionalItems": false,
+        "properties": {"a": {}}, "additionalProperties": false,
+        "format": "email",
+        "contentMediaType": "application/json"
+    }), Draft::Draft7)]
+    #[test_case(&json!({"maximum": 5, "exclusiveMaximum": true, "id": "http://example.com"}), Draft::Draft4)]
+    fn no_warnings(schema: &Value, draft: Draft) {
+        assert_eq!(warnings(schema, draft), vec![]);
+    }
+
+    #[test]
+    fn not_supported_by_draft() {
+        // `then` & `else` are dropped together with `if`
+        assert_eq!(
+            warnings(&json!({"if": {}, "then": {}}), Draft::Draft4),
+            vec![(
+                "/if".to_string(),
+                WarningKind::UnknownKeyword {
+                    keyword: keyword("if")
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn custom_format() {
+        let (_, warnings) = JSONSchema::options()
+            .with_format("custom", |_| true)
+            .compile_with_diagnostics(&json!({"format": "custom"}))
+            .expect("Valid schema");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn warnings_as_errors() {
+        let schema = json!({"properties": {"a": {"maxLenght": 5}}});
+        let mut options = JSONSchema::options();
+        options.with_warnings_as_errors();
+        let error = options.compile(&schema).expect_err("Unknown keyword");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::CompilationWarning {
+                warning: WarningKind::UnknownKeyword { .. }
+            }
+        ));
+        assert_eq!(error.schema_path.to_string(), "/properties/a/maxLenght");
+        assert_eq!(*error.instance, json!(5));
+        assert_eq!(
+            error.to_string(),
+            r#"Unknown keyword "maxLenght" is ignored"#
+        );
+        assert!(options.compile_with_diagnostics(&schema).is_err());
+        assert!(options.compile(&json!({"maxLength": 5})).is_ok());
+    }
+}
@@ -0,0 +1,428 @@
+//! Translation of ECMA-262 regular expressions into the `fancy_regex` syntax.
+//!
+//! JSON Schema requires regular expressions in `pattern`, `patternProperties` and the `regex`
+//! format to follow the ECMA-262 dialect. Patterns are interpreted as if the `u` flag is set,
+//! therefore escaped letters that have no meaning in ECMA-262 (e.g. `\a` or `\z`) are rejected.
+//! As in Annex B, any escaped ASCII punctuation character matches itself (e.g. `\-` or `\@`).
+//!
+//! The translation covers:
+//!   - ASCII-only `\d`, `\w` and their negations, ECMA-262 whitespace for `\s` / `\S`;
+//!   - `.` that does not match line terminators (`\n`, `\r`, U+2028 and U+2029);
+//!   - `\cX` control escapes, `\0`, `\uXXXX` (including surrogate pairs) and `\u{X...}`;
+//!   - `\b` inside character classes (backspace), `[]` and `[^]`;
+//!   - characters that are special only inside `regex` character classes (`[`, `&`, `~`);
+//!   - `-` next to `\d`, `\w`, `\s` or their negations in character classes (e.g. `[\w-.]`),
+//!     which is a literal as in Annex B.
+//!
+//! Not supported:
+//!   - look-behind assertions of variable length;
+//!   - `\b` and `\B` use Unicode word characters instead of ASCII ones;
+//!   - Unicode property escapes (`\p{...}`) that are unknown to the `regex` crate;
+//!   - other Annex B extensions, e.g. octal escapes or unbalanced `{` treated as a literal.
+use fancy_regex::Error;
+use std::{char, fmt, fmt::Write};
+
+const DIGIT: &str = "0-9";
+const WORD: &str = "A-Za-z0-9_";
+const WHITESPACE: &str = r"\t\n\x{0b}\x{0c}\r \x{a0}\x{1680}\x{2000}-\x{200a}\x{2028}\x{2029}\x{202f}\x{205f}\x{3000}\x{feff}";
+const NOT_LINE_TERMINATOR: &str = r"[^\n\r\x{2028}\x{2029}]";
+const ANY: &str = r"(?s:.)";
+const NOTHING: &str = r"[^\x{0}-\x{10ffff}]";
+
+/// Limits & the engine choice for compiling regular expressions.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RegexOptions {
+    pub(crate) backtrack_limit: Option<usize>,
+    pub(crate) size_limit: Option<usize>,
+    pub(crate) linear_time_only: bool,
+}
+
+#[derive(Debug)]
+enum Engine {
+    Backtracking(fancy_regex::Regex),
+    Linear(regex::Regex),
+}
+
+/// A compiled ECMA-262 regular expression.
+#[derive(Debug)]
+pub(crate) struct Regex {
+    original: String,
+    engine: Engine,
+}
+
+impl Regex {
+    /// Compile an ECMA-262 regular expression.
+    /// In the linear-time only mode, patterns that require backtracking (look-around assertions
+    /// and back-references) are rejected.
+    pub(crate) fn with_options(pattern: &str, options: &RegexOptions) -> Result<Regex, Error> {
+        let translated = translate(pattern)?;
+        let engine = if options.linear_time_only {
+            let mut builder = regex::RegexBuilder::new(&translated);
+            if let Some(limit) = options.size_limit {
+                builder.size_limit(limit);
+            }
+            Engine::Linear(builder.build().map_err(Error::InnerError)?)
+        } else {
+            let mut builder = fancy_regex::RegexBuilder::new(&translated);
+            if let Some(limit) = options.backtrack_limit {
+                builder.backtrack_limit(limit);
+            }
+            if let Some(limit) = options.size_limit {
+                builder.delegate_size_limit(limit);
+            }
+            Engine::Backtracking(builder.build()?)
+        };
+        Ok(Regex {
+            original: pattern.to_string(),
+            engine,
+        })
+    }
+
+    /// Check if the regex matches the input text.
+    #[inline]
+    pub(crate) fn is_match(&self, text: &str) -> Result<bool, Error> {
+        match &self.engine {
+            Engine::Backtracking(regex) => regex.is_match(text),
+            Engine::Linear(regex) => Ok(regex.is_match(text)),
+        }
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.original.fmt(f)
+    }
+}
+
+/// Convert an ECMA-262 regular expression into an equivalent `fancy_regex` one.
+pub(crate) fn translate(pattern: &str) -> Result<String, Error> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::with_capacity(pattern.len());
+    let mut idx = 0;
+    let mut in_class = false;
+    while let Some(&current) = chars.get(idx) {
+        idx += 1;
+        match current {
+            '\\' => idx = translate_escape(&chars, idx, in_class, &mut out)?,
+            '[' if in_class => out.push_str(r"\["),
+            // A range can't start or end with a set, therefore the hyphen is a literal
+            '-' if in_class && is_set_escape(&chars, idx) => out.push_str(r"\-"),
+            '&' | '~' if in_class => {
+                out.push('\\');
+                out.push(current)
+            }
+            ']' if in_class => {
+                in_class = false;
+                out.push(current)
+            }
+            '[' => match (chars.get(idx), chars.get(idx + 1)) {
+                (Some(']'), _) => {
+                    out.push_str(NOTHING);
+                    idx += 1;
+                }
+                (Some('^'), Some(']')) => {
+                    out.push_str(ANY);
+                    idx += 2;
+                }
+                _ => {
+                    in_class = true;
+                    out.push('[')
+                }
+            },
+            '.' if !in_class => out.push_str(NOT_LINE_TERMINATOR),
+            '(' if !in_class && chars.get(idx) == Some(&'?') => {
+                let group: String = chars[idx..].iter().take(3).collect();
+                if !(group.starts_with("?:")
+                    || group.starts_with("?=")
+                    || group.starts_with("?!")
+                    || group.starts_with("?<"))
+                {
+                    // Inline flags & other extensions are not a part of ECMA-262
+                    return Err(Error::UnknownFlag(group));
+                }
+                out.push(current)
+            }
+            _ => out.push(current),
+        }
+    }
+    if in_class {
+        return Err(Error::InvalidClass);
+    }
+    Ok(out)
+}
+
+/// Translate an escape sequence. `idx` points to the character right after `\`.
+/// Returns the index of the first character after the escape sequence.
+fn translate_escape(
+    chars: &[char],
+    idx: usize,
+    in_class: bool,
+    out: &mut String,
+) -> Result<usize, Error> {
+    let next = *chars.get(idx).ok_or(Error::TrailingBackslash)?;
+    let idx = idx + 1;
+    match next {
+        'd' | 'D' | 'w' | 'W' | 's' | 'S' => {
+            let set = match next.to_ascii_lowercase() {
+                'd' => DIGIT,
+                'w' => WORD,
+                _ => WHITESPACE,
+            };
+            push_set(out, set, next.is_ascii_uppercase(), in_class);
+            if in_class && chars.get(idx) == Some(&'-') {
+                out.push_str(r"\-");
+                return Ok(idx + 1);
+            }
+        }
+        // Backspace inside character classes
+        'b' if in_class => out.push_str(r"\x{08}"),
+        'c' => match chars.get(idx) {
+            Some(letter) if letter.is_ascii_alphabetic() => {
+                push_codepoint(out, u32::from(*letter) % 32);
+                return Ok(idx + 1);
+            }
+            _ => return Err(Error::InvalidEscape(r"\c".to_string())),
+        },
+        '0' if !matches!(chars.get(idx), Some(c) if c.is_ascii_digit()) => push_codepoint(out, 0),
+        '1'..='9' if !in_class => {
+            // Back-reference
+            out.push('\\');
+            out.push(next)
+        }
+        'x' => {
+            let value = parse_hex(chars, idx, 2).ok_or(Error::InvalidHex)?;
+            push_codepoint(out, value);
+            return Ok(idx + 2);
+        }
+        'u' => return translate_unicode_escape(chars, idx, out),
+        'k' | 'p' | 'P' if matches!(chars.get(idx), Some('<') | Some('{')) => {
+            // Named back-references & Unicode property escapes are passed as is
+            let close = if chars[idx] == '<' { '>' } else { '}' };
+            let end = chars[idx..]
+                .iter()
+                .position(|c| *c == close)
+                .ok_or(Error::ParseError)?;
+            out.push('\\');
+            out.push(next);
+            out.extend(&chars[idx..=idx + end]);
+            return Ok(idx + end + 1);
+        }
+        // Assertions, character escapes and escaped syntax characters
+        'b' | 'B' | 'f' | 'n' | 'r' | 't' | 'v' | '^' | '$' | '\\' | '.' | '*' | '+' | '?'
+        | '(' | ')' | '[' | ']' | '{' | '}' | '|' => {
+            out.push('\\');
+            out.push(next)
+        }
+        // Identity escapes
+        _ if next.is_ascii_punctuation() => push_codepoint(out, u32::from(next)),
+        _ => return Err(Error::InvalidEscape(format!("\\{}", next))),
+    }
+    Ok(idx)
+}
+
+/// `\uXXXX` or `\u{X...}`. `idx` points to the character right after `u`.
+fn translate_unicode_escape(chars: &[char], idx: usize, out: &mut String) -> Result<usize, Error> {
+    if chars.get(idx) == Some(&'{') {
+        let end = chars[idx..]
+            .iter()
+            .position(|c| *c == '}')
+            .ok_or(Error::InvalidHex)?;
+        if !(2..=7).contains(&end) {
+            return Err(Error::InvalidHex);
+        }
+        let value = parse_hex(chars, idx + 1, end - 1).ok_or(Error::InvalidHex)?;
+        if char::from_u32(value).is_none() {
+            return Err(Error::InvalidCodepointValue);
+        }
+        push_codepoint(out, value);
+        return Ok(idx + end + 1);
+    }
+    let high = parse_hex(chars, idx, 4).ok_or(Error::InvalidHex)?;
+    let idx = idx + 4;
+    if (0xD800..0xDC00).contains(&high)
+        && chars.get(idx) == Some(&'\\')
+        && chars.get(idx + 1) == Some(&'u')
+    {
+        if let Some(low) = parse_hex(chars, idx + 2, 4) {
+            if (0xDC00..0xE000).contains(&low) {
+                // Surrogate pair
+                push_codepoint(out, 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00));
+                return Ok(idx + 6);
+            }
+        }
+    }
+    if char::from_u32(high).is_none() {
+        // Lone surrogates can't be present in Rust strings
+        return Err(Error::InvalidCodepointValue);
+    }
+    push_codepoint(out, high);
+    Ok(idx)
+}
+
+/// Whether `\d`, `\w`, `\s` or their negations start at `idx`.
+fn is_set_escape(chars: &[char], idx: usize) -> bool {
+    chars.get(idx) == Some(&'\\')
+        && matches!(
+            chars.get(idx + 1),
+            Some('d') | Some('D') | Some('w') | Some('W') | Some('s') | Some('S')
+        )
+}
+
+fn parse_hex(chars: &[char], start: usize, length: usize) -> Option<u32> {
+    let digits = chars.get(start..start + length)?;
+    if !digits.iter().all(char::is_ascii_hexdigit) {
+        return None;
+    }
+    u32::from_str_radix(&digits.iter().collect::<String>(), 16).ok()
+}
+
+fn push_codepoint(out: &mut String, value: u32) {
+    write!(out, r"\x{{{:x}}}", value).expect("Writing to a string never fails");
+}
+
+fn push_set(out: &mut String, set: &str, negated: bool, in_class: bool) {
+    if in_class && !negated {
+        out.push_str(set);
+    } else {
+        // Negated sets inside classes are nested classes, which are supported by `regex`
+        out.push('[');
+        if negated {
+            out.push('^');
+        }
+        out.push_str(set);
+        out.push(']');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Regex, RegexOptions};
+    use test_case::test_case;
+
+    #[test_case(r"^[\w\-\.\+]+$", "CC-BY-4.0", true)]
+    #[test_case(r"^[\w\-\.\+]+$", "CC-BY-!", false)]
+    #[test_case(r"^\W+$", "1_0", false)]
+    #[test_case(r"\\w", r"\w", true)]
+    // Cases from the optional `ecmascript-regex` tests of the JSON Schema test suite
+    #[test_case(r"^abc$", "abc\n", false; "dollar matches only the end of the string")]
+    #[test_case(r"^\cC$", "\u{3}", true; "control escape")]
+    #[test_case(r"^\cc$", "\u{3}", true; "lowercase control escape")]
+    #[test_case(r"^\d$", "0", true; "ASCII digit")]
+    #[test_case(r"^\d$", "߀", false; "NKO digit zero is not a digit")]
+    #[test_case(r"^\D$", "߀", true; "NKO digit zero is a non-digit")]
+    #[test_case(r"^\w$", "a", true; "ASCII word")]
+    #[test_case(r"^\w$", "é", false; "latin-1 letter is not a word character")]
+    #[test_case(r"^\W$", "é", true; "latin-1 letter is a non-word character")]
+    #[test_case(r"^\s$", " ", true; "space")]
+    #[test_case(r"^\s$", "\t", true; "tab")]
+    #[test_case(r"^\s$", "\u{b}", true; "line tabulation")]
+    #[test_case(r"^\s$", "\u{c}", true; "form feed")]
+    #[test_case(r"^\s$", "\u{a0}", true; "latin-1 non-breaking space")]
+    #[test_case(r"^\s$", "\u{feff}", true; "zero-width no-break space")]
+    #[test_case(r"^\s$", "\u{2029}", true; "paragraph separator")]
+    #[test_case(r"^\s$", "\u{2003}", true; "em space")]
+    #[test_case(r"^\s$", "\u{3000}", true; "ideographic space")]
+    #[test_case(r"^\S$", "\u{3000}", false; "ideographic space is not a non-whitespace")]
+    #[test_case(r"^\s$", "\u{180e}", false; "mongolian vowel separator is not whitespace")]
+    #[test_case(r"^[\s\d]+$", "1 2", true; "sets in class")]
+    #[test_case(r"^[^\d]$", "1", false; "negated class with set")]
+    #[test_case(r"^[\D]$", "a", true; "negated set in class")]
+    #[test_case(r"^.$", "\n", false; "dot does not match new line")]
+    #[test_case(r"^.$", "\u{2028}", false; "dot does not match line separator")]
+    #[test_case(r"^[^]$", "\n", true; "any character")]
+    #[test_case(r"[]", "a", false; "empty class matches nothing")]
+    #[test_case(r"^[\b]$", "\u{8}", true; "backspace in class")]
+    #[test_case(r"^\u00e9$", "é", true; "unicode escape")]
+    #[test_case(r"^\u{1F600}$", "😀", true; "unicode code point escape")]
+    #[test_case(r"^\uD83D\uDE00$", "😀", true; "surrogate pair")]
+    #[test_case(r"^\x41\0$", "A\u{0}", true; "hex & null escapes")]
+    #[test_case(r"^\/$", "/", true; "escaped slash")]
+    #[test_case(r"^[[]$", "[", true; "bracket in class")]
+    #[test_case(r"^[a&&b]+$", "a&", true; "ampersands in class")]
+    #[test_case(r"^(?<x>a)\k<x>$", "aa", true; "named back-reference")]
+    #[test_case(r"^(a)\1$", "aa", true; "back-reference")]
+    #[test_case(r"^(?!eo:)", "eo:bands", false; "negative look-ahead")]
+    #[test_case(r"^\p{Lu}$", "Á", true; "unicode property")]
+    // Identity escapes of ASCII punctuation
+    #[test_case(r"^\-x$", "-x", true; "escaped hyphen")]
+    #[test_case(r"^a\@b$", "a@b", true; "escaped at sign")]
+    #[test_case(r"^\:$", ":", true; "escaped colon")]
+    #[test_case(r"^\'$", "'", true; "escaped apostrophe")]
+    #[test_case(r#"^\"$"#, "\"", true; "escaped quote")]
+    #[test_case(r"^\#$", "#", true; "escaped hash")]
+    #[test_case(r"^[\#\:]+$", "#:", true; "escaped punctuation in class")]
+    #[test_case(r"^[a-z\-]+$", "a-b", true; "escaped hyphen in class")]
+    #[test_case(r"^\d{3}\-\d{4}$", "555-1234", true; "escaped hyphen between digits")]
+    #[test_case(r"^[\w-.]+$", "a-b.c", true; "hyphen after set in class")]
+    #[test_case(r"^[\w-.]+$", "a,b", false; "hyphen after set in class is not a range")]
+    #[test_case(r"^[.-\d]+$", "-.1", true; "hyphen before set in class")]
+    #[test_case(r"^[.-\d]+$", "/", false; "hyphen before set in class is not a range")]
+    fn regex_matches(pattern: &str, text: &str, is_matching: bool) {
+        let compiled =
+            Regex::with_options(pattern, &RegexOptions::default()).expect("A valid regex");
+        assert_eq!(
+            compiled.is_match(text).expect("A valid pattern"),
+            is_matching
+        );
+    }
+
+    #[test_case(r"\")]
+    #[test_case(r"\d\")]
+    #[test_case(r"^\a$"; "not a control escape")]
+    #[test_case(r"\z")]
+    #[test_case(r"\c1")]
+    #[test_case(r"(?i)a"; "inline flags")]
+    #[test_case(r"\uD83D"; "lone surrogate")]
+    #[test_case(r"\u{110000}")]
+    #[test_case(r"[a"; "unclosed class")]
+    fn invalid_patterns(pattern: &str) {
+        assert!(Regex::with_options(pattern, &RegexOptions::default()).is_err())
+    }
+
+    #[test_case(r"^(?!eo:)"; "look-ahead")]
+    #[test_case(r"(?<=a)b"; "look-behind")]
+    #[test_case(r"^(a)\1$"; "back-reference")]
+    fn linear_time_only_rejects_backtracking(pattern: &str) {
+        let options = RegexOptions {
+            linear_time_only: true,
+            ..RegexOptions::default()
+        };
+        assert!(Regex::with_options(pattern, &RegexOptions::default()).is_ok());
+        assert!(Regex::with_options(pattern, &options).is_err());
+    }
+
+    #[test]
+    fn linear_time_only() {
+        let options = RegexOptions {
+            linear_time_only: true,
+            ..RegexOptions::default()
+        };
+        let regex = Regex::with_options(r"^\d+\.\w$", &options).expect("A valid regex");
+        assert!(regex.is_match("42.a").expect("Never fails"));
+        assert!(!regex.is_match("42a").expect("Never fails"));
+    }
+
+    #[test]
+    fn size_limit() {
+        let options = RegexOptions {
+            size_limit: Some(100),
+            ..RegexOptions::default()
+        };
+        assert!(Regex::with_options(r"\w{1000}", &options).is_err());
+        let options = RegexOptions {
+            linear_time_only: true,
+            ..options
+        };
+        assert!(Regex::with_options(r"\w{1000}", &options).is_err());
+    }
+
+    #[test]
+    fn backtrack_limit() {
+        let options = RegexOptions {
+            backtrack_limit: Some(10),
+            ..RegexOptions::default()
+        };
+        let regex = Regex::with_options(r"^(a|ab)*(?=c)", &options).expect("A valid regex");
+        assert!(regex.is_match(&"ab".repeat(100)).is_err());
+    }
+}
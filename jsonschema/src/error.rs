@@ -1,5 +1,6 @@
 //! Error types
 use crate::{
+    diagnostics::{Warning, WarningKind},
     paths::JSONPointer,
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
 };
@@ -27,6 +28,30 @@ pub struct ValidationError<'a> {
     pub schema_path: JSONPointer,
 }
 
+/// A validation error that doesn't borrow the validated instance.
+pub type OwnedValidationError = ValidationError<'static>;
+
+/// How much of the instance is copied into errors converted with
+/// [`ValidationError::into_owned_with`].
+///
+/// Error messages are built from the copied instance, so they are less detailed if it is
+/// truncated or omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstancePayload {
+    /// Copy the whole instance.
+    Full,
+    /// Keep at most `max_length` characters of strings and `max_length` items of arrays &
+    /// objects. Arrays & objects nested deeper than `max_depth` levels are emptied.
+    Truncated {
+        /// Maximum nesting level of arrays & objects.
+        max_depth: usize,
+        /// Maximum number of characters in strings and items in arrays & objects.
+        max_length: usize,
+    },
+    /// Replace the instance with `null`.
+    Omitted,
+}
+
 /// An iterator over instances of `ValidationError` that represent validation error for the
 /// input instance.
 ///
@@ -70,6 +95,8 @@ pub enum ValidationErrorKind {
     AnyOf,
     /// Results from a [`fancy_regex::Error::BacktrackLimitExceeded`] variant when matching
     BacktrackLimitExceeded { error: fancy_regex::Error },
+    /// A part of the schema is ignored and warnings are treated as errors.
+    CompilationWarning { warning: WarningKind },
     /// The input value doesn't match expected constant.
     Constant { expected_value: Value },
     /// The input array doesn't contain items conforming to the specified schema.
@@ -96,8 +123,17 @@ pub enum ValidationErrorKind {
     Utf8 { error: Utf8Error },
     /// May happen during ref resolution when remote document is not a valid JSON.
     JSONParse { error: serde_json::Error },
+    /// A serialized compiled schema is malformed or was produced by a different crate version.
+    IncompatibleArtifact { reason: String },
+    /// A validation limit is exceeded. The validation is aborted and other errors are not reported.
+    LimitExceeded { limit: Limit },
+    /// The validation is interrupted by a deadline or a cancellation token. Other errors are not
+    /// reported.
+    Interrupted { reason: Interruption },
     /// `ref` value is not valid.
     InvalidReference { reference: String },
+    /// A regular expression in the schema is invalid or violates the configured limits.
+    InvalidRegex { error: fancy_regex::Error },
     /// Invalid URL, e.g. invalid port number or IP address
     InvalidURL { error: url::ParseError },
     /// Too many items in an array.
@@ -117,7 +153,7 @@ pub enum ValidationErrorKind {
     /// Not enough properties in an object.
     MinProperties { limit: u64 },
     /// When some number is not a multiple of another number.
-    MultipleOf { multiple_of: f64 },
+    MultipleOf { multiple_of: Value },
     /// Negated schema failed validation.
     Not { schema: Value },
     /// The given schema is valid under more than one of the given schemas.
@@ -143,6 +179,51 @@ pub enum ValidationErrorKind {
     UniqueItems,
     /// Reference contains unknown scheme.
     UnknownReferenceScheme { scheme: String },
+    /// Meta-schema requires a vocabulary that is not supported.
+    UnknownVocabulary { vocabulary: String },
+}
+
+/// Limits on resources used by a validation.
+/// See [`CompilationOptions::with_max_instance_depth`](crate::CompilationOptions::with_max_instance_depth),
+/// [`CompilationOptions::with_max_evaluated_nodes`](crate::CompilationOptions::with_max_evaluated_nodes) and
+/// [`CompilationOptions::with_max_ref_depth`](crate::CompilationOptions::with_max_ref_depth).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Maximum nesting level of arrays & objects in the instance.
+    InstanceDepth(usize),
+    /// Maximum number of evaluated (sub-)schemas.
+    EvaluatedNodes(usize),
+    /// Maximum number of nested `$ref` expansions.
+    RefDepth(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::InstanceDepth(limit) => write!(f, "instance depth of {}", limit),
+            Limit::EvaluatedNodes(limit) => write!(f, "{} evaluated schemas", limit),
+            Limit::RefDepth(limit) => write!(f, "$ref depth of {}", limit),
+        }
+    }
+}
+
+/// Why a validation was interrupted.
+/// See [`Interrupt`](crate::interrupt::Interrupt).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interruption {
+    /// The deadline has passed.
+    Timeout,
+    /// The cancellation token was cancelled.
+    Cancelled,
+}
+
+impl fmt::Display for Interruption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Interruption::Timeout => f.write_str("deadline exceeded"),
+            Interruption::Cancelled => f.write_str("cancelled"),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -152,9 +233,206 @@ pub enum TypeKind {
     Multiple(PrimitiveTypesBitMap),
 }
 
+impl ValidationErrorKind {
+    fn duplicate(&self) -> ValidationErrorKind {
+        match self {
+            ValidationErrorKind::AdditionalItems { limit } => {
+                ValidationErrorKind::AdditionalItems { limit: *limit }
+            }
+            ValidationErrorKind::AdditionalProperties { unexpected } => {
+                ValidationErrorKind::AdditionalProperties {
+                    unexpected: unexpected.clone(),
+                }
+            }
+            ValidationErrorKind::AnyOf => ValidationErrorKind::AnyOf,
+            ValidationErrorKind::BacktrackLimitExceeded { error } => {
+                ValidationErrorKind::BacktrackLimitExceeded {
+                    error: duplicate_regex_error(error),
+                }
+            }
+            ValidationErrorKind::CompilationWarning { warning } => {
+                ValidationErrorKind::CompilationWarning {
+                    warning: warning.clone(),
+                }
+            }
+            ValidationErrorKind::Constant { expected_value } => ValidationErrorKind::Constant {
+                expected_value: expected_value.clone(),
+            },
+            ValidationErrorKind::Contains => ValidationErrorKind::Contains,
+            ValidationErrorKind::ContentEncoding { content_encoding } => {
+                ValidationErrorKind::ContentEncoding {
+                    content_encoding: content_encoding.clone(),
+                }
+            }
+            ValidationErrorKind::ContentMediaType { content_media_type } => {
+                ValidationErrorKind::ContentMediaType {
+                    content_media_type: content_media_type.clone(),
+                }
+            }
+            ValidationErrorKind::Enum { options } => ValidationErrorKind::Enum {
+                options: options.clone(),
+            },
+            ValidationErrorKind::ExclusiveMaximum { limit } => {
+                ValidationErrorKind::ExclusiveMaximum {
+                    limit: limit.clone(),
+                }
+            }
+            ValidationErrorKind::ExclusiveMinimum { limit } => {
+                ValidationErrorKind::ExclusiveMinimum {
+                    limit: limit.clone(),
+                }
+            }
+            ValidationErrorKind::FalseSchema => ValidationErrorKind::FalseSchema,
+            ValidationErrorKind::FileNotFound { error } => ValidationErrorKind::FileNotFound {
+                error: io::Error::new(error.kind(), error.to_string()),
+            },
+            ValidationErrorKind::Format { format } => ValidationErrorKind::Format { format },
+            ValidationErrorKind::FromUtf8 { error } => ValidationErrorKind::FromUtf8 {
+                error: error.clone(),
+            },
+            ValidationErrorKind::Utf8 { error } => ValidationErrorKind::Utf8 { error: *error },
+            ValidationErrorKind::JSONParse { error } => ValidationErrorKind::JSONParse {
+                error: serde::de::Error::custom(error),
+            },
+            ValidationErrorKind::IncompatibleArtifact { reason } => {
+                ValidationErrorKind::IncompatibleArtifact {
+                    reason: reason.clone(),
+                }
+            }
+            ValidationErrorKind::LimitExceeded { limit } => {
+                ValidationErrorKind::LimitExceeded { limit: *limit }
+            }
+            ValidationErrorKind::Interrupted { reason } => {
+                ValidationErrorKind::Interrupted { reason: *reason }
+            }
+            ValidationErrorKind::InvalidReference { reference } => {
+                ValidationErrorKind::InvalidReference {
+                    reference: reference.clone(),
+                }
+            }
+            ValidationErrorKind::InvalidRegex { error } => ValidationErrorKind::InvalidRegex {
+                error: duplicate_regex_error(error),
+            },
+            ValidationErrorKind::InvalidURL { error } => {
+                ValidationErrorKind::InvalidURL { error: *error }
+            }
+            ValidationErrorKind::MaxItems { limit } => {
+                ValidationErrorKind::MaxItems { limit: *limit }
+            }
+            ValidationErrorKind::Maximum { limit } => ValidationErrorKind::Maximum {
+                limit: limit.clone(),
+            },
+            ValidationErrorKind::MaxLength { limit } => {
+                ValidationErrorKind::MaxLength { limit: *limit }
+            }
+            ValidationErrorKind::MaxProperties { limit } => {
+                ValidationErrorKind::MaxProperties { limit: *limit }
+            }
+            ValidationErrorKind::MinItems { limit } => {
+                ValidationErrorKind::MinItems { limit: *limit }
+            }
+            ValidationErrorKind::Minimum { limit } => ValidationErrorKind::Minimum {
+                limit: limit.clone(),
+            },
+            ValidationErrorKind::MinLength { limit } => {
+                ValidationErrorKind::MinLength { limit: *limit }
+            }
+            ValidationErrorKind::MinProperties { limit } => {
+                ValidationErrorKind::MinProperties { limit: *limit }
+            }
+            ValidationErrorKind::MultipleOf { multiple_of } => ValidationErrorKind::MultipleOf {
+                multiple_of: multiple_of.clone(),
+            },
+            ValidationErrorKind::Not { schema } => ValidationErrorKind::Not {
+                schema: schema.clone(),
+            },
+            ValidationErrorKind::OneOfMultipleValid => ValidationErrorKind::OneOfMultipleValid,
+            ValidationErrorKind::OneOfNotValid => ValidationErrorKind::OneOfNotValid,
+            ValidationErrorKind::Pattern { pattern } => ValidationErrorKind::Pattern {
+                pattern: pattern.clone(),
+            },
+            ValidationErrorKind::PropertyNames { error } => ValidationErrorKind::PropertyNames {
+                error: Box::new(error.duplicate()),
+            },
+            ValidationErrorKind::Required { property } => ValidationErrorKind::Required {
+                property: property.clone(),
+            },
+            #[cfg(any(feature = "reqwest", test))]
+            ValidationErrorKind::Reqwest { error } => ValidationErrorKind::FileNotFound {
+                error: io::Error::other(error.to_string()),
+            },
+            ValidationErrorKind::Schema => ValidationErrorKind::Schema,
+            ValidationErrorKind::Type { kind } => ValidationErrorKind::Type {
+                kind: match kind {
+                    TypeKind::Single(type_) => TypeKind::Single(*type_),
+                    TypeKind::Multiple(types) => TypeKind::Multiple(*types),
+                },
+            },
+            ValidationErrorKind::UniqueItems => ValidationErrorKind::UniqueItems,
+            ValidationErrorKind::UnknownReferenceScheme { scheme } => {
+                ValidationErrorKind::UnknownReferenceScheme {
+                    scheme: scheme.clone(),
+                }
+            }
+            ValidationErrorKind::UnknownVocabulary { vocabulary } => {
+                ValidationErrorKind::UnknownVocabulary {
+                    vocabulary: vocabulary.clone(),
+                }
+            }
+        }
+    }
+}
+
+fn duplicate_regex_error(error: &fancy_regex::Error) -> fancy_regex::Error {
+    use fancy_regex::Error as RegexError;
+    match error {
+        RegexError::UnclosedOpenParen => RegexError::UnclosedOpenParen,
+        RegexError::InvalidRepeat => RegexError::InvalidRepeat,
+        RegexError::RecursionExceeded => RegexError::RecursionExceeded,
+        RegexError::LookBehindNotConst => RegexError::LookBehindNotConst,
+        RegexError::TrailingBackslash => RegexError::TrailingBackslash,
+        RegexError::InvalidEscape(escape) => RegexError::InvalidEscape(escape.clone()),
+        RegexError::UnclosedUnicodeName => RegexError::UnclosedUnicodeName,
+        RegexError::InvalidHex => RegexError::InvalidHex,
+        RegexError::InvalidCodepointValue => RegexError::InvalidCodepointValue,
+        RegexError::InvalidClass => RegexError::InvalidClass,
+        RegexError::UnknownFlag(flag) => RegexError::UnknownFlag(flag.clone()),
+        RegexError::NonUnicodeUnsupported => RegexError::NonUnicodeUnsupported,
+        RegexError::InvalidBackref => RegexError::InvalidBackref,
+        RegexError::InnerError(error) => RegexError::InnerError(error.clone()),
+        RegexError::InvalidGroupName => RegexError::InvalidGroupName,
+        RegexError::InvalidGroupNameBackref(name) => {
+            RegexError::InvalidGroupNameBackref(name.clone())
+        }
+        RegexError::NamedBackrefOnly => RegexError::NamedBackrefOnly,
+        RegexError::TargetNotRepeatable => RegexError::TargetNotRepeatable,
+        RegexError::StackOverflow => RegexError::StackOverflow,
+        RegexError::BacktrackLimitExceeded => RegexError::BacktrackLimitExceeded,
+        _ => RegexError::ParseError,
+    }
+}
+
 /// Shortcuts for creation of specific error kinds.
 impl<'a> ValidationError<'a> {
-    pub(crate) fn into_owned(self) -> ValidationError<'static> {
+    /// Convert to an error that doesn't borrow the instance by copying it.
+    ///
+    /// ```rust
+    /// # use jsonschema::{error::OwnedValidationError, JSONSchema};
+    /// # use serde_json::json;
+    /// fn first_error(schema: &JSONSchema, instance: &serde_json::Value) -> Option<OwnedValidationError> {
+    ///     schema
+    ///         .validate(instance)
+    ///         .err()
+    ///         .and_then(|mut errors| errors.next())
+    ///         .map(|error| error.into_owned())
+    /// }
+    ///
+    /// let schema = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+    /// let error = first_error(&schema, &json!(42)).expect("Invalid instance");
+    /// assert_eq!(error.to_string(), r#"42 is not of type "string""#);
+    /// ```
+    #[must_use]
+    pub fn into_owned(self) -> ValidationError<'static> {
         ValidationError {
             instance_path: self.instance_path.clone(),
             instance: Cow::Owned(self.instance.into_owned()),
@@ -163,6 +441,54 @@ impl<'a> ValidationError<'a> {
         }
     }
 
+    /// Convert to an error that doesn't borrow the instance by copying as much of it as
+    /// `payload` allows. Only the kept part of a borrowed instance is copied.
+    ///
+    /// ```rust
+    /// # use jsonschema::{error::InstancePayload, JSONSchema};
+    /// # use serde_json::json;
+    /// let schema = JSONSchema::compile(&json!({"maxItems": 2})).expect("Valid schema");
+    /// let instance = json!([1, 2, 3, 4, 5]);
+    /// let error = schema
+    ///     .validate(&instance)
+    ///     .expect_err("Too many items")
+    ///     .next()
+    ///     .expect("One error")
+    ///     .into_owned_with(InstancePayload::Truncated {
+    ///         max_depth: 1,
+    ///         max_length: 2,
+    ///     });
+    /// assert_eq!(*error.instance, json!([1, 2]));
+    /// ```
+    #[must_use]
+    pub fn into_owned_with(self, payload: InstancePayload) -> ValidationError<'static> {
+        let instance = match payload {
+            InstancePayload::Full => self.instance.into_owned(),
+            InstancePayload::Truncated {
+                max_depth,
+                max_length,
+            } => truncate(&self.instance, max_depth, max_length),
+            InstancePayload::Omitted => Value::Null,
+        };
+        ValidationError {
+            instance_path: self.instance_path,
+            instance: Cow::Owned(instance),
+            kind: self.kind,
+            schema_path: self.schema_path,
+        }
+    }
+
+    /// A copy of this error. Sources of I/O, JSON and HTTP errors can't be cloned, so only their
+    /// messages are kept. HTTP errors become `FileNotFound`, as there is no way to construct them.
+    pub(crate) fn duplicate(&self) -> ValidationError<'static> {
+        ValidationError {
+            instance_path: self.instance_path.clone(),
+            instance: Cow::Owned(self.instance.as_ref().clone()),
+            kind: self.kind.duplicate(),
+            schema_path: self.schema_path.clone(),
+        }
+    }
+
     pub(crate) const fn additional_items(
         schema_path: JSONPointer,
         instance_path: JSONPointer,
@@ -435,6 +761,43 @@ impl<'a> ValidationError<'a> {
             schema_path: JSONPointer::default(),
         }
     }
+    pub(crate) fn compilation_warning(schema: &'a Value, warning: &Warning) -> ValidationError<'a> {
+        let instance = schema
+            .pointer(&warning.schema_path.to_string())
+            .map_or(Cow::Owned(Value::Null), Cow::Borrowed);
+        ValidationError {
+            instance_path: JSONPointer::default(),
+            instance,
+            kind: ValidationErrorKind::CompilationWarning {
+                warning: warning.kind.clone(),
+            },
+            schema_path: warning.schema_path.clone(),
+        }
+    }
+    pub(crate) fn limit_exceeded(instance: &'a Value, limit: Limit) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: JSONPointer::default(),
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::LimitExceeded { limit },
+            schema_path: JSONPointer::default(),
+        }
+    }
+    pub(crate) fn interrupted(instance: &'a Value, reason: Interruption) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: JSONPointer::default(),
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::Interrupted { reason },
+            schema_path: JSONPointer::default(),
+        }
+    }
+    pub(crate) fn incompatible_artifact(reason: String) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: JSONPointer::default(),
+            instance: Cow::Owned(Value::Null),
+            kind: ValidationErrorKind::IncompatibleArtifact { reason },
+            schema_path: JSONPointer::default(),
+        }
+    }
     pub(crate) fn invalid_reference(reference: String) -> ValidationError<'a> {
         ValidationError {
             instance_path: JSONPointer::default(),
@@ -559,7 +922,7 @@ impl<'a> ValidationError<'a> {
         schema_path: JSONPointer,
         instance_path: JSONPointer,
         instance: &'a Value,
-        multiple_of: f64,
+        multiple_of: Value,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
@@ -656,6 +1019,18 @@ impl<'a> ValidationError<'a> {
         }
     }
 
+    pub(crate) fn invalid_regex(
+        schema_path: JSONPointer,
+        pattern: &str,
+        error: fancy_regex::Error,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: JSONPointer::default(),
+            instance: Cow::Owned(Value::String(pattern.to_string())),
+            kind: ValidationErrorKind::InvalidRegex { error },
+            schema_path,
+        }
+    }
     pub(crate) fn schema(instance: &'a Value) -> ValidationError<'a> {
         ValidationError {
             instance_path: JSONPointer::default(),
@@ -724,6 +1099,14 @@ impl<'a> ValidationError<'a> {
             schema_path: JSONPointer::default(),
         }
     }
+    pub(crate) fn unknown_vocabulary(vocabulary: String) -> ValidationError<'a> {
+        ValidationError {
+            instance_path: JSONPointer::default(),
+            instance: Cow::Owned(Value::Null),
+            kind: ValidationErrorKind::UnknownVocabulary { vocabulary },
+            schema_path: JSONPointer::default(),
+        }
+    }
     pub(crate) fn utf8(error: Utf8Error) -> ValidationError<'a> {
         ValidationError {
             instance_path: JSONPointer::default(),
@@ -773,6 +1156,29 @@ impl From<reqwest::Error> for ValidationError<'_> {
     }
 }
 
+fn truncate(value: &Value, max_depth: usize, max_length: usize) -> Value {
+    match value {
+        Value::String(string) => Value::String(string.chars().take(max_length).collect()),
+        Value::Array(_) if max_depth == 0 => Value::Array(Vec::new()),
+        Value::Object(_) if max_depth == 0 => Value::Object(Map::new()),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .take(max_length)
+                .map(|item| truncate(item, max_depth - 1, max_length))
+                .collect(),
+        ),
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .take(max_length)
+                .map(|(key, value)| (key.clone(), truncate(value, max_depth - 1, max_length)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
 /// Textual representation of various validation errors.
 impl fmt::Display for ValidationError<'_> {
     #[allow(clippy::too_many_lines)] // The function is long but it does formatting only
@@ -789,17 +1195,30 @@ impl fmt::Display for ValidationError<'_> {
             ValidationErrorKind::UnknownReferenceScheme { scheme } => {
                 write!(f, "Unknown scheme: {}", scheme)
             }
+            ValidationErrorKind::UnknownVocabulary { vocabulary } => {
+                write!(f, "Unknown vocabulary: {}", vocabulary)
+            }
+            ValidationErrorKind::CompilationWarning { warning } => warning.fmt(f),
+            ValidationErrorKind::LimitExceeded { limit } => {
+                write!(f, "Validation limit exceeded: {}", limit)
+            }
+            ValidationErrorKind::Interrupted { reason } => {
+                write!(f, "Validation interrupted: {}", reason)
+            }
+            ValidationErrorKind::IncompatibleArtifact { reason } => {
+                write!(f, "Incompatible compiled schema: {}", reason)
+            }
             ValidationErrorKind::Format { format } => {
                 write!(f, r#"{} is not a "{}""#, self.instance, format)
             }
             ValidationErrorKind::AdditionalItems { limit } => {
-                // It's safe to unwrap here as ValidationErrorKind::AdditionalItems is reported only in
-                // case of arrays with more items than expected
+                // The instance is always an array with more items than expected, unless it was
+                // truncated or omitted with `into_owned_with`
                 let extras: Vec<&Value> = self
                     .instance
                     .as_array()
-                    .expect("Always valid")
-                    .iter()
+                    .into_iter()
+                    .flatten()
                     .skip(*limit)
                     .collect();
                 let verb = {
@@ -887,6 +1306,11 @@ impl fmt::Display for ValidationError<'_> {
             ValidationErrorKind::InvalidReference { reference } => {
                 write!(f, "Invalid reference: {}", reference)
             }
+            ValidationErrorKind::InvalidRegex { error } => write!(
+                f,
+                "{} is not a valid regular expression: {}",
+                self.instance, error
+            ),
             ValidationErrorKind::Maximum { limit } => write!(
                 f,
                 "{} is greater than the maximum of {}",
@@ -986,6 +1410,30 @@ mod tests {
     use serde_json::json;
     use test_case::test_case;
 
+    #[test]
+    fn duplicate() {
+        let instance = json!(42);
+        let error = ValidationError::file_not_found(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Denied",
+        ));
+        let copy = error.duplicate();
+        assert_eq!(copy.to_string(), error.to_string());
+        match copy.kind {
+            ValidationErrorKind::FileNotFound { error } => {
+                assert_eq!(error.kind(), io::ErrorKind::PermissionDenied)
+            }
+            kind => panic!("Unexpected kind: {:?}", kind),
+        }
+        let error = ValidationError::minimum(
+            JSONPointer::default(),
+            JSONPointer::default(),
+            &instance,
+            json!(43),
+        );
+        assert_eq!(error.duplicate().to_string(), error.to_string());
+    }
+
     #[test]
     fn single_type_error() {
         let instance = json!(42);
@@ -1130,4 +1578,72 @@ mod tests {
         assert!(result.next().is_none());
         assert_eq!(error.instance_path, JSONPointer::from(expected));
     }
+
+    #[test_case(InstancePayload::Full, &json!({"a": ["xyz", [1, 2]], "b": 1}))]
+    #[test_case(
+        InstancePayload::Truncated { max_depth: 2, max_length: 2 },
+        &json!({"a": ["xy", []], "b": 1});
+        "truncated"
+    )]
+    #[test_case(
+        InstancePayload::Truncated { max_depth: 0, max_length: 2 },
+        &json!({});
+        "too deep"
+    )]
+    #[test_case(InstancePayload::Omitted, &json!(null))]
+    fn instance_payload(payload: InstancePayload, expected: &Value) {
+        let schema = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+        let instance = json!({"a": ["xyz", [1, 2]], "b": 1});
+        let error = schema
+            .validate(&instance)
+            .expect_err("Not a string")
+            .next()
+            .expect("One error")
+            .into_owned_with(payload);
+        assert_eq!(&*error.instance, expected);
+    }
+
+    #[test]
+    fn validate_owned() {
+        let schema = JSONSchema::options()
+            .with_instance_payload(InstancePayload::Truncated {
+                max_depth: 1,
+                max_length: 1,
+            })
+            .compile(&json!({"items": {"type": "string"}, "minItems": 4}))
+            .expect("Valid schema");
+        let errors = {
+            let instance = json!([1, "a", 2]);
+            schema.validate_owned(&instance).expect_err("Invalid")
+        };
+        let errors: Vec<_> = errors
+            .iter()
+            .map(|error| (error.instance_path.to_string(), (*error.instance).clone()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                ("/0".to_string(), json!(1)),
+                ("/2".to_string(), json!(2)),
+                ("".to_string(), json!([1])),
+            ]
+        );
+        assert!(schema.validate_owned(&json!(["a", "b", "c", "d"])).is_ok());
+    }
+
+    #[test]
+    fn additional_items_without_payload() {
+        let schema = JSONSchema::compile(&json!({"items": [{}], "additionalItems": false}))
+            .expect("Valid schema");
+        let instance = json!([1, 2]);
+        let error = schema
+            .validate(&instance)
+            .expect_err("Too many items")
+            .next()
+            .expect("One error")
+            .into_owned_with(InstancePayload::Omitted);
+        assert!(error
+            .to_string()
+            .starts_with("Additional items are not allowed"));
+    }
 }
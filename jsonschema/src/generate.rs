@@ -0,0 +1,1204 @@
+//! Generation of instances for property-based testing.
+//!
+//! A [`Generator`] produces instances that are valid, or deliberately invalid, against a compiled
+//! schema. Every candidate is re-checked with [`JSONSchema::is_valid`], therefore the results are
+//! always correct. However, schemas that combine constraints in ways the generator doesn't
+//! understand (e.g. `not` or `if`) may exhaust all attempts, and then `None` is returned. The same
+//! happens if strings, arrays or objects must be longer than 10 000 elements.
+//!
+//! Keywords that guide the generation:
+//!   - `type`, `const`, `enum`;
+//!   - `properties`, `required`, `additionalProperties`, `minProperties`;
+//!   - `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `multipleOf`;
+//!   - `minLength`, `maxLength`, `format` (common formats only) & `pattern` (literals, classes,
+//!     groups, alternations and quantifiers; look-arounds & backreferences are not supported);
+//!   - `items`, `additionalItems`, `minItems`, `maxItems`, `uniqueItems`, `contains`;
+//!   - `allOf`, `anyOf`, `oneOf` & `$ref`.
+//!
+//! ```rust
+//! use jsonschema::JSONSchema;
+//! use serde_json::json;
+//!
+//! let schema = json!({
+//!     "properties": {"id": {"type": "integer", "minimum": 1}},
+//!     "required": ["id"]
+//! });
+//! let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+//! let mut generator = compiled.generator(42);
+//! let valid = generator.valid().expect("Satisfiable schema");
+//! assert!(compiled.is_valid(&valid));
+//! let invalid = generator.invalid().expect("Not every instance is valid");
+//! assert!(!compiled.is_valid(&invalid));
+//! ```
+//!
+//! With the `proptest` feature enabled, [`valid_strategy`] & [`invalid_strategy`] wrap a generator
+//! into `proptest` strategies.
+use crate::{
+    compilation::{JSONSchema, DEFAULT_SCOPE},
+    schemas::{self, id_keyword, Draft},
+};
+use serde_json::{Map, Number, Value};
+use std::{iter::Peekable, str::Chars};
+use url::Url;
+
+/// How many candidates are tried before giving up.
+const DEFAULT_MAX_ATTEMPTS: usize = 100;
+/// How deep optional parts of instances may be nested.
+const DEFAULT_MAX_DEPTH: usize = 4;
+/// How many elements could be generated on top of the required minimum.
+const EXTRA_ELEMENTS: usize = 3;
+/// Strings, arrays & objects that must be longer than this are not generated.
+const MAX_LENGTH: usize = 10_000;
+/// Width of the range for numbers that are bounded from one side only.
+const NUMERIC_RANGE: i64 = 100;
+
+/// Generates instances for a compiled schema.
+/// Created via [`JSONSchema::generator`]. The same seed produces the same sequence of instances.
+#[derive(Debug)]
+pub struct Generator<'a> {
+    schema: &'a JSONSchema,
+    rng: Rng,
+    max_attempts: usize,
+    max_depth: usize,
+}
+
+impl<'a> Generator<'a> {
+    pub(crate) const fn new(schema: &'a JSONSchema, seed: u64) -> Generator<'a> {
+        Generator {
+            schema,
+            rng: Rng(seed),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Set how many candidates are tried before `valid` or `invalid` give up.
+    #[inline]
+    pub const fn with_max_attempts(&mut self, max_attempts: usize) -> &mut Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set how deep optional properties & array items may be nested.
+    /// Required parts of recursive schemas are generated beyond this depth if necessary.
+    #[inline]
+    pub const fn with_max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Generate an instance that is valid against the schema.
+    /// Returns `None` if no valid instance was found within the configured number of attempts.
+    pub fn valid(&mut self) -> Option<Value> {
+        for _ in 0..self.max_attempts {
+            if let Some(instance) = self.candidate() {
+                if self.schema.is_valid(&instance) {
+                    return Some(instance);
+                }
+            }
+        }
+        None
+    }
+
+    /// Generate an instance that is invalid against the schema.
+    /// Candidates are either valid instances with a single mutated value or arbitrary values.
+    /// Returns `None` if no invalid instance was found within the configured number of attempts.
+    pub fn invalid(&mut self) -> Option<Value> {
+        for _ in 0..self.max_attempts {
+            let candidate = match self.candidate() {
+                Some(instance) if self.rng.chance() => self.mutate(instance),
+                _ => self.any(0),
+            };
+            if !self.schema.is_valid(&candidate) {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    fn candidate(&mut self) -> Option<Value> {
+        let draft = self.schema.draft();
+        let root = self.schema.schema.clone();
+        let base = match schemas::id_of(draft, &root) {
+            Some(id) => Url::parse(id).unwrap_or_else(|_| DEFAULT_SCOPE.clone()),
+            None => DEFAULT_SCOPE.clone(),
+        };
+        self.generate(&root, &base, 0)
+    }
+
+    fn generate(&mut self, schema: &Value, base: &Url, depth: usize) -> Option<Value> {
+        let object = match schema {
+            Value::Bool(true) => return Some(self.any(depth)),
+            Value::Object(object) => object,
+            _ => return None,
+        };
+        // Recursive schemas with required recursion can't be generated
+        if depth > self.max_depth * 2 + EXTRA_ELEMENTS {
+            return None;
+        }
+        let draft = self.schema.draft();
+        let mut base = base.clone();
+        if let Some(id) = object.get(id_keyword(draft)).and_then(Value::as_str) {
+            base = base.join(id).ok()?;
+        }
+        if let Some(value) = object.get("const") {
+            return Some(value.clone());
+        }
+        if let Some(Value::Array(options)) = object.get("enum") {
+            return self.choose(options).cloned();
+        }
+        if let Some(Value::String(reference)) = object.get("$ref") {
+            let (scope, target) = self.resolve(reference, &base)?;
+            if ignores_ref_siblings(draft) || object.len() == 1 {
+                return self.generate(&target, &scope, depth + 1);
+            }
+            let mut merged = object.clone();
+            merged.remove("$ref");
+            merge(&mut merged, &target);
+            return self.generate(&Value::Object(merged), &base, depth + 1);
+        }
+        if object.contains_key("allOf") {
+            let mut merged = object.clone();
+            if let Some(Value::Array(subschemas)) = merged.remove("allOf") {
+                for subschema in &subschemas {
+                    let subschema = self.flatten(subschema, &base, depth)?;
+                    merge(&mut merged, &subschema);
+                }
+            }
+            return self.generate(&Value::Object(merged), &base, depth);
+        }
+        for keyword in &["anyOf", "oneOf"] {
+            if let Some(Value::Array(subschemas)) = object.get(*keyword) {
+                let subschema = self.choose(subschemas)?;
+                let subschema = self.flatten(subschema, &base, depth)?;
+                let mut merged = object.clone();
+                merged.remove(*keyword);
+                merge(&mut merged, &subschema);
+                return self.generate(&Value::Object(merged), &base, depth);
+            }
+        }
+        let types = types_of(object);
+        let type_ = *self.choose(&types)?;
+        match type_ {
+            "null" => Some(Value::Null),
+            "boolean" => Some(Value::Bool(self.rng.chance())),
+            "integer" => self.number(object, true),
+            "number" => {
+                let integer = self.rng.chance();
+                self.number(object, integer)
+            }
+            "string" => self.string(object),
+            "array" => self.array(object, &base, depth),
+            "object" => self.object(object, &base, depth),
+            _ => None,
+        }
+    }
+
+    /// Resolve references on the top level of `schema`, so it could be merged with others.
+    fn flatten(&mut self, schema: &Value, base: &Url, depth: usize) -> Option<Value> {
+        match schema {
+            Value::Object(object) if depth <= self.max_depth * 2 + EXTRA_ELEMENTS => {
+                match object.get("$ref") {
+                    Some(Value::String(reference)) => {
+                        let (scope, target) = self.resolve(reference, base)?;
+                        let mut flattened = self.flatten(&target, &scope, depth + 1)?;
+                        if !ignores_ref_siblings(self.schema.draft()) {
+                            let mut siblings = object.clone();
+                            siblings.remove("$ref");
+                            merge(&mut siblings, &flattened);
+                            flattened = Value::Object(siblings);
+                        }
+                        Some(flattened)
+                    }
+                    _ => Some(schema.clone()),
+                }
+            }
+            Value::Bool(true) => Some(Value::Object(Map::new())),
+            _ => None,
+        }
+    }
+
+    /// Resolve `reference` to its scope & target. References inside the target are absolute, so
+    /// the target could be merged into schemas from other documents.
+    fn resolve(&self, reference: &str, base: &Url) -> Option<(Url, Value)> {
+        let url = base.join(reference).ok()?;
+        let draft = self.schema.draft();
+        let (scope, target) = self.schema.resolver.resolve_fragment(draft, &url).ok()?;
+        let mut target = (*target).clone();
+        absolutize(&mut target, &scope, draft)?;
+        Some((scope, target))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn number(&mut self, schema: &Map<String, Value>, integer: bool) -> Option<Value> {
+        let (mut low, mut high) = bounds(schema);
+        match (low, high) {
+            (None, None) => {
+                low = Some((-NUMERIC_RANGE as f64, false));
+                high = Some((NUMERIC_RANGE as f64, false));
+            }
+            (Some((value, _)), None) => high = Some((value + NUMERIC_RANGE as f64, false)),
+            (None, Some((value, _))) => low = Some((value - NUMERIC_RANGE as f64, false)),
+            _ => {}
+        }
+        let (low, low_exclusive) = low?;
+        let (high, high_exclusive) = high?;
+        if let Some(multiple_of) = schema.get("multipleOf").and_then(Value::as_f64) {
+            let mut first = (low / multiple_of).ceil() as i64;
+            if low_exclusive && (first as f64 * multiple_of - low).abs() < f64::EPSILON {
+                first += 1;
+            }
+            let mut last = (high / multiple_of).floor() as i64;
+            if high_exclusive && (last as f64 * multiple_of - high).abs() < f64::EPSILON {
+                last -= 1;
+            }
+            let factor = self.rng.between(first, last)?;
+            return to_number(factor as f64 * multiple_of);
+        }
+        if integer {
+            let mut first = low.ceil() as i64;
+            if low_exclusive && first as f64 <= low {
+                first += 1;
+            }
+            let mut last = high.floor() as i64;
+            if high_exclusive && last as f64 >= high {
+                last -= 1;
+            }
+            return self.rng.between(first, last).map(Value::from);
+        }
+        if high < low || (high - low).abs() < f64::EPSILON && (low_exclusive || high_exclusive) {
+            return None;
+        }
+        let value = self.rng.ratio().mul_add(high - low, low);
+        if (low_exclusive && value <= low) || (high_exclusive && value >= high) {
+            return to_number((low + high) / 2.0);
+        }
+        to_number(value)
+    }
+
+    fn string(&mut self, schema: &Map<String, Value>) -> Option<Value> {
+        if let Some(format) = schema.get("format").and_then(Value::as_str) {
+            if let Some(value) = self.format(format) {
+                return Some(Value::String(value));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            if let Some(pattern) = parse_pattern(pattern) {
+                let mut value = String::new();
+                self.pattern(&pattern, &mut value);
+                return Some(Value::String(value));
+            }
+        }
+        let min = usize_of(schema, "minLength").unwrap_or(0);
+        if min > MAX_LENGTH {
+            return None;
+        }
+        let max =
+            usize_of(schema, "maxLength").unwrap_or_else(|| min.saturating_add(EXTRA_ELEMENTS * 3));
+        let length = self.rng.between_usize(min, max).unwrap_or(min);
+        Some(Value::String(
+            (0..length)
+                .map(|_| ALPHANUMERIC[self.rng.below(ALPHANUMERIC.len())])
+                .collect(),
+        ))
+    }
+
+    fn format(&mut self, format: &str) -> Option<String> {
+        let number = self.rng.below(1000);
+        let value = match format {
+            "date" => format!("2021-{:02}-{:02}", number % 12 + 1, number % 28 + 1),
+            "date-time" => format!(
+                "2021-{:02}-{:02}T12:00:00Z",
+                number % 12 + 1,
+                number % 28 + 1
+            ),
+            "time" => format!("{:02}:{:02}:00Z", number % 24, number % 60),
+            "email" | "idn-email" => format!("user{}@example.com", number),
+            "hostname" | "idn-hostname" => format!("host{}.example.com", number),
+            "ipv4" => format!("10.0.{}.{}", number % 256, number / 4),
+            "ipv6" => format!("2001:db8::{:x}", number),
+            "uri" | "iri" => format!("http://example.com/{}", number),
+            "uri-reference" | "iri-reference" | "json-pointer" => format!("/{}", number),
+            "uri-template" => format!("http://example.com/{}/{{id}}", number),
+            "uuid" => format!("123e4567-e89b-12d3-a456-{:012x}", number),
+            "relative-json-pointer" => format!("{}/a", number % 10),
+            "regex" => ".*".to_string(),
+            "duration" => format!("P{}D", number),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn pattern(&mut self, pattern: &[Atom], output: &mut String) {
+        for atom in pattern {
+            match atom {
+                Atom::Literal(literal) => output.push(*literal),
+                Atom::Class(ranges, negated) => output.push(self.class(ranges, *negated)),
+                Atom::Group(alternatives) => {
+                    if let Some(alternative) = self.choose(alternatives) {
+                        self.pattern(alternative, output)
+                    }
+                }
+                Atom::Repeat(atom, min, max) => {
+                    let count = self.rng.between_usize(*min, *max).unwrap_or(*min);
+                    for _ in 0..count {
+                        self.pattern(std::slice::from_ref(atom), output);
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn class(&mut self, ranges: &[(char, char)], negated: bool) -> char {
+        let contains = |c: char| {
+            ranges
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&c))
+        };
+        if negated {
+            let start = self.rng.below(ALPHANUMERIC.len());
+            return (0..ALPHANUMERIC.len())
+                .map(|offset| ALPHANUMERIC[(start + offset) % ALPHANUMERIC.len()])
+                .chain(" -_.,:;!?".chars())
+                .find(|c| !contains(*c))
+                .unwrap_or('\u{a1}');
+        }
+        let (start, end) = match self.choose(ranges) {
+            Some(range) => *range,
+            None => return 'a',
+        };
+        let width = (end as u32).saturating_sub(start as u32);
+        let offset = self.rng.below(width as usize + 1) as u32;
+        std::char::from_u32(start as u32 + offset).unwrap_or(start)
+    }
+
+    fn array(&mut self, schema: &Map<String, Value>, base: &Url, depth: usize) -> Option<Value> {
+        let min = usize_of(schema, "minItems").unwrap_or(0);
+        if min > MAX_LENGTH {
+            return None;
+        }
+        let mut max = usize_of(schema, "maxItems").unwrap_or(usize::MAX);
+        if let (Some(Value::Array(positional)), Some(Value::Bool(false))) =
+            (schema.get("items"), schema.get("additionalItems"))
+        {
+            max = max.min(positional.len());
+        }
+        let length = if depth >= self.max_depth {
+            min
+        } else {
+            self.rng
+                .between_usize(min, max.min(min.saturating_add(EXTRA_ELEMENTS)))?
+        };
+        let unique = schema.get("uniqueItems") == Some(&Value::Bool(true));
+        let mut items = Vec::with_capacity(length);
+        if let Some(contains) = schema.get("contains") {
+            if max == 0 {
+                return None;
+            }
+            items.push(self.generate(contains, base, depth + 1)?);
+        }
+        while items.len() < length {
+            let index = items.len();
+            let item_schema = match schema.get("items") {
+                Some(Value::Array(positional)) => match positional.get(index) {
+                    Some(item_schema) => item_schema,
+                    None => match schema.get("additionalItems") {
+                        Some(additional) => additional,
+                        None => &Value::Bool(true),
+                    },
+                },
+                Some(item_schema) => item_schema,
+                None => &Value::Bool(true),
+            };
+            let mut item = self.generate(item_schema, base, depth + 1)?;
+            for _ in 0..EXTRA_ELEMENTS {
+                if !unique || !items.contains(&item) {
+                    break;
+                }
+                item = self.generate(item_schema, base, depth + 1)?;
+            }
+            items.push(item);
+        }
+        Some(Value::Array(items))
+    }
+
+    fn object(&mut self, schema: &Map<String, Value>, base: &Url, depth: usize) -> Option<Value> {
+        let empty = Map::new();
+        let properties = match schema.get("properties") {
+            Some(Value::Object(properties)) => properties,
+            _ => &empty,
+        };
+        let additional = match schema.get("additionalProperties") {
+            Some(additional) => additional.clone(),
+            None => Value::Bool(true),
+        };
+        let mut object = Map::new();
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                let property_schema = properties.get(name).unwrap_or(&additional);
+                let value = self.generate(property_schema, base, depth + 1)?;
+                object.insert(name.to_string(), value);
+            }
+        }
+        if depth < self.max_depth {
+            for (name, property_schema) in properties {
+                if !object.contains_key(name) && self.rng.chance() {
+                    if let Some(value) = self.generate(property_schema, base, depth + 1) {
+                        object.insert(name.clone(), value);
+                    }
+                }
+            }
+        }
+        let min = usize_of(schema, "minProperties").unwrap_or(0);
+        if min > MAX_LENGTH {
+            return None;
+        }
+        let mut index = 0;
+        while object.len() < min && additional != Value::Bool(false) {
+            let name = format!("property{}", index);
+            index += 1;
+            if !object.contains_key(&name) {
+                let value = self.generate(&additional, base, depth + 1)?;
+                object.insert(name, value);
+            }
+        }
+        Some(Value::Object(object))
+    }
+
+    /// An arbitrary value. Containers are not generated beyond the maximum depth.
+    fn any(&mut self, depth: usize) -> Value {
+        let kinds = if depth >= self.max_depth { 4 } else { 6 };
+        match self.rng.below(kinds) {
+            0 => Value::Null,
+            1 => Value::Bool(self.rng.chance()),
+            2 => Value::from(self.rng.between(-NUMERIC_RANGE, NUMERIC_RANGE).unwrap_or(0)),
+            3 => {
+                let length = self.rng.below(EXTRA_ELEMENTS * 3);
+                Value::String(
+                    (0..length)
+                        .map(|_| ALPHANUMERIC[self.rng.below(ALPHANUMERIC.len())])
+                        .collect(),
+                )
+            }
+            4 => {
+                let length = self.rng.below(EXTRA_ELEMENTS + 1);
+                Value::Array((0..length).map(|_| self.any(depth + 1)).collect())
+            }
+            _ => {
+                let length = self.rng.below(EXTRA_ELEMENTS + 1);
+                Value::Object(
+                    (0..length)
+                        .map(|index| (format!("property{}", index), self.any(depth + 1)))
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Replace a random value inside `instance` with a value of another type.
+    fn mutate(&mut self, mut instance: Value) -> Value {
+        let mut pointers = Vec::new();
+        collect_pointers(&instance, &mut String::new(), &mut pointers);
+        let pointer = match self.choose(&pointers) {
+            Some(pointer) => pointer.clone(),
+            None => return instance,
+        };
+        if let Some(target) = instance.pointer_mut(&pointer) {
+            let original = primitive_type(target);
+            let mut replacement = self.any(self.max_depth);
+            for _ in 0..EXTRA_ELEMENTS {
+                if primitive_type(&replacement) != original {
+                    break;
+                }
+                replacement = self.any(self.max_depth);
+            }
+            *target = replacement;
+        }
+        instance
+    }
+
+    fn choose<'t, T>(&mut self, items: &'t [T]) -> Option<&'t T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.rng.below(items.len())])
+        }
+    }
+}
+
+const ALPHANUMERIC: [char; 36] = [
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
+    't', 'u', 'v', 'w', 'x', 'y', 'z', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
+];
+
+/// `SplitMix64` - small, fast & good enough for test data.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    const fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    const fn chance(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    /// A float in `[0, 1)`.
+    fn ratio(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    const fn between(&mut self, low: i64, high: i64) -> Option<i64> {
+        if high < low {
+            return None;
+        }
+        let width = high.wrapping_sub(low) as u64;
+        match width.checked_add(1) {
+            Some(range) => Some(low.wrapping_add((self.next() % range) as i64)),
+            None => Some(self.next() as i64),
+        }
+    }
+
+    const fn between_usize(&mut self, low: usize, high: usize) -> Option<usize> {
+        if high < low {
+            return None;
+        }
+        Some(low + self.below(high - low + 1))
+    }
+}
+
+/// A simplified regular expression that is only suitable for generating strings.
+#[derive(Debug)]
+enum Atom {
+    Literal(char),
+    Class(Vec<(char, char)>, bool),
+    Group(Vec<Vec<Atom>>),
+    Repeat(Box<Atom>, usize, usize),
+}
+
+const DIGITS: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+const SPACE: &[(char, char)] = &[(' ', ' ')];
+const ANY: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9')];
+
+/// Parse a regular expression. Returns `None` if it uses unsupported syntax.
+fn parse_pattern(pattern: &str) -> Option<Vec<Atom>> {
+    let mut chars = pattern.chars().peekable();
+    let alternatives = parse_alternatives(&mut chars)?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(vec![Atom::Group(alternatives)])
+}
+
+fn parse_alternatives(chars: &mut Peekable<Chars<'_>>) -> Option<Vec<Vec<Atom>>> {
+    let mut alternatives = vec![Vec::new()];
+    while let Some(&c) = chars.peek() {
+        let atom = match c {
+            ')' => break,
+            '|' => {
+                chars.next();
+                alternatives.push(Vec::new());
+                continue;
+            }
+            '^' | '$' => {
+                chars.next();
+                continue;
+            }
+            '*' | '+' | '?' | '{' => return None,
+            _ => parse_atom(chars)?,
+        };
+        let atom = parse_quantifier(chars, atom)?;
+        alternatives.last_mut()?.push(atom);
+    }
+    Some(alternatives)
+}
+
+fn parse_atom(chars: &mut Peekable<Chars<'_>>) -> Option<Atom> {
+    match chars.next()? {
+        '.' => Some(Atom::Class(ANY.to_vec(), false)),
+        '\\' => parse_escape(chars),
+        '[' => {
+            let negated = chars.peek() == Some(&'^');
+            if negated {
+                chars.next();
+            }
+            let mut ranges = Vec::new();
+            loop {
+                let start = match chars.next()? {
+                    ']' => break,
+                    '\\' => match parse_escape(chars)? {
+                        Atom::Literal(c) => c,
+                        Atom::Class(class, false) => {
+                            ranges.extend(class);
+                            continue;
+                        }
+                        _ => return None,
+                    },
+                    c => c,
+                };
+                let mut lookahead = chars.clone();
+                if lookahead.next() == Some('-') && !matches!(lookahead.peek(), Some(']') | None) {
+                    chars.next();
+                    let end = match chars.next()? {
+                        '\\' => match parse_escape(chars)? {
+                            Atom::Literal(c) => c,
+                            _ => return None,
+                        },
+                        c => c,
+                    };
+                    if end < start {
+                        return None;
+                    }
+                    ranges.push((start, end));
+                } else {
+                    ranges.push((start, start));
+                }
+            }
+            Some(Atom::Class(ranges, negated))
+        }
+        '(' => {
+            if chars.peek() == Some(&'?') {
+                chars.next();
+                // Only non-capturing groups
+                if chars.next()? != ':' {
+                    return None;
+                }
+            }
+            let alternatives = parse_alternatives(chars)?;
+            if chars.next()? != ')' {
+                return None;
+            }
+            Some(Atom::Group(alternatives))
+        }
+        c => Some(Atom::Literal(c)),
+    }
+}
+
+fn parse_escape(chars: &mut Peekable<Chars<'_>>) -> Option<Atom> {
+    let atom = match chars.next()? {
+        'd' => Atom::Class(DIGITS.to_vec(), false),
+        'D' => Atom::Class(DIGITS.to_vec(), true),
+        'w' => Atom::Class(WORD.to_vec(), false),
+        'W' => Atom::Class(WORD.to_vec(), true),
+        's' => Atom::Class(SPACE.to_vec(), false),
+        'S' => Atom::Class(SPACE.to_vec(), true),
+        'n' => Atom::Literal('\n'),
+        't' => Atom::Literal('\t'),
+        'r' => Atom::Literal('\r'),
+        // Backreferences, word boundaries, etc.
+        c if c.is_ascii_alphanumeric() => return None,
+        c => Atom::Literal(c),
+    };
+    Some(atom)
+}
+
+fn parse_quantifier(chars: &mut Peekable<Chars<'_>>, atom: Atom) -> Option<Atom> {
+    let (min, max) = match chars.peek() {
+        Some('*') => (0, EXTRA_ELEMENTS),
+        Some('+') => (1, 1 + EXTRA_ELEMENTS),
+        Some('?') => (0, 1),
+        Some('{') => {
+            chars.next();
+            let mut spec = String::new();
+            loop {
+                match chars.next()? {
+                    '}' => break,
+                    c => spec.push(c),
+                }
+            }
+            let (min, max) = match spec.find(',') {
+                Some(idx) => {
+                    let min = spec[..idx].parse().ok()?;
+                    let max = match &spec[idx + 1..] {
+                        "" => min + EXTRA_ELEMENTS,
+                        max => max.parse().ok()?,
+                    };
+                    (min, max)
+                }
+                None => {
+                    let count = spec.parse().ok()?;
+                    (count, count)
+                }
+            };
+            if max < min {
+                return None;
+            }
+            // Lazy quantifiers generate the same strings
+            if chars.peek() == Some(&'?') {
+                chars.next();
+            }
+            return Some(Atom::Repeat(Box::new(atom), min, max));
+        }
+        _ => return Some(atom),
+    };
+    chars.next();
+    if chars.peek() == Some(&'?') {
+        chars.next();
+    }
+    Some(Atom::Repeat(Box::new(atom), min, max))
+}
+
+const fn ignores_ref_siblings(draft: Draft) -> bool {
+    match draft {
+        #[cfg(feature = "draft201909")]
+        Draft::Draft201909 => false,
+        _ => true,
+    }
+}
+
+/// Make all `$id`s & `$ref`s in `schema` absolute URLs, resolved against `base`.
+fn absolutize(schema: &mut Value, base: &Url, draft: Draft) -> Option<()> {
+    match schema {
+        Value::Object(object) => {
+            let mut base = base.clone();
+            if let Some(Value::String(id)) = object.get_mut(id_keyword(draft)) {
+                base = base.join(id).ok()?;
+                *id = base.to_string();
+            }
+            for (keyword, value) in object.iter_mut() {
+                match (keyword.as_str(), value) {
+                    ("enum", _) | ("const", _) => {}
+                    ("$ref", Value::String(reference)) => {
+                        *reference = base.join(reference).ok()?.to_string();
+                    }
+                    (_, value) => absolutize(value, &base, draft)?,
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                absolutize(item, base, draft)?;
+            }
+        }
+        _ => {}
+    }
+    Some(())
+}
+
+/// Merge keywords of `other` into `schema`, so the result is (approximately) the intersection
+/// of both schemas.
+fn merge(schema: &mut Map<String, Value>, other: &Value) {
+    let other = match other {
+        Value::Object(other) => other,
+        Value::Bool(true) => return,
+        _ => {
+            schema.insert("not".to_string(), Value::Object(Map::new()));
+            return;
+        }
+    };
+    for (keyword, value) in other {
+        let merged = match (keyword.as_str(), schema.get_mut(keyword)) {
+            (_, None) => value.clone(),
+            ("properties", Some(Value::Object(properties))) => {
+                if let Value::Object(other_properties) = value {
+                    for (name, subschema) in other_properties {
+                        let merged = match properties.remove(name) {
+                            Some(existing) => all_of(existing, subschema.clone()),
+                            None => subschema.clone(),
+                        };
+                        properties.insert(name.clone(), merged);
+                    }
+                }
+                continue;
+            }
+            ("required", Some(Value::Array(required))) => {
+                if let Value::Array(other_required) = value {
+                    for name in other_required {
+                        if !required.contains(name) {
+                            required.push(name.clone());
+                        }
+                    }
+                }
+                continue;
+            }
+            ("type", Some(existing)) => {
+                let existing = type_names(existing);
+                let merged: Vec<_> = type_names(value)
+                    .into_iter()
+                    .filter(|name| {
+                        existing.contains(name)
+                            || (*name == "integer" && existing.contains(&"number"))
+                    })
+                    .chain(
+                        existing
+                            .iter()
+                            .filter(|name| {
+                                **name == "integer" && type_names(value).contains(&"number")
+                            })
+                            .copied(),
+                    )
+                    .map(Value::from)
+                    .collect();
+                Value::Array(merged)
+            }
+            (
+                "minimum" | "exclusiveMinimum" | "minLength" | "minItems" | "minProperties",
+                Some(existing),
+            ) if existing.is_number() && value.is_number() => {
+                if existing.as_f64() >= value.as_f64() {
+                    continue;
+                }
+                value.clone()
+            }
+            (
+                "maximum" | "exclusiveMaximum" | "maxLength" | "maxItems" | "maxProperties",
+                Some(existing),
+            ) if existing.is_number() && value.is_number() => {
+                if existing.as_f64() <= value.as_f64() {
+                    continue;
+                }
+                value.clone()
+            }
+            (
+                "items" | "additionalItems" | "additionalProperties" | "propertyNames" | "contains",
+                Some(existing),
+            ) if !existing.is_array() && !value.is_array() => {
+                all_of(existing.clone(), value.clone())
+            }
+            // Other keywords are kept as is. Candidates are re-checked anyway
+            (_, Some(_)) => continue,
+        };
+        schema.insert(keyword.clone(), merged);
+    }
+}
+
+fn all_of(left: Value, right: Value) -> Value {
+    let mut combined = Map::with_capacity(1);
+    combined.insert("allOf".to_string(), Value::Array(vec![left, right]));
+    Value::Object(combined)
+}
+
+fn type_names(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(name) => vec![name.as_str()],
+        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Candidate types. If `type` is absent, the type is guessed from other keywords.
+fn types_of(schema: &Map<String, Value>) -> Vec<&str> {
+    if let Some(type_) = schema.get("type") {
+        return type_names(type_);
+    }
+    let has = |keywords: &[&str]| keywords.iter().any(|keyword| schema.contains_key(*keyword));
+    let mut types = Vec::new();
+    if has(&[
+        "properties",
+        "required",
+        "additionalProperties",
+        "patternProperties",
+        "minProperties",
+        "maxProperties",
+    ]) {
+        types.push("object");
+    }
+    if has(&[
+        "items",
+        "additionalItems",
+        "minItems",
+        "maxItems",
+        "uniqueItems",
+        "contains",
+    ]) {
+        types.push("array");
+    }
+    if has(&["minLength", "maxLength", "pattern", "format"]) {
+        types.push("string");
+    }
+    if has(&[
+        "minimum",
+        "maximum",
+        "exclusiveMinimum",
+        "exclusiveMaximum",
+        "multipleOf",
+    ]) {
+        types.push("number");
+    }
+    if types.is_empty() {
+        vec![
+            "null", "boolean", "integer", "number", "string", "array", "object",
+        ]
+    } else {
+        types
+    }
+}
+
+/// Lower & upper bounds together with whether they are exclusive.
+type Bound = Option<(f64, bool)>;
+
+fn bounds(schema: &Map<String, Value>) -> (Bound, Bound) {
+    let bound = |keyword: &str, exclusive_keyword: &str| -> Bound {
+        let inclusive = schema.get(keyword).and_then(Value::as_f64);
+        match schema.get(exclusive_keyword) {
+            // Draft 4
+            Some(Value::Bool(exclusive)) => inclusive.map(|value| (value, *exclusive)),
+            Some(exclusive) => match (inclusive, exclusive.as_f64()) {
+                (Some(inclusive), Some(exclusive))
+                    if (keyword == "minimum") == (inclusive > exclusive) =>
+                {
+                    Some((inclusive, false))
+                }
+                (_, Some(exclusive)) => Some((exclusive, true)),
+                (inclusive, None) => inclusive.map(|value| (value, false)),
+            },
+            None => inclusive.map(|value| (value, false)),
+        }
+    };
+    (
+        bound("minimum", "exclusiveMinimum"),
+        bound("maximum", "exclusiveMaximum"),
+    )
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn to_number(value: f64) -> Option<Value> {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Some(Value::from(value as i64))
+    } else {
+        Number::from_f64(value).map(Value::Number)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn usize_of(schema: &Map<String, Value>, keyword: &str) -> Option<usize> {
+    schema
+        .get(keyword)
+        .and_then(Value::as_u64)
+        .map(|value| value as usize)
+}
+
+const fn primitive_type(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+fn collect_pointers(value: &Value, current: &mut String, pointers: &mut Vec<String>) {
+    pointers.push(current.clone());
+    let length = current.len();
+    match value {
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                current.push('/');
+                current.push_str(&idx.to_string());
+                collect_pointers(item, current, pointers);
+                current.truncate(length);
+            }
+        }
+        Value::Object(object) => {
+            for (key, item) in object {
+                current.push('/');
+                current.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                collect_pointers(item, current, pointers);
+                current.truncate(length);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A `proptest` strategy that produces instances valid against `schema`.
+/// Seeds that don't lead to a valid instance are rejected.
+#[cfg(feature = "proptest")]
+pub fn valid_strategy(
+    schema: std::sync::Arc<JSONSchema>,
+) -> impl proptest::strategy::Strategy<Value = Value> {
+    use proptest::{arbitrary::any, strategy::Strategy};
+    any::<u64>().prop_filter_map("No valid instance was generated", move |seed| {
+        Generator::new(&schema, seed).valid()
+    })
+}
+
+/// A `proptest` strategy that produces instances invalid against `schema`.
+/// Seeds that don't lead to an invalid instance are rejected.
+#[cfg(feature = "proptest")]
+pub fn invalid_strategy(
+    schema: std::sync::Arc<JSONSchema>,
+) -> impl proptest::strategy::Strategy<Value = Value> {
+    use proptest::{arbitrary::any, strategy::Strategy};
+    any::<u64>().prop_filter_map("No invalid instance was generated", move |seed| {
+        Generator::new(&schema, seed).invalid()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_pattern;
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(&json!(true))]
+    #[test_case(&json!({}))]
+    #[test_case(&json!({"type": "null"}))]
+    #[test_case(&json!({"const": {"a": 1}}))]
+    #[test_case(&json!({"enum": [1, "a", null]}))]
+    #[test_case(&json!({"type": "integer", "minimum": 5, "maximum": 7}))]
+    #[test_case(&json!({"type": "integer", "exclusiveMinimum": 5, "exclusiveMaximum": 7}))]
+    #[test_case(&json!({"type": "number", "minimum": 0.5, "maximum": 0.6}))]
+    #[test_case(&json!({"type": "number", "multipleOf": 0.25, "minimum": 1}))]
+    #[test_case(&json!({"type": "integer", "multipleOf": 3, "exclusiveMaximum": -10}))]
+    #[test_case(&json!({"type": "string", "minLength": 3, "maxLength": 4}))]
+    #[test_case(&json!({"type": "string", "pattern": "^[A-Z]{2}-\\d{3,5}(foo|bar)?$"}))]
+    #[test_case(&json!({"type": "string", "pattern": "^(?:[a-f0-9]+\\.)*[^\\s]x$"}))]
+    #[test_case(&json!({"type": "string", "format": "date-time"}))]
+    #[test_case(&json!({"type": "string", "format": "email"}))]
+    #[test_case(&json!({"type": "array", "items": {"type": "boolean"}, "minItems": 2, "maxItems": 3}))]
+    #[test_case(&json!({"type": "array", "items": {"type": "integer"}, "minItems": 3, "uniqueItems": true}))]
+    #[test_case(&json!({"items": [{"type": "string"}, {"type": "null"}], "additionalItems": false}))]
+    #[test_case(&json!({"contains": {"const": 42}}))]
+    #[test_case(&json!({"properties": {"a": {"type": "string"}}, "required": ["a", "b"], "additionalProperties": {"type": "integer"}}))]
+    #[test_case(&json!({"minProperties": 3, "additionalProperties": {"type": "boolean"}}))]
+    #[test_case(&json!({"allOf": [{"properties": {"a": {"minimum": 1}}, "required": ["a"]}, {"properties": {"a": {"maximum": 2}}}]}))]
+    #[test_case(&json!({"allOf": [{"type": ["string", "integer"]}, {"type": "integer"}]}))]
+    #[test_case(&json!({"anyOf": [{"type": "string", "maxLength": 0}, {"type": "boolean"}]}))]
+    #[test_case(&json!({"oneOf": [{"type": "integer"}, {"minimum": 1000}]}))]
+    #[test_case(&json!({"$ref": "#/definitions/a", "definitions": {"a": {"type": "integer", "maximum": 0}}}))]
+    #[test_case(&json!({"allOf": [{"$ref": "#/definitions/a"}], "required": ["b"], "definitions": {"a": {"required": ["a"]}}}))]
+    #[test_case(&json!({"properties": {"children": {"type": "array", "items": {"$ref": "#"}}}, "required": ["children"]}))]
+    fn valid(schema: &Value) {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        for seed in 0..20 {
+            let instance = compiled
+                .generator(seed)
+                .valid()
+                .expect("Should generate a valid instance");
+            assert!(compiled.is_valid(&instance), "{}", instance);
+        }
+    }
+
+    #[test_case(&json!({"type": "integer"}))]
+    #[test_case(&json!({"properties": {"a": {"properties": {"b": {"type": "string"}}}}}))]
+    #[test_case(&json!({"required": ["a"]}))]
+    #[test_case(&json!({"items": {"enum": [1, 2]}}))]
+    fn invalid(schema: &Value) {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        for seed in 0..20 {
+            let instance = compiled
+                .generator(seed)
+                .invalid()
+                .expect("Should generate an invalid instance");
+            assert!(!compiled.is_valid(&instance), "{}", instance);
+        }
+    }
+
+    #[test_case(&json!(false))]
+    #[test_case(&json!({"type": "integer", "minimum": 2, "maximum": 1}))]
+    #[test_case(&json!({"required": ["a"], "properties": {"a": false}}))]
+    #[test_case(&json!({"type": "string", "minLength": 18_446_744_073_709_551_615_u64}); "huge min length")]
+    #[test_case(&json!({"type": "array", "minItems": 18_446_744_073_709_551_615_u64}); "huge min items")]
+    #[test_case(&json!({"type": "object", "minProperties": 18_446_744_073_709_551_615_u64}); "huge min properties")]
+    fn unsatisfiable(schema: &Value) {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        assert!(compiled
+            .generator(0)
+            .with_max_attempts(10)
+            .valid()
+            .is_none());
+    }
+
+    #[test_case(&json!({"allOf": [{"$ref": "http://example.com/a.json#/definitions/a"}], "required": ["b"]}))]
+    #[test_case(&json!({"anyOf": [{"$ref": "http://example.com/a.json#/definitions/a"}]}))]
+    #[test_case(&json!({"properties": {"b": {"$ref": "http://example.com/a.json#/definitions/a"}}, "required": ["b"]}))]
+    fn external_references(schema: &Value) {
+        // Relative references in the external document are resolved against it
+        let compiled = JSONSchema::options()
+            .with_document(
+                "http://example.com/a.json".to_string(),
+                json!({
+                    "definitions": {
+                        "a": {"properties": {"n": {"$ref": "#/definitions/n"}}, "required": ["n"]},
+                        "n": {"type": "integer", "minimum": 5}
+                    }
+                }),
+            )
+            .compile(schema)
+            .expect("Valid schema");
+        for seed in 0..20 {
+            let instance = compiled
+                .generator(seed)
+                .valid()
+                .expect("Should generate a valid instance");
+            assert!(compiled.is_valid(&instance), "{}", instance);
+        }
+    }
+
+    #[test]
+    fn always_valid() {
+        let compiled = JSONSchema::compile(&json!(true)).expect("Valid schema");
+        assert!(compiled
+            .generator(0)
+            .with_max_attempts(10)
+            .invalid()
+            .is_none());
+    }
+
+    #[test]
+    fn deterministic() {
+        let compiled =
+            JSONSchema::compile(&json!({"items": {"type": "string"}})).expect("Valid schema");
+        let mut first = compiled.generator(7);
+        let mut second = compiled.generator(7);
+        assert_eq!(first.valid(), second.valid());
+        assert_eq!(first.invalid(), second.invalid());
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn strategies() {
+        use super::{invalid_strategy, valid_strategy};
+        use proptest::test_runner::TestRunner;
+        use std::sync::Arc;
+
+        let compiled = Arc::new(
+            JSONSchema::compile(&json!({"type": "integer", "minimum": 3})).expect("Valid schema"),
+        );
+        let mut runner = TestRunner::default();
+        runner
+            .run(&valid_strategy(Arc::clone(&compiled)), |instance| {
+                assert!(compiled.is_valid(&instance));
+                Ok(())
+            })
+            .expect("All instances are valid");
+        runner
+            .run(&invalid_strategy(Arc::clone(&compiled)), |instance| {
+                assert!(!compiled.is_valid(&instance));
+                Ok(())
+            })
+            .expect("All instances are invalid");
+    }
+
+    #[test_case("a(b|c)*d")]
+    #[test_case("[a-z0-9_-]+")]
+    #[test_case("\\w{2,}\\.")]
+    fn supported_patterns(pattern: &str) {
+        assert!(parse_pattern(pattern).is_some())
+    }
+
+    #[test_case("(?=a)b"; "lookahead")]
+    #[test_case("(a)\\1"; "backreference")]
+    #[test_case("a**"; "nothing_to_repeat")]
+    #[test_case("(a"; "unclosed_group")]
+    fn unsupported_patterns(pattern: &str) {
+        assert!(parse_pattern(pattern).is_none())
+    }
+}
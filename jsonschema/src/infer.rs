@@ -0,0 +1,326 @@
+//! Inference of a schema from sample instances.
+//!
+//! The inferred schema is a Draft 7 schema that all samples are valid against:
+//!   - `type` is a union of all observed types. Integers are widened to `number` if any
+//!     non-integer number is observed at the same location;
+//!   - `properties` are collected from all objects, and `required` lists properties that are
+//!     present in all of them;
+//!   - `items` is inferred from the items of all arrays;
+//!   - `enum` is used for strings with few distinct values that repeat across samples;
+//!   - `format` is used if all strings match one of the `date-time`, `date`, `time`, `email`,
+//!     `ipv4`, `ipv6` or `uri` formats, as checked by the `format` keyword validators.
+//!
+//! ```rust
+//! use jsonschema::{infer, JSONSchema};
+//! use serde_json::json;
+//!
+//! let samples = vec![
+//!     json!({"id": 1, "email": "alice@example.com", "tags": ["a"]}),
+//!     json!({"id": 2, "email": "bob@example.com"}),
+//! ];
+//! let schema = infer::infer(&samples);
+//! assert_eq!(schema["properties"]["id"], json!({"type": "integer"}));
+//! assert_eq!(schema["properties"]["email"], json!({"type": "string", "format": "email"}));
+//! assert_eq!(schema["required"], json!(["email", "id"]));
+//! let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+//! assert!(samples.iter().all(|sample| compiled.is_valid(sample)));
+//! ```
+use crate::{compilation::JSONSchema, schemas::Draft};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Formats that are detected, from the most specific to the least specific one.
+const FORMATS: &[&str] = &["date-time", "date", "time", "email", "ipv4", "ipv6", "uri"];
+const DRAFT7_URI: &str = "http://json-schema.org/draft-07/schema#";
+
+lazy_static::lazy_static! {
+    static ref FORMAT_VALIDATORS: Vec<(&'static str, JSONSchema)> = FORMATS
+        .iter()
+        .map(|format| {
+            let schema = json!({"type": "string", "format": format});
+            let compiled = JSONSchema::options()
+                .with_draft(Draft::Draft7)
+                .compile(&schema)
+                .expect("Valid schema");
+            (*format, compiled)
+        })
+        .collect();
+}
+
+/// Infer a schema from `samples` with the default settings.
+pub fn infer<'a, I>(samples: I) -> Value
+where
+    I: IntoIterator<Item = &'a Value>,
+{
+    SchemaInference::new().infer(samples)
+}
+
+/// Configurable schema inference.
+#[derive(Debug, Clone)]
+pub struct SchemaInference {
+    max_enum_values: usize,
+    detect_formats: bool,
+}
+
+impl Default for SchemaInference {
+    fn default() -> Self {
+        SchemaInference::new()
+    }
+}
+
+impl SchemaInference {
+    /// Default settings: `enum` has at most 5 values and formats are detected.
+    #[must_use]
+    pub const fn new() -> SchemaInference {
+        SchemaInference {
+            max_enum_values: 5,
+            detect_formats: true,
+        }
+    }
+
+    /// Set the maximum number of distinct strings that are listed in `enum`.
+    /// `0` disables `enum` inference.
+    #[inline]
+    pub const fn with_max_enum_values(&mut self, max_enum_values: usize) -> &mut Self {
+        self.max_enum_values = max_enum_values;
+        self
+    }
+
+    /// Do not infer `format`.
+    #[inline]
+    pub const fn without_formats(&mut self) -> &mut Self {
+        self.detect_formats = false;
+        self
+    }
+
+    /// Infer a schema from `samples`. With no samples, the result accepts any instance.
+    pub fn infer<'a, I>(&self, samples: I) -> Value
+    where
+        I: IntoIterator<Item = &'a Value>,
+    {
+        let mut shape = Shape::default();
+        for sample in samples {
+            shape.observe(sample, self);
+        }
+        let mut schema = Map::new();
+        schema.insert("$schema".to_string(), Value::String(DRAFT7_URI.to_string()));
+        if let Value::Object(inferred) = shape.into_schema() {
+            schema.extend(inferred);
+        }
+        Value::Object(schema)
+    }
+}
+
+/// Everything observed at a single location across all samples.
+#[derive(Debug, Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    number: bool,
+    string: Option<Strings>,
+    items: Option<Box<Shape>>,
+    // Number of observed objects
+    objects: usize,
+    // Property -> shape & number of objects it is present in
+    properties: BTreeMap<String, (Shape, usize)>,
+}
+
+#[derive(Debug)]
+struct Strings {
+    count: usize,
+    // `None` once there are more distinct values than allowed in `enum`
+    distinct: Option<BTreeSet<String>>,
+    // Formats that all observed strings match
+    formats: Vec<&'static str>,
+}
+
+impl Shape {
+    fn observe(&mut self, value: &Value, settings: &SchemaInference) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(number) if number.is_i64() || number.is_u64() => self.integer = true,
+            Value::Number(_) => self.number = true,
+            Value::String(string) => {
+                let strings = self.string.get_or_insert_with(|| Strings {
+                    count: 0,
+                    distinct: Some(BTreeSet::new()),
+                    formats: if settings.detect_formats {
+                        FORMATS.to_vec()
+                    } else {
+                        Vec::new()
+                    },
+                });
+                strings.count += 1;
+                if let Some(distinct) = &mut strings.distinct {
+                    distinct.insert(string.clone());
+                    if distinct.len() > settings.max_enum_values {
+                        strings.distinct = None;
+                    }
+                }
+                strings
+                    .formats
+                    .retain(|format| matches_format(format, value));
+            }
+            Value::Array(items) => {
+                let shape = self.items.get_or_insert_with(Box::default);
+                for item in items {
+                    shape.observe(item, settings);
+                }
+            }
+            Value::Object(object) => {
+                self.objects += 1;
+                for (key, value) in object {
+                    let (shape, count) = self.properties.entry(key.clone()).or_default();
+                    shape.observe(value, settings);
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    fn into_schema(self) -> Value {
+        let mut types = Vec::new();
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if self.number {
+            types.push("number");
+        } else if self.integer {
+            types.push("integer");
+        }
+        if self.string.is_some() {
+            types.push("string");
+        }
+        if self.items.is_some() {
+            types.push("array");
+        }
+        if self.objects > 0 {
+            types.push("object");
+        }
+        let mut schema = Map::new();
+        match types.as_slice() {
+            [] => return Value::Object(schema),
+            [type_] => {
+                schema.insert("type".to_string(), Value::String((*type_).to_string()));
+            }
+            _ => {
+                schema.insert(
+                    "type".to_string(),
+                    Value::Array(types.iter().map(|type_| Value::from(*type_)).collect()),
+                );
+            }
+        }
+        if let Some(strings) = self.string {
+            match strings.distinct {
+                // `enum` applies to all types, therefore it is only used for strings alone
+                Some(distinct) if types.len() == 1 && distinct.len() < strings.count => {
+                    schema.insert(
+                        "enum".to_string(),
+                        Value::Array(distinct.into_iter().map(Value::String).collect()),
+                    );
+                }
+                _ => {
+                    if let Some(format) = strings.formats.first() {
+                        schema.insert("format".to_string(), Value::String((*format).to_string()));
+                    }
+                }
+            }
+        }
+        if let Some(items) = self.items {
+            let items = items.into_schema();
+            match &items {
+                Value::Object(object) if object.is_empty() => {}
+                _ => {
+                    schema.insert("items".to_string(), items);
+                }
+            }
+        }
+        if self.objects > 0 {
+            let objects = self.objects;
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            for (name, (shape, count)) in self.properties {
+                if count == objects {
+                    required.push(Value::String(name.clone()));
+                }
+                properties.insert(name, shape.into_schema());
+            }
+            if !properties.is_empty() {
+                schema.insert("properties".to_string(), Value::Object(properties));
+            }
+            if !required.is_empty() {
+                schema.insert("required".to_string(), Value::Array(required));
+            }
+        }
+        Value::Object(schema)
+    }
+}
+
+fn matches_format(format: &str, value: &Value) -> bool {
+    match FORMAT_VALIDATORS.iter().find(|(name, _)| *name == format) {
+        Some((_, validator)) => validator.is_valid(value),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{infer, SchemaInference};
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(&[json!(1), json!(2)], &json!({"type": "integer"}))]
+    #[test_case(&[json!(1), json!(2.5)], &json!({"type": "number"}))]
+    #[test_case(&[json!(1), json!(null), json!(true)], &json!({"type": ["null", "boolean", "integer"]}))]
+    #[test_case(&[json!("a"), json!("b"), json!("a")], &json!({"type": "string", "enum": ["a", "b"]}))]
+    #[test_case(&[json!("a"), json!("b")], &json!({"type": "string"}); "unique strings")]
+    #[test_case(&[json!("a"), json!(1), json!("a")], &json!({"type": ["integer", "string"]}); "mixed types")]
+    #[test_case(&[json!("2021-01-01"), json!("2021-02-01")], &json!({"type": "string", "format": "date"}))]
+    #[test_case(&[json!("2021-01-01"), json!("today")], &json!({"type": "string"}); "not all match format")]
+    #[test_case(&[json!("10.0.0.1"), json!("127.0.0.1")], &json!({"type": "string", "format": "ipv4"}))]
+    #[test_case(&[json!("http://example.com"), json!("ftp://example.com")], &json!({"type": "string", "format": "uri"}))]
+    #[test_case(&[json!([1, 2]), json!([])], &json!({"type": "array", "items": {"type": "integer"}}))]
+    #[test_case(&[json!([]), json!([])], &json!({"type": "array"}); "empty arrays")]
+    #[test_case(
+        &[json!({"a": 1, "b": {"c": "x"}}), json!({"a": 2, "b": {}})],
+        &json!({
+            "type": "object",
+            "properties": {"a": {"type": "integer"}, "b": {"type": "object", "properties": {"c": {"type": "string"}}}},
+            "required": ["a", "b"]
+        })
+    )]
+    #[test_case(&[], &json!({}))]
+    fn inferred(samples: &[Value], expected: &Value) {
+        let mut schema = infer(samples);
+        assert_eq!(
+            schema
+                .as_object_mut()
+                .and_then(|schema| schema.remove("$schema")),
+            Some(json!("http://json-schema.org/draft-07/schema#"))
+        );
+        assert_eq!(&schema, expected);
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        for sample in samples {
+            assert!(compiled.is_valid(sample), "{}", sample);
+        }
+    }
+
+    #[test]
+    fn settings() {
+        let samples = vec![json!("2021-01-01"), json!("2021-01-01")];
+        let schema = SchemaInference::new()
+            .with_max_enum_values(0)
+            .without_formats()
+            .infer(&samples);
+        assert_eq!(
+            schema,
+            json!({"$schema": "http://json-schema.org/draft-07/schema#", "type": "string"})
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Cooperative interruption of long-running validations.
+//!
+//! An [`Interrupt`] is passed to [`JSONSchema::validate_with_interrupt`],
+//! [`JSONSchema::is_valid_with_interrupt`] or [`JSONSchema::apply_with_interrupt`]. The deadline
+//! and the cancellation token are checked periodically between (sub-)schema evaluations and before
+//! every `pattern` & `$ref`; once
+//! either fires, the remaining evaluation is skipped and the validation fails with
+//! [`ValidationErrorKind::Interrupted`]. A single keyword (e.g. a `pattern` match) is never
+//! interrupted in the middle.
+//!
+//! ```rust
+//! use jsonschema::{
+//!     error::{Interruption, ValidationErrorKind},
+//!     interrupt::{CancellationToken, Interrupt},
+//!     JSONSchema,
+//! };
+//! use serde_json::json;
+//! use std::time::Duration;
+//!
+//! let schema = JSONSchema::compile(&json!({"items": {"type": "integer"}})).expect("Valid schema");
+//! let token = CancellationToken::new();
+//! let mut interrupt = Interrupt::new();
+//! interrupt
+//!     .with_timeout(Duration::from_secs(1))
+//!     .with_token(token.clone());
+//! let instance = json!([1, 2, 3]);
+//! assert!(schema.validate_with_interrupt(&instance, &interrupt).is_ok());
+//! // E.g. from another thread
+//! token.cancel();
+//! let errors: Vec<_> = schema
+//!     .validate_with_interrupt(&instance, &interrupt)
+//!     .expect_err("Cancelled")
+//!     .collect();
+//! assert!(matches!(
+//!     errors[0].kind,
+//!     ValidationErrorKind::Interrupted { reason: Interruption::Cancelled }
+//! ));
+//! ```
+//!
+//! [`JSONSchema::validate_with_interrupt`]: crate::JSONSchema::validate_with_interrupt
+//! [`JSONSchema::is_valid_with_interrupt`]: crate::JSONSchema::is_valid_with_interrupt
+//! [`JSONSchema::apply_with_interrupt`]: crate::JSONSchema::apply_with_interrupt
+//! [`ValidationErrorKind::Interrupted`]: crate::error::ValidationErrorKind::Interrupted
+use crate::error::Interruption;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A flag shared between a validation and the code that may want to stop it.
+/// Clones refer to the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop all validations that use this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `cancel` was called on this token or any of its clones.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Conditions for stopping a validation early.
+#[derive(Debug, Clone, Default)]
+pub struct Interrupt {
+    deadline: Option<Instant>,
+    token: Option<CancellationToken>,
+}
+
+impl Interrupt {
+    /// Create an interrupt that never fires.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the validation once `deadline` has passed.
+    #[inline]
+    pub const fn with_deadline(&mut self, deadline: Instant) -> &mut Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop the validation once `timeout` has elapsed since this call.
+    #[inline]
+    pub fn with_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Stop the validation once `token` is cancelled.
+    #[inline]
+    pub fn with_token(&mut self, token: CancellationToken) -> &mut Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Whether the validation should stop now.
+    pub(crate) fn check(&self) -> Option<Interruption> {
+        if matches!(&self.token, Some(token) if token.is_cancelled()) {
+            return Some(Interruption::Cancelled);
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Some(Interruption::Timeout),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CancellationToken, Interrupt};
+    use crate::{
+        error::{Interruption, ValidationErrorKind},
+        JSONSchema,
+    };
+    use serde_json::{json, Value};
+    use std::time::{Duration, Instant};
+    use test_case::test_case;
+
+    lazy_static::lazy_static! {
+        static ref TOKEN: CancellationToken = CancellationToken::new();
+    }
+
+    fn assert_interrupted(
+        schema: &JSONSchema,
+        instance: &Value,
+        interrupt: &Interrupt,
+        expected: Interruption,
+    ) {
+        let errors: Vec<_> = schema
+            .validate_with_interrupt(instance, interrupt)
+            .expect_err("Interrupted")
+            .collect();
+        assert_eq!(errors.len(), 1);
+        match &errors[0].kind {
+            ValidationErrorKind::Interrupted { reason } => assert_eq!(*reason, expected),
+            kind => panic!("Unexpected error: {:?}", kind),
+        }
+        assert!(!schema.is_valid_with_interrupt(instance, interrupt));
+        assert!(!schema
+            .apply_with_interrupt(instance, interrupt)
+            .basic()
+            .is_valid());
+        assert!(!schema.apply_with_interrupt(instance, interrupt).flag());
+    }
+
+    #[test_case(&json!(1))]
+    #[test_case(&json!([1, 2, 3]))]
+    fn not_fired(instance: &Value) {
+        let schema =
+            JSONSchema::compile(&json!({"items": {"type": "integer"}})).expect("Valid schema");
+        let mut interrupt = Interrupt::new();
+        interrupt
+            .with_timeout(Duration::from_secs(60))
+            .with_token(CancellationToken::new());
+        assert!(schema.validate_with_interrupt(instance, &interrupt).is_ok());
+        assert!(schema.is_valid_with_interrupt(instance, &interrupt));
+        assert!(schema
+            .apply_with_interrupt(instance, &interrupt)
+            .basic()
+            .is_valid());
+        // Regular errors are reported as usual
+        assert!(schema
+            .validate_with_interrupt(&json!(["a"]), &interrupt)
+            .is_err());
+    }
+
+    #[test]
+    fn deadline_passed() {
+        let schema = JSONSchema::compile(&json!({"type": "integer"})).expect("Valid schema");
+        let mut interrupt = Interrupt::new();
+        interrupt.with_deadline(Instant::now());
+        assert_interrupted(&schema, &json!(1), &interrupt, Interruption::Timeout);
+        // Without the interrupt the validation is not affected
+        assert!(schema.is_valid(&json!(1)));
+    }
+
+    #[test]
+    fn cancelled_before_start() {
+        let schema = JSONSchema::compile(&json!({"type": "integer"})).expect("Valid schema");
+        let token = CancellationToken::new();
+        let mut interrupt = Interrupt::new();
+        interrupt.with_token(token.clone());
+        token.cancel();
+        assert!(token.is_cancelled());
+        assert_interrupted(&schema, &json!(1), &interrupt, Interruption::Cancelled);
+    }
+
+    #[test]
+    fn cancelled_during_validation() {
+        fn cancel(_: &str) -> bool {
+            TOKEN.cancel();
+            true
+        }
+        let schema = JSONSchema::options()
+            .with_format("cancel", cancel)
+            .compile(&json!({"items": {"format": "cancel"}}))
+            .expect("Valid schema");
+        let mut interrupt = Interrupt::new();
+        interrupt.with_token(TOKEN.clone());
+        let instance = Value::from(vec!["a"; 1000]);
+        let errors: Vec<_> = schema
+            .validate_with_interrupt(&instance, &interrupt)
+            .expect_err("Interrupted")
+            .collect();
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::Interrupted {
+                reason: Interruption::Cancelled
+            }
+        ));
+    }
+
+    #[test_case(&json!({"items": [{"format": "slow"}, {"pattern": "^a"}]}); "pattern")]
+    #[test_case(&json!({"items": [{"format": "slow"}, {"$ref": "#/definitions/a"}], "definitions": {"a": {}}}); "reference")]
+    fn slow_keyword(schema: &Value) {
+        fn slow(_: &str) -> bool {
+            std::thread::sleep(Duration::from_millis(20));
+            true
+        }
+        // Far fewer nodes than the check interval
+        let schema = JSONSchema::options()
+            .with_format("slow", slow)
+            .compile(schema)
+            .expect("Valid schema");
+        let instance = json!(["a", "a"]);
+        let mut interrupt = Interrupt::new();
+        interrupt.with_timeout(Duration::from_millis(10));
+        let errors: Vec<_> = schema
+            .validate_with_interrupt(&instance, &interrupt)
+            .expect_err("Interrupted")
+            .collect();
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::Interrupted {
+                reason: Interruption::Timeout
+            }
+        ));
+        interrupt.with_timeout(Duration::from_millis(10));
+        assert!(!schema.is_valid_with_interrupt(&instance, &interrupt));
+    }
+
+    #[test]
+    fn with_limits() {
+        let schema = JSONSchema::options()
+            .with_max_evaluated_nodes(1000)
+            .compile(&json!({"type": "integer"}))
+            .expect("Valid schema");
+        let mut interrupt = Interrupt::new();
+        interrupt.with_deadline(Instant::now());
+        assert_interrupted(&schema, &json!(1), &interrupt, Interruption::Timeout);
+        assert!(schema.is_valid(&json!(1)));
+    }
+}
@@ -0,0 +1,353 @@
+//! Read-only access to the compiled validation tree.
+//!
+//! A compiled schema is a tree of nodes. Each node corresponds to a (sub-)schema and consists of
+//! keywords, and keywords that apply sub-schemas (e.g. `properties` or `allOf`) have child nodes.
+//! Targets of `$ref` are compiled into a separate arena shared by all references and are not a
+//! part of the tree, but they are available via [`Keyword::target`].
+//!
+//! ```rust
+//! use jsonschema::{introspection::{Keyword, Visitor}, JSONSchema};
+//! use serde_json::json;
+//!
+//! /// Collects all required properties
+//! struct Required(Vec<String>);
+//!
+//! impl Visitor for Required {
+//!     fn visit_keyword(&mut self, keyword: &Keyword<'_>) {
+//!         if keyword.name() == Some("required") {
+//!             if let Some(required) = keyword.value().and_then(|value| value.as_array()) {
+//!                 for property in required.iter().filter_map(|value| value.as_str()) {
+//!                     self.0.push(format!("{}/{}", keyword.schema_path(), property));
+//!                 }
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let schema = json!({
+//!     "required": ["name"],
+//!     "properties": {"address": {"required": ["city"]}}
+//! });
+//! let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+//! let mut visitor = Required(vec![]);
+//! compiled.walk(&mut visitor);
+//! assert_eq!(visitor.0, vec!["/properties/address/required/city", "/required/name"]);
+//! ```
+use crate::{
+    compilation::arena::Arena, keywords::BoxedValidator, paths::JSONPointer,
+    schema_node::SchemaNode,
+};
+use serde_json::Value;
+use std::fmt;
+
+/// The shape of a compiled schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// A boolean schema: `true` or `false`.
+    Boolean(bool),
+    /// An object schema composed of keywords.
+    Keywords,
+    /// An array-valued schema, e.g. a list of property names in `dependencies`.
+    Array,
+}
+
+/// A (sub-)schema in the compiled tree.
+#[derive(Debug, Clone, Copy)]
+pub struct Node<'a> {
+    node: &'a SchemaNode,
+    // The raw schema `schema_path` is relative to
+    base: &'a Value,
+    references: &'a Arena,
+}
+
+impl<'a> Node<'a> {
+    pub(crate) const fn new(
+        node: &'a SchemaNode,
+        base: &'a Value,
+        references: &'a Arena,
+    ) -> Node<'a> {
+        Node {
+            node,
+            base,
+            references,
+        }
+    }
+
+    /// Location of this schema within the root schema. For nodes reached via
+    /// [`Keyword::target`], the location is relative to the `$ref` target.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn schema_path(&self) -> &'a JSONPointer {
+        self.node.relative_path()
+    }
+
+    /// The shape of this schema.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn kind(&self) -> NodeKind {
+        match self.node.as_boolean() {
+            Some(value) => NodeKind::Boolean(value),
+            None if self.node.is_array() => NodeKind::Array,
+            None => NodeKind::Keywords,
+        }
+    }
+
+    /// The raw schema this node was compiled from.
+    #[must_use]
+    pub fn value(&self) -> Option<&'a Value> {
+        self.base.pointer(&self.schema_path().to_string())
+    }
+
+    /// Compiled keywords of this schema. Keywords that don't affect validation (e.g.
+    /// `title`) are not included, and some keywords are compiled together (e.g. `properties`
+    /// is a part of `additionalProperties` if both are present).
+    #[must_use]
+    pub fn keywords(&self) -> Vec<Keyword<'a>> {
+        self.node
+            .keyword_validators()
+            .into_iter()
+            .map(|(name, validator)| Keyword {
+                name,
+                validator,
+                node: *self,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+/// A compiled keyword of a schema.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyword<'a> {
+    name: Option<&'a str>,
+    validator: &'a BoxedValidator,
+    node: Node<'a>,
+}
+
+impl<'a> Keyword<'a> {
+    /// Keyword name. Validators of array-valued & boolean schemas don't have names.
+    #[must_use]
+    pub const fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+
+    /// Location of this keyword within the root schema.
+    #[must_use]
+    pub fn schema_path(&self) -> JSONPointer {
+        match self.name {
+            Some(name) => self.node.schema_path().clone_with(name.to_string()),
+            None => self.node.schema_path().clone(),
+        }
+    }
+
+    /// The raw keyword value.
+    #[must_use]
+    pub fn value(&self) -> Option<&'a Value> {
+        match self.name {
+            Some(name) => self.node.value()?.get(name),
+            None => self.node.value(),
+        }
+    }
+
+    /// Sub-schemas applied by this keyword.
+    #[must_use]
+    pub fn children(&self) -> Vec<Node<'a>> {
+        self.validator
+            .children()
+            .into_iter()
+            .map(|child| Node::new(child, self.node.base, self.node.references))
+            .collect()
+    }
+
+    /// The compiled target of a `$ref` keyword. `None` for other keywords and references that
+    /// can't be resolved. Targets may come from other documents, and they are not visited by
+    /// [`Visitor`] to avoid infinite recursion.
+    #[must_use]
+    pub fn target(&self) -> Option<Node<'a>> {
+        let references = self.node.references;
+        self.validator
+            .reference_target()
+            .and_then(|index| references.target(index))
+            .map(|(node, value)| Node::new(node, value, references))
+    }
+
+    /// The node this keyword belongs to.
+    #[must_use]
+    pub const fn node(&self) -> Node<'a> {
+        self.node
+    }
+}
+
+impl fmt::Display for Keyword<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.validator.fmt(f)
+    }
+}
+
+/// A visitor over the compiled tree. Nodes are visited depth-first, each node before its
+/// keywords, and each keyword before its children.
+pub trait Visitor {
+    /// Called for every node.
+    fn visit_node(&mut self, _node: &Node<'_>) {}
+    /// Called for every keyword.
+    fn visit_keyword(&mut self, _keyword: &Keyword<'_>) {}
+}
+
+pub(crate) fn walk<V: Visitor + ?Sized>(node: &Node<'_>, visitor: &mut V) {
+    visitor.visit_node(node);
+    for keyword in node.keywords() {
+        visitor.visit_keyword(&keyword);
+        for child in keyword.children() {
+            walk(&child, visitor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Keyword, Node, NodeKind, Visitor};
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[derive(Default)]
+    struct Collect {
+        nodes: Vec<(String, NodeKind)>,
+        keywords: Vec<(Option<String>, String)>,
+    }
+
+    impl Visitor for Collect {
+        fn visit_node(&mut self, node: &Node<'_>) {
+            self.nodes
+                .push((node.schema_path().to_string(), node.kind()));
+        }
+        fn visit_keyword(&mut self, keyword: &Keyword<'_>) {
+            self.keywords.push((
+                keyword.name().map(str::to_string),
+                keyword.schema_path().to_string(),
+            ));
+        }
+    }
+
+    fn collect(schema: &Value) -> Collect {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        let mut visitor = Collect::default();
+        compiled.walk(&mut visitor);
+        visitor
+    }
+
+    #[test_case(&json!({"properties": {"a": {"type": "string"}}}), &["", "/properties/a"])]
+    #[test_case(&json!({"properties": {"a": true}, "additionalProperties": false}), &["", "/properties/a"])]
+    #[test_case(&json!({"allOf": [{}, {"not": {}}]}), &["", "/allOf/0", "/allOf/1", "/allOf/1/not"])]
+    #[test_case(&json!({"if": {}, "then": {}, "else": {}}), &["", "/if", "/then", "/else"])]
+    #[test_case(&json!({"items": [{}, {}], "additionalItems": {}}), &["", "/additionalItems", "/items/0", "/items/1"])]
+    #[test_case(&json!({"dependencies": {"a": {}}}), &["", "/dependencies/a"])]
+    #[test_case(&json!({"dependencies": {"a": ["b"]}}), &["", "/dependencies/a"])]
+    #[test_case(&json!({"$ref": "#/definitions/a", "definitions": {"a": {}}}), &[""])]
+    fn nodes(schema: &Value, expected: &[&str]) {
+        let paths: Vec<_> = collect(schema)
+            .nodes
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test_case(&json!(true), NodeKind::Boolean(true))]
+    #[test_case(&json!(false), NodeKind::Boolean(false))]
+    #[test_case(&json!({}), NodeKind::Keywords)]
+    fn kind(schema: &Value, expected: NodeKind) {
+        assert_eq!(collect(schema).nodes[0].1, expected);
+    }
+
+    #[test]
+    fn array_kind() {
+        let visitor = collect(&json!({"dependencies": {"a": ["b"]}}));
+        assert_eq!(visitor.nodes[1].1, NodeKind::Array);
+    }
+
+    #[test]
+    fn keywords() {
+        let visitor = collect(&json!({"properties": {"a": {"minLength": 1}}, "title": "Ignored"}));
+        assert_eq!(
+            visitor.keywords,
+            vec![
+                (Some("properties".to_string()), "/properties".to_string()),
+                (
+                    Some("minLength".to_string()),
+                    "/properties/a/minLength".to_string()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn keyword_value() {
+        let schema = json!({"properties": {"a": {"maxLength": 5}}});
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        let root = compiled.root();
+        let properties = &root.keywords()[0];
+        let child = properties.children()[0];
+        assert_eq!(child.value(), Some(&json!({"maxLength": 5})));
+        let max_length = &child.keywords()[0];
+        assert_eq!(max_length.value(), Some(&json!(5)));
+        assert_eq!(max_length.to_string(), "maxLength: 5");
+    }
+
+    #[test]
+    fn array_dependency_value() {
+        // Array-valued dependencies are nodes of their own, located at the dependency
+        let schema = json!({"dependencies": {"a": ["b"]}});
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        let dependency = compiled.root().keywords()[0].children()[0];
+        assert_eq!(dependency.schema_path().to_string(), "/dependencies/a");
+        assert_eq!(dependency.value(), Some(&json!(["b"])));
+    }
+
+    #[test_case(&json!({"$ref": "#/definitions/a", "definitions": {"a": {"items": {"minimum": 1}}}}); "local")]
+    #[test_case(&json!({"$ref": "http://example.com/a.json#/definitions/a"}); "external")]
+    fn reference_target(schema: &Value) {
+        let compiled = JSONSchema::options()
+            .with_document(
+                "http://example.com/a.json".to_string(),
+                json!({"definitions": {"a": {"items": {"minimum": 1}}}}),
+            )
+            .compile(schema)
+            .expect("Valid schema");
+        let root = compiled.root();
+        let reference = &root.keywords()[0];
+        assert_eq!(reference.name(), Some("$ref"));
+        assert!(reference.children().is_empty());
+        let target = reference.target().expect("Resolvable");
+        assert_eq!(target.value(), Some(&json!({"items": {"minimum": 1}})));
+        let items = &target.keywords()[0];
+        let minimum = &items.children()[0].keywords()[0];
+        assert_eq!(minimum.schema_path().to_string(), "/items/minimum");
+        assert_eq!(minimum.value(), Some(&json!(1)));
+        assert!(minimum.target().is_none());
+    }
+
+    #[test]
+    fn recursive_target() {
+        let schema = json!({"properties": {"child": {"$ref": "#"}}});
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        let properties = &compiled.root().keywords()[0];
+        let reference = &properties.children()[0].keywords()[0];
+        let target = reference.target().expect("Resolvable");
+        assert_eq!(target.value(), Some(&schema));
+        // Walking doesn't follow references
+        assert_eq!(collect(&schema).nodes.len(), 2);
+    }
+
+    #[test]
+    fn unresolvable_target() {
+        let schema = json!({"$ref": "#/definitions/missing"});
+        let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+        assert!(compiled.root().keywords()[0].target().is_none());
+    }
+}
@@ -27,6 +27,10 @@ impl AdditionalItemsObjectValidator {
     }
 }
 impl Validate for AdditionalItemsObjectValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items
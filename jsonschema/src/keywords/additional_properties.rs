@@ -8,15 +8,18 @@
 //! Each valid combination of these keywords has a validator here.
 use crate::{
     compilation::{compile_validators, context::CompilationContext, JSONSchema},
+    ecma::Regex,
     error::{error, no_error, ErrorIterator, ValidationError},
-    keywords::CompilationResult,
+    keywords::{
+        pattern_properties::{apply_matched, compile_pattern, is_valid_matched, validate_matched},
+        CompilationResult,
+    },
     output::{Annotations, BasicOutput, OutputUnit},
     paths::{AbsolutePath, InstancePath, JSONPointer},
     schema_node::SchemaNode,
     validator::{format_validators, PartialApplication, Validate},
 };
 use ahash::AHashMap;
-use fancy_regex::Regex;
 use serde_json::{Map, Value};
 
 pub(crate) type PatternedValidators = Vec<(Regex, SchemaNode)>;
@@ -25,6 +28,7 @@ pub(crate) type PatternedValidators = Vec<(Regex, SchemaNode)>;
 pub(crate) trait PropertiesValidatorsMap: Send + Sync {
     fn get_validator(&self, property: &str) -> Option<&SchemaNode>;
     fn get_key_validator(&self, property: &str) -> Option<(&String, &SchemaNode)>;
+    fn nodes(&self) -> Vec<&SchemaNode>;
 }
 
 // Iterating over a small vector and comparing strings is faster than a map lookup
@@ -51,6 +55,9 @@ impl PropertiesValidatorsMap for SmallValidatorsMap {
         }
         None
     }
+    fn nodes(&self) -> Vec<&SchemaNode> {
+        self.iter().map(|(_, node)| node).collect()
+    }
 }
 
 impl PropertiesValidatorsMap for BigValidatorsMap {
@@ -63,6 +70,9 @@ impl PropertiesValidatorsMap for BigValidatorsMap {
     fn get_key_validator(&self, property: &str) -> Option<(&String, &SchemaNode)> {
         self.get_key_value(property)
     }
+    fn nodes(&self) -> Vec<&SchemaNode> {
+        self.values().collect()
+    }
 }
 
 macro_rules! dynamic_map {
@@ -99,16 +109,27 @@ macro_rules! is_valid_pattern_schema {
     }};
 }
 
+/// Evaluates to whether `$property` matches `$re`. Returns `false` if the value is invalid
+/// against the sub-schema of the matched pattern.
+macro_rules! is_valid_matched_pattern {
+    ($re:expr, $node:expr, $schema:ident, $property:ident, $value:ident) => {{
+        match is_valid_matched($re, $node, $schema, $property, $value) {
+            // Matched & valid - check the next pattern
+            Some(true) => true,
+            // Invalid - there is no reason to check other patterns
+            Some(false) => return false,
+            None => false,
+        }
+    }};
+}
+
 macro_rules! is_valid_patterns {
     ($schema:ident, $patterns:expr, $property:ident, $value:ident) => {{
         // One property may match multiple patterns, therefore we need to check them all
         let mut has_match = false;
         for (re, node) in $patterns {
             // If there is a match, then the value should match the sub-schema
-            if re.is_match($property).unwrap_or(false) {
-                has_match = true;
-                is_valid_pattern_schema!(node, $schema, $value)
-            }
+            has_match |= is_valid_matched_pattern!(re, node, $schema, $property, $value);
         }
         if !has_match {
             // No pattern matched - INVALID property
@@ -186,6 +207,10 @@ impl AdditionalPropertiesValidator {
     }
 }
 impl Validate for AdditionalPropertiesValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             item.values().all(|i| self.node.is_valid(schema, i))
@@ -347,6 +372,10 @@ impl AdditionalPropertiesNotEmptyFalseValidator<BigValidatorsMap> {
     }
 }
 impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseValidator<M> {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.properties.nodes()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             for (property, value) in item {
@@ -488,6 +517,12 @@ impl AdditionalPropertiesNotEmptyValidator<BigValidatorsMap> {
     }
 }
 impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValidator<M> {
+    fn children(&self) -> Vec<&SchemaNode> {
+        let mut children = self.properties.nodes();
+        children.push(&self.node);
+        children
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(map) = instance {
             for (property, value) in map {
@@ -622,15 +657,18 @@ impl AdditionalPropertiesWithPatternsValidator {
     }
 }
 impl Validate for AdditionalPropertiesWithPatternsValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        let mut children: Vec<_> = self.patterns.iter().map(|(_, node)| node).collect();
+        children.push(&self.node);
+        children
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             for (property, value) in item.iter() {
                 let mut has_match = false;
                 for (re, node) in &self.patterns {
-                    if re.is_match(property).unwrap_or(false) {
-                        has_match = true;
-                        is_valid_pattern_schema!(node, schema, value)
-                    }
+                    has_match |= is_valid_matched_pattern!(re, node, schema, property, value);
                 }
                 if !has_match && !is_valid!(self.node, schema, value) {
                     return false;
@@ -653,10 +691,12 @@ impl Validate for AdditionalPropertiesWithPatternsValidator {
                 errors.extend(
                     self.patterns
                         .iter()
-                        .filter(|(re, _)| re.is_match(property).unwrap_or(false))
-                        .flat_map(|(_, node)| {
+                        .filter_map(|(re, node)| {
+                            validate_matched(re, node, schema, property, value, instance_path)
+                        })
+                        .flat_map(|errors| {
                             has_match = true;
-                            validate!(node, schema, value, instance_path, property)
+                            errors
                         }),
                 );
                 if !has_match {
@@ -683,10 +723,12 @@ impl Validate for AdditionalPropertiesWithPatternsValidator {
                 let path = instance_path.push(property.clone());
                 let mut has_match = false;
                 for (pattern, node) in &self.patterns {
-                    if pattern.is_match(property).unwrap_or(false) {
+                    if let Some(matched) =
+                        apply_matched(pattern, node, schema, property, value, &path)
+                    {
                         has_match = true;
                         pattern_matched_propnames.push(property.clone());
-                        output += node.apply_rooted(schema, value, &path)
+                        output += matched
                     }
                 }
                 if !has_match {
@@ -769,6 +811,10 @@ impl AdditionalPropertiesWithPatternsFalseValidator {
     }
 }
 impl Validate for AdditionalPropertiesWithPatternsFalseValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.patterns.iter().map(|(_, node)| node).collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             // No properties are allowed, except ones defined in `patternProperties`
@@ -793,10 +839,12 @@ impl Validate for AdditionalPropertiesWithPatternsFalseValidator {
                 errors.extend(
                     self.patterns
                         .iter()
-                        .filter(|(re, _)| re.is_match(property).unwrap_or(false))
-                        .flat_map(|(_, node)| {
+                        .filter_map(|(re, node)| {
+                            validate_matched(re, node, schema, property, value, instance_path)
+                        })
+                        .flat_map(|errors| {
                             has_match = true;
-                            validate!(node, schema, value, instance_path, property)
+                            errors
                         }),
                 );
                 if !has_match {
@@ -831,10 +879,12 @@ impl Validate for AdditionalPropertiesWithPatternsFalseValidator {
                 let path = instance_path.push(property.clone());
                 let mut has_match = false;
                 for (pattern, node) in &self.patterns {
-                    if pattern.is_match(property).unwrap_or(false) {
+                    if let Some(matched) =
+                        apply_matched(pattern, node, schema, property, value, &path)
+                    {
                         has_match = true;
                         pattern_matched_props.push(property.clone());
-                        output += node.apply_rooted(schema, value, &path);
+                        output += matched;
                     }
                 }
                 if !has_match {
@@ -943,6 +993,13 @@ impl AdditionalPropertiesWithPatternsNotEmptyValidator<BigValidatorsMap> {
     }
 }
 impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNotEmptyValidator<M> {
+    fn children(&self) -> Vec<&SchemaNode> {
+        let mut children = self.properties.nodes();
+        children.extend(self.patterns.iter().map(|(_, node)| node));
+        children.push(&self.node);
+        children
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             for (property, value) in item.iter() {
@@ -951,9 +1008,7 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
                         // Valid for `properties`, check `patternProperties`
                         for (re, node) in &self.patterns {
                             // If there is a match, then the value should match the sub-schema
-                            if re.is_match(property).unwrap_or(false) {
-                                is_valid_pattern_schema!(node, schema, value)
-                            }
+                            is_valid_matched_pattern!(re, node, schema, property, value);
                         }
                     } else {
                         // INVALID, no reason to check the next one
@@ -963,10 +1018,7 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
                     let mut has_match = false;
                     for (re, node) in &self.patterns {
                         // If there is a match, then the value should match the sub-schema
-                        if re.is_match(property).unwrap_or(false) {
-                            has_match = true;
-                            is_valid_pattern_schema!(node, schema, value)
-                        }
+                        has_match |= is_valid_matched_pattern!(re, node, schema, property, value);
                     }
                     if !has_match && !is_valid!(self.node, schema, value) {
                         return false;
@@ -993,20 +1045,22 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
                     errors.extend(
                         self.patterns
                             .iter()
-                            .filter(|(re, _)| re.is_match(property).unwrap_or(false))
-                            .flat_map(|(_, validators)| {
-                                validate!(validators, schema, value, instance_path, name)
-                            }),
+                            .filter_map(|(re, node)| {
+                                validate_matched(re, node, schema, property, value, instance_path)
+                            })
+                            .flatten(),
                     );
                 } else {
                     let mut has_match = false;
                     errors.extend(
                         self.patterns
                             .iter()
-                            .filter(|(re, _)| re.is_match(property).unwrap_or(false))
-                            .flat_map(|(_, node)| {
+                            .filter_map(|(re, node)| {
+                                validate_matched(re, node, schema, property, value, instance_path)
+                            })
+                            .flat_map(|errors| {
                                 has_match = true;
-                                validate!(node, schema, value, instance_path, property)
+                                errors
                             }),
                     );
                     if !has_match {
@@ -1036,9 +1090,11 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesWithPatternsNo
                 } else {
                     let mut has_match = false;
                     for (pattern, node) in &self.patterns {
-                        if pattern.is_match(property).unwrap_or(false) {
+                        if let Some(matched) =
+                            apply_matched(pattern, node, schema, property, value, &path)
+                        {
                             has_match = true;
-                            output += node.apply_rooted(schema, value, &path);
+                            output += matched;
                         }
                     }
                     if !has_match {
@@ -1134,6 +1190,12 @@ impl AdditionalPropertiesWithPatternsNotEmptyFalseValidator<BigValidatorsMap> {
 impl<M: PropertiesValidatorsMap> Validate
     for AdditionalPropertiesWithPatternsNotEmptyFalseValidator<M>
 {
+    fn children(&self) -> Vec<&SchemaNode> {
+        let mut children = self.properties.nodes();
+        children.extend(self.patterns.iter().map(|(_, node)| node));
+        children
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             // No properties are allowed, except ones defined in `properties` or `patternProperties`
@@ -1143,9 +1205,7 @@ impl<M: PropertiesValidatorsMap> Validate
                         // Valid for `properties`, check `patternProperties`
                         for (re, node) in &self.patterns {
                             // If there is a match, then the value should match the sub-schema
-                            if re.is_match(property).unwrap_or(false) {
-                                is_valid_pattern_schema!(node, schema, value)
-                            }
+                            is_valid_matched_pattern!(re, node, schema, property, value);
                         }
                     } else {
                         // INVALID, no reason to check the next one
@@ -1175,20 +1235,22 @@ impl<M: PropertiesValidatorsMap> Validate
                     errors.extend(
                         self.patterns
                             .iter()
-                            .filter(|(re, _)| re.is_match(property).unwrap_or(false))
-                            .flat_map(|(_, node)| {
-                                validate!(node, schema, value, instance_path, name)
-                            }),
+                            .filter_map(|(re, node)| {
+                                validate_matched(re, node, schema, property, value, instance_path)
+                            })
+                            .flatten(),
                     );
                 } else {
                     let mut has_match = false;
                     errors.extend(
                         self.patterns
                             .iter()
-                            .filter(|(re, _)| re.is_match(property).unwrap_or(false))
-                            .flat_map(|(_, node)| {
+                            .filter_map(|(re, node)| {
+                                validate_matched(re, node, schema, property, value, instance_path)
+                            })
+                            .flat_map(|errors| {
                                 has_match = true;
-                                validate!(node, schema, value, instance_path, property)
+                                errors
                             }),
                     );
                     if !has_match {
@@ -1227,9 +1289,11 @@ impl<M: PropertiesValidatorsMap> Validate
                 } else {
                     let mut has_match = false;
                     for (pattern, node) in &self.patterns {
-                        if pattern.is_match(property).unwrap_or(false) {
+                        if let Some(matched) =
+                            apply_matched(pattern, node, schema, property, value, &path)
+                        {
                             has_match = true;
-                            output += node.apply_rooted(schema, value, &path);
+                            output += matched;
                         }
                     }
                     if !has_match {
@@ -1273,8 +1337,8 @@ pub(crate) fn compile<'a>(
     if let Some(patterns) = parent.get("patternProperties") {
         if let Value::Object(obj) = patterns {
             // Compile all patterns & their validators to avoid doing work in the `patternProperties` validator
-            if let Ok(compiled_patterns) = compile_patterns(obj, context) {
-                match schema {
+            match compile_patterns(obj, context) {
+                Ok(compiled_patterns) => match schema {
                     Value::Bool(true) => None, // "additionalProperties" are "true" by default
                     Value::Bool(false) => {
                         if let Some(properties) = properties {
@@ -1308,9 +1372,8 @@ pub(crate) fn compile<'a>(
                             ))
                         }
                     }
-                }
-            } else {
-                Some(Err(ValidationError::null_schema()))
+                },
+                Err(error) => Some(Err(error)),
             }
         } else {
             Some(Err(ValidationError::null_schema()))
@@ -1356,12 +1419,9 @@ fn compile_patterns<'a>(
     let mut compiled_patterns = Vec::with_capacity(obj.len());
     for (pattern, subschema) in obj {
         let pattern_context = keyword_context.with_path(pattern.to_string());
-        if let Ok(compiled_pattern) = Regex::new(pattern) {
-            if let Ok(node) = compile_validators(subschema, &pattern_context) {
-                compiled_patterns.push((compiled_pattern, node));
-            } else {
-                return Err(ValidationError::schema(subschema));
-            }
+        let compiled_pattern = compile_pattern(pattern, &pattern_context)?;
+        if let Ok(node) = compile_validators(subschema, &pattern_context) {
+            compiled_patterns.push((compiled_pattern, node));
         } else {
             return Err(ValidationError::schema(subschema));
         }
@@ -32,6 +32,10 @@ impl AllOfValidator {
 }
 
 impl Validate for AllOfValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.schemas.iter().collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         self.schemas.iter().all(|n| n.is_valid(schema, instance))
     }
@@ -92,6 +96,10 @@ impl SingleValueAllOfValidator {
 }
 
 impl Validate for SingleValueAllOfValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         self.node.is_valid(schema, instance)
     }
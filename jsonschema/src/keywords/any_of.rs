@@ -40,6 +40,10 @@ impl AnyOfValidator {
 }
 
 impl Validate for AnyOfValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.schemas.iter().collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         self.schemas.iter().any(|s| s.is_valid(schema, instance))
     }
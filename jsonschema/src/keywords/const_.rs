@@ -5,6 +5,7 @@ use crate::{
     validator::Validate,
 };
 use serde_json::{Map, Number, Value};
+#[cfg(not(feature = "arbitrary-precision"))]
 use std::f64::EPSILON;
 
 use crate::paths::{InstancePath, JSONPointer};
@@ -152,6 +153,7 @@ impl core::fmt::Display for ConstNullValidator {
 struct ConstNumberValidator {
     // This is saved in order to ensure that the error message is not altered by precision loss
     original_value: Number,
+    #[cfg(not(feature = "arbitrary-precision"))]
     value: f64,
     schema_path: JSONPointer,
 }
@@ -161,6 +163,7 @@ impl ConstNumberValidator {
     pub(crate) fn compile(original_value: &Number, schema_path: JSONPointer) -> CompilationResult {
         Ok(Box::new(ConstNumberValidator {
             original_value: original_value.clone(),
+            #[cfg(not(feature = "arbitrary-precision"))]
             value: original_value
                 .as_f64()
                 .expect("A JSON number will always be representable as f64"),
@@ -188,6 +191,7 @@ impl Validate for ConstNumberValidator {
         }
     }
 
+    #[cfg(not(feature = "arbitrary-precision"))]
     fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
             (self.value - item.as_f64().expect("Always representable as f64")).abs() < EPSILON
@@ -195,6 +199,15 @@ impl Validate for ConstNumberValidator {
             false
         }
     }
+
+    #[cfg(feature = "arbitrary-precision")]
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        if let Value::Number(item) = instance {
+            helpers::equal_numbers(&self.original_value, item)
+        } else {
+            false
+        }
+    }
 }
 
 impl core::fmt::Display for ConstNumberValidator {
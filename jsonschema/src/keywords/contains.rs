@@ -29,6 +29,10 @@ impl ContainsValidator {
 }
 
 impl Validate for ContainsValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items.iter().any(|i| self.node.is_valid(schema, i))
@@ -128,6 +132,10 @@ impl MinContainsValidator {
 }
 
 impl Validate for MinContainsValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn validate<'a, 'b>(
         &self,
         schema: &'a JSONSchema,
@@ -227,6 +235,10 @@ impl MaxContainsValidator {
 }
 
 impl Validate for MaxContainsValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn validate<'a, 'b>(
         &self,
         schema: &'a JSONSchema,
@@ -336,6 +348,10 @@ impl MinMaxContainsValidator {
 }
 
 impl Validate for MinMaxContainsValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn validate<'a, 'b>(
         &self,
         schema: &'a JSONSchema,
@@ -30,7 +30,7 @@ impl DependenciesValidator {
                             (&keyword_context.schema_path).into(),
                         )
                         .expect("The required validator compilation does not return None")?];
-                        SchemaNode::new_from_array(&keyword_context, validators)
+                        SchemaNode::new_from_array(&item_context, validators)
                     }
                     _ => compile_validators(subschema, &item_context)?,
                 };
@@ -44,6 +44,10 @@ impl DependenciesValidator {
 }
 
 impl Validate for DependenciesValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.dependencies.iter().map(|(_, node)| node).collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             self.dependencies
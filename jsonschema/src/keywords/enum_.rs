@@ -1,12 +1,34 @@
 use crate::{
     compilation::{context::CompilationContext, JSONSchema},
     error::{error, no_error, ErrorIterator, ValidationError},
-    keywords::{helpers, CompilationResult},
+    keywords::{helpers, unique_items::HashedValue, CompilationResult},
     paths::{InstancePath, JSONPointer},
     primitive_type::{PrimitiveType, PrimitiveTypesBitMap},
     validator::Validate,
 };
-use serde_json::{Map, Value};
+use ahash::{AHashMap, AHashSet, AHasher};
+use serde_json::{Map, Number, Value};
+use std::hash::{Hash, Hasher};
+
+// Comparing a few items one by one is cheaper than hashing the instance
+const ITEMS_SIZE_THRESHOLD: usize = 16;
+
+fn format_items(f: &mut std::fmt::Formatter<'_>, items: &[Value]) -> std::fmt::Result {
+    write!(
+        f,
+        "enum: [{}]",
+        items
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+/// Items of the `enum` keyword value.
+fn items(options: &Value) -> &[Value] {
+    options.as_array().map_or(&[], Vec::as_slice)
+}
 
 #[derive(Debug)]
 pub(crate) struct EnumValidator {
@@ -70,18 +92,227 @@ impl Validate for EnumValidator {
 
 impl core::fmt::Display for EnumValidator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "enum: [{}]",
-            self.items
-                .iter()
-                .map(Value::to_string)
-                .collect::<Vec<String>>()
-                .join(", ")
+        format_items(f, &self.items)
+    }
+}
+
+/// A large enum of strings.
+#[derive(Debug)]
+pub(crate) struct StringEnumValidator {
+    options: Value,
+    strings: AHashSet<String>,
+    schema_path: JSONPointer,
+}
+
+impl StringEnumValidator {
+    #[inline]
+    pub(crate) fn compile<'a>(
+        schema: &'a Value,
+        items: &'a [Value],
+        schema_path: JSONPointer,
+    ) -> CompilationResult<'a> {
+        let strings = items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        Ok(Box::new(StringEnumValidator {
+            options: schema.clone(),
+            strings,
+            schema_path,
+        }))
+    }
+}
+
+impl Validate for StringEnumValidator {
+    fn validate<'b>(
+        &self,
+        schema: &JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        if self.is_valid(schema, instance) {
+            no_error()
+        } else {
+            error(ValidationError::enumeration(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                &self.options,
+            ))
+        }
+    }
+
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        if let Value::String(instance) = instance {
+            self.strings.contains(instance.as_str())
+        } else {
+            false
+        }
+    }
+}
+
+impl core::fmt::Display for StringEnumValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_items(f, items(&self.options))
+    }
+}
+
+/// A large enum of integers that fit into `i64`.
+#[derive(Debug)]
+pub(crate) struct IntegerEnumValidator {
+    options: Value,
+    integers: AHashSet<i64>,
+    schema_path: JSONPointer,
+}
+
+impl IntegerEnumValidator {
+    #[inline]
+    pub(crate) fn compile<'a>(
+        schema: &'a Value,
+        items: &'a [Value],
+        schema_path: JSONPointer,
+    ) -> CompilationResult<'a> {
+        let integers = items.iter().filter_map(Value::as_i64).collect();
+        Ok(Box::new(IntegerEnumValidator {
+            options: schema.clone(),
+            integers,
+            schema_path,
+        }))
+    }
+}
+
+/// The value of an integral float if it fits into `i64`.
+#[cfg(not(feature = "arbitrary-precision"))]
+fn integral_float(number: &Number) -> Option<i64> {
+    let value = number.as_f64()?;
+    #[allow(clippy::cast_possible_truncation)]
+    if value.fract() == 0. && value.abs() < 9_223_372_036_854_775_808. {
+        Some(value as i64)
+    } else {
+        None
+    }
+}
+
+/// The value of an integral float if it fits into `i64`.
+#[cfg(feature = "arbitrary-precision")]
+fn integral_float(number: &Number) -> Option<i64> {
+    // `f64` is not exact for numbers with arbitrary precision
+    helpers::Decimal::new(number).as_i64()
+}
+
+impl Validate for IntegerEnumValidator {
+    fn validate<'b>(
+        &self,
+        schema: &JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        if self.is_valid(schema, instance) {
+            no_error()
+        } else {
+            error(ValidationError::enumeration(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                &self.options,
+            ))
+        }
+    }
+
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        if let Value::Number(number) = instance {
+            if let Some(value) = number.as_i64() {
+                self.integers.contains(&value)
+            } else if number.is_u64() {
+                // Larger than any item
+                false
+            } else {
+                // Integral floats are equal to the corresponding integers (see `helpers::equal`)
+                matches!(integral_float(number), Some(value) if self.integers.contains(&value))
+            }
+        } else {
+            false
+        }
+    }
+}
+
+impl core::fmt::Display for IntegerEnumValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_items(f, items(&self.options))
+    }
+}
+
+#[inline]
+fn hash(value: &Value) -> u64 {
+    let mut hasher = AHasher::default();
+    HashedValue(value).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A large enum of mixed or compound values.
+#[derive(Debug)]
+pub(crate) struct HashedEnumValidator {
+    options: Value,
+    // Indices of `options` items by their hashes. Values that are equal according to `helpers::equal` have
+    // the same hash, but different values may collide
+    buckets: AHashMap<u64, Vec<usize>>,
+    schema_path: JSONPointer,
+}
+
+impl HashedEnumValidator {
+    #[inline]
+    pub(crate) fn compile<'a>(
+        schema: &'a Value,
+        items: &'a [Value],
+        schema_path: JSONPointer,
+    ) -> CompilationResult<'a> {
+        let mut buckets: AHashMap<u64, Vec<usize>> = AHashMap::with_capacity(items.len());
+        for (idx, item) in items.iter().enumerate() {
+            buckets.entry(hash(item)).or_default().push(idx);
+        }
+        Ok(Box::new(HashedEnumValidator {
+            options: schema.clone(),
+            buckets,
+            schema_path,
+        }))
+    }
+}
+
+impl Validate for HashedEnumValidator {
+    fn validate<'b>(
+        &self,
+        schema: &JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        if self.is_valid(schema, instance) {
+            no_error()
+        } else {
+            error(ValidationError::enumeration(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                &self.options,
+            ))
+        }
+    }
+
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        let items = items(&self.options);
+        matches!(
+            self.buckets.get(&hash(instance)),
+            Some(bucket) if bucket.iter().any(|idx| helpers::equal(instance, &items[*idx]))
         )
     }
 }
 
+impl core::fmt::Display for HashedEnumValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_items(f, items(&self.options))
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct SingleValueEnumValidator {
     value: Value,
@@ -149,8 +380,14 @@ pub(crate) fn compile<'a>(
                 value,
                 schema_path,
             ))
-        } else {
+        } else if items.len() <= ITEMS_SIZE_THRESHOLD {
             Some(EnumValidator::compile(schema, items, schema_path))
+        } else if items.iter().all(Value::is_string) {
+            Some(StringEnumValidator::compile(schema, items, schema_path))
+        } else if items.iter().all(Value::is_i64) {
+            Some(IntegerEnumValidator::compile(schema, items, schema_path))
+        } else {
+            Some(HashedEnumValidator::compile(schema, items, schema_path))
         }
     } else {
         Some(Err(ValidationError::schema(schema)))
@@ -159,13 +396,138 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use super::{
+        EnumValidator, HashedEnumValidator, IntegerEnumValidator, StringEnumValidator,
+        ITEMS_SIZE_THRESHOLD,
+    };
+    use crate::{error::ValidationErrorKind, paths::JSONPointer, tests_util};
     use serde_json::{json, Value};
     use test_case::test_case;
 
     #[test_case(&json!({"enum": [1]}), &json!(2), "/enum")]
     #[test_case(&json!({"enum": [1, 3]}), &json!(2), "/enum")]
+    #[test_case(&large(&json!("a")), &json!("b"), "/enum")]
+    #[test_case(&large(&json!(1.5)), &json!("b"), "/enum")]
     fn schema_path(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_path(schema, instance, expected)
     }
+
+    /// An enum large enough to be hashed: integers from 0 to 99 and `extra`.
+    fn large(extra: &Value) -> Value {
+        let mut items: Vec<_> = (0..100).map(Value::from).collect();
+        items.push(extra.clone());
+        json!({ "enum": items })
+    }
+
+    fn strings() -> Value {
+        let items: Vec<_> = (0..100).map(|idx| format!("code-{}", idx)).collect();
+        json!({ "enum": items })
+    }
+
+    #[test_case(&json!("code-0"))]
+    #[test_case(&json!("code-99"))]
+    fn strings_valid(instance: &Value) {
+        tests_util::is_valid(&strings(), instance)
+    }
+
+    #[test_case(&json!("code-100"))]
+    #[test_case(&json!(0))]
+    #[test_case(&json!(null))]
+    #[test_case(&json!(["code-0"]); "array")]
+    fn strings_invalid(instance: &Value) {
+        tests_util::is_not_valid(&strings(), instance)
+    }
+
+    #[test_case(&json!(0))]
+    #[test_case(&json!(42))]
+    #[test_case(&json!(42.0))]
+    #[test_case(&json!(-5))]
+    #[test_case(&json!(-5.0))]
+    fn integers_valid(instance: &Value) {
+        tests_util::is_valid(&large(&json!(-5)), instance)
+    }
+
+    #[test_case(&json!(100))]
+    #[test_case(&json!(42.5))]
+    #[test_case(&json!(-1))]
+    #[test_case(&json!(u64::MAX))]
+    #[test_case(&json!(1e30))]
+    #[test_case(&json!("42"); "string")]
+    #[test_case(&json!([42]); "array")]
+    fn integers_invalid(instance: &Value) {
+        tests_util::is_not_valid(&large(&json!(-5)), instance)
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    fn number(repr: &str) -> Value {
+        serde_json::from_str(repr).expect("Valid JSON")
+    }
+
+    #[test_case(&json!({"a": [1, "b"]}), &json!({"a": [1.0, "b"]}))]
+    #[test_case(&json!([1, 2]), &json!([1, 2]))]
+    #[test_case(&json!(null), &json!(null))]
+    #[test_case(&json!(true), &json!(true))]
+    #[test_case(&json!(1.5), &json!(1.5))]
+    #[test_case(&json!(u64::MAX), &json!(u64::MAX))]
+    #[test_case(&json!("a"), &json!("a"))]
+    #[test_case(&json!("a"), &json!(42.0); "integral float")]
+    #[test_case(&json!(10_000_000_000_000_000_000_u64), &json!(1e19); "large integral float")]
+    #[test_case(&json!(1e19), &json!(10_000_000_000_000_000_000_u64); "large integer")]
+    #[cfg_attr(
+        feature = "arbitrary-precision",
+        test_case(&number("9007199254740993"), &number("9007199254740993.0"); "beyond f64 precision")
+    )]
+    #[cfg_attr(
+        feature = "arbitrary-precision",
+        test_case(&number("1e400"), &number("10e399"); "beyond f64 range")
+    )]
+    #[cfg_attr(
+        feature = "arbitrary-precision",
+        test_case(&number("[9007199254740993]"), &number("[9007199254740993.0]"); "hashed beyond f64 precision")
+    )]
+    fn mixed_valid(extra: &Value, instance: &Value) {
+        tests_util::is_valid(&large(extra), instance)
+    }
+
+    #[test_case(&json!({"a": [1, "b"]}), &json!({"a": [2, "b"]}))]
+    #[test_case(&json!([1, 2]), &json!([2, 1]))]
+    #[test_case(&json!(null), &json!(false))]
+    #[test_case(&json!(true), &json!(false))]
+    #[test_case(&json!(1.5), &json!(2.5))]
+    #[test_case(&json!("a"), &json!("b"))]
+    #[test_case(&json!("a"), &json!(100))]
+    #[cfg_attr(
+        feature = "arbitrary-precision",
+        test_case(&number("9007199254740993"), &number("9007199254740992.0"); "beyond f64 precision")
+    )]
+    fn mixed_invalid(extra: &Value, instance: &Value) {
+        tests_util::is_not_valid(&large(extra), instance)
+    }
+
+    #[test_case(&strings())]
+    #[test_case(&large(&json!(-5)))]
+    #[test_case(&large(&json!("a")))]
+    fn same_as_linear(schema: &Value) {
+        let items = schema["enum"].as_array().expect("An array");
+        assert!(items.len() > ITEMS_SIZE_THRESHOLD);
+        let expected = EnumValidator::compile(&schema["enum"], items, JSONPointer::default())
+            .expect("Valid enum")
+            .to_string();
+        for validator in [
+            StringEnumValidator::compile,
+            IntegerEnumValidator::compile,
+            HashedEnumValidator::compile,
+        ]
+        .iter()
+        {
+            let validator =
+                validator(&schema["enum"], items, JSONPointer::default()).expect("Valid enum");
+            assert_eq!(validator.to_string(), expected);
+        }
+        let error = tests_util::validate(schema, &json!({}));
+        match error.kind {
+            ValidationErrorKind::Enum { options } => assert_eq!(options, schema["enum"]),
+            kind => panic!("Unexpected error: {:?}", kind),
+        }
+    }
 }
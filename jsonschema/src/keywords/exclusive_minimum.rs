@@ -1,3 +1,5 @@
+#[cfg(feature = "arbitrary-precision")]
+use crate::keywords::helpers::Decimal;
 use crate::{
     compilation::{context::CompilationContext, JSONSchema},
     error::{error, no_error, ErrorIterator, ValidationError},
@@ -5,25 +7,30 @@ use crate::{
     paths::{InstancePath, JSONPointer},
     validator::Validate,
 };
+#[cfg(not(feature = "arbitrary-precision"))]
 use num_cmp::NumCmp;
 use serde_json::{Map, Value};
 
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct ExclusiveMinimumU64Validator {
     limit: u64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct ExclusiveMinimumI64Validator {
     limit: i64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct ExclusiveMinimumF64Validator {
     limit: f64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 macro_rules! validate {
     ($validator: ty) => {
         impl Validate for $validator {
@@ -67,9 +74,12 @@ macro_rules! validate {
     };
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 validate!(ExclusiveMinimumU64Validator);
+#[cfg(not(feature = "arbitrary-precision"))]
 validate!(ExclusiveMinimumI64Validator);
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl Validate for ExclusiveMinimumF64Validator {
     fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
@@ -103,12 +113,14 @@ impl Validate for ExclusiveMinimumF64Validator {
         }
     }
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 impl core::fmt::Display for ExclusiveMinimumF64Validator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "exclusiveMinimum: {}", self.limit)
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 #[inline]
 pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
@@ -142,6 +154,68 @@ pub(crate) fn compile<'a>(
     }
 }
 
+/// Compares numbers exactly, without converting them to `f64`.
+#[cfg(feature = "arbitrary-precision")]
+pub(crate) struct ExclusiveMinimumBigValidator {
+    limit: Decimal,
+    limit_val: Value,
+    schema_path: JSONPointer,
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl Validate for ExclusiveMinimumBigValidator {
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        if let Value::Number(item) = instance {
+            Decimal::new(item) > self.limit
+        } else {
+            true
+        }
+    }
+
+    fn validate<'a, 'b>(
+        &self,
+        schema: &'a JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        if self.is_valid(schema, instance) {
+            no_error()
+        } else {
+            error(ValidationError::exclusive_minimum(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                self.limit_val.clone(),
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl core::fmt::Display for ExclusiveMinimumBigValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exclusiveMinimum: {}", self.limit_val)
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[inline]
+pub(crate) fn compile<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    context: &CompilationContext,
+) -> Option<CompilationResult<'a>> {
+    if let Value::Number(limit) = schema {
+        Some(Ok(Box::new(ExclusiveMinimumBigValidator {
+            limit: Decimal::new(limit),
+            limit_val: schema.clone(),
+            schema_path: context.as_pointer_with("exclusiveMinimum"),
+        })))
+    } else {
+        Some(Err(ValidationError::schema(schema)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
@@ -9,8 +9,9 @@ use uuid::Uuid;
 
 use crate::{
     compilation::{context::CompilationContext, JSONSchema},
+    ecma,
     error::{error, no_error, ErrorIterator, ValidationError},
-    keywords::{pattern, CompilationResult},
+    keywords::CompilationResult,
     paths::{InstancePath, JSONPointer},
     validator::Validate,
     Draft,
@@ -268,12 +269,30 @@ impl Validate for JSONPointerValidator {
         }
     }
 }
-format_validator!(RegexValidator, "regex");
+/// Compiles instances with the same limits as `pattern`, since they may come from untrusted input.
+struct RegexValidator {
+    schema_path: JSONPointer,
+    options: ecma::RegexOptions,
+}
+impl RegexValidator {
+    pub(crate) fn compile<'a>(context: &CompilationContext) -> CompilationResult<'a> {
+        let schema_path = context.as_pointer_with("format");
+        Ok(Box::new(RegexValidator {
+            schema_path,
+            options: context.config.regex_options().clone(),
+        }))
+    }
+}
+impl core::fmt::Display for RegexValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "format: regex".fmt(f)
+    }
+}
 impl Validate for RegexValidator {
     validate!("regex");
     fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            pattern::convert_regex(item).is_ok()
+            ecma::Regex::with_options(item, &self.options).is_ok()
         } else {
             true
         }
@@ -512,6 +531,25 @@ mod tests {
         assert!(compiled.is_valid(&instance))
     }
 
+    #[test]
+    fn regex_options() {
+        let schema = json!({"format": "regex"});
+        let instance = json!("^(?!foo)\\w{1000}");
+        assert!(JSONSchema::compile(&schema).unwrap().is_valid(&instance));
+        let linear = JSONSchema::options()
+            .with_linear_time_regex()
+            .compile(&schema)
+            .unwrap();
+        assert!(!linear.is_valid(&instance));
+        assert!(linear.is_valid(&json!("^\\w{1000}")));
+        let limited = JSONSchema::options()
+            .with_regex_size_limit(1000)
+            .compile(&schema)
+            .unwrap();
+        assert!(!limited.is_valid(&json!("^\\w{1000}")));
+        assert!(limited.is_valid(&json!("^(?!foo)")));
+    }
+
     #[test]
     fn schema_path() {
         tests_util::assert_schema_path(&json!({"format": "date"}), &json!("bla"), "/format")
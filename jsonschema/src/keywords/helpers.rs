@@ -1,6 +1,12 @@
+#[cfg(feature = "arbitrary-precision")]
+use fraction::BigUint;
+#[cfg(not(feature = "arbitrary-precision"))]
 use num_cmp::NumCmp;
-use serde_json::{Map, Value};
+use serde_json::{Map, Number, Value};
+#[cfg(feature = "arbitrary-precision")]
+use std::cmp::Ordering;
 
+#[cfg(not(feature = "arbitrary-precision"))]
 macro_rules! num_cmp {
     ($left:expr, $right:expr) => {
         if let Some(b) = $right.as_u64() {
@@ -19,22 +25,194 @@ pub(crate) fn equal(left: &Value, right: &Value) -> bool {
         (Value::String(left), Value::String(right)) => left == right,
         (Value::Bool(left), Value::Bool(right)) => left == right,
         (Value::Null, Value::Null) => true,
-        (Value::Number(left), Value::Number(right)) => {
-            if let Some(a) = left.as_u64() {
-                num_cmp!(a, right)
-            } else if let Some(a) = left.as_i64() {
-                num_cmp!(a, right)
-            } else {
-                let a = left.as_f64().expect("Always valid");
-                num_cmp!(a, right)
-            }
-        }
+        (Value::Number(left), Value::Number(right)) => equal_numbers(left, right),
         (Value::Array(left), Value::Array(right)) => equal_arrays(left, right),
         (Value::Object(left), Value::Object(right)) => equal_objects(left, right),
         (_, _) => false,
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
+#[inline]
+pub(crate) fn equal_numbers(left: &Number, right: &Number) -> bool {
+    if let Some(a) = left.as_u64() {
+        num_cmp!(a, right)
+    } else if let Some(a) = left.as_i64() {
+        num_cmp!(a, right)
+    } else {
+        let a = left.as_f64().expect("Always valid");
+        num_cmp!(a, right)
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[inline]
+pub(crate) fn equal_numbers(left: &Number, right: &Number) -> bool {
+    match (left.as_u64(), right.as_u64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => match (left.as_i64(), right.as_i64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => Decimal::new(left) == Decimal::new(right),
+        },
+    }
+}
+
+/// Exponents beyond this bound are saturated, so numbers with astronomically large (or small)
+/// exponents compare as equal. Far below `i64::MAX` to leave room for adding digit counts.
+#[cfg(feature = "arbitrary-precision")]
+const EXPONENT_LIMIT: i64 = i64::MAX / 4;
+
+/// Exact representation of a JSON number: `digits * 10^scale`.
+///
+/// With `arbitrary_precision` enabled, `serde_json` keeps numbers in their textual form, which
+/// can't be represented as `f64` without losing precision (or at all, e.g. `1e400`). Zeros implied
+/// by the exponent are never expanded, so numbers like `1e300000000` from untrusted instances are
+/// cheap to compare.
+#[cfg(feature = "arbitrary-precision")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Decimal {
+    is_negative: bool,
+    /// Significant digits without leading & trailing zeros. Empty for zero.
+    digits: String,
+    scale: i64,
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl Decimal {
+    pub(crate) fn new(number: &Number) -> Decimal {
+        let repr = number.to_string();
+        let (is_negative, repr) = match repr.strip_prefix('-') {
+            Some(repr) => (true, repr),
+            None => (false, repr.as_str()),
+        };
+        let (mantissa, exponent) = match repr.find(&['e', 'E'][..]) {
+            Some(idx) => {
+                let exponent = &repr[idx + 1..];
+                let exponent = exponent.parse::<i64>().unwrap_or_else(|_| {
+                    // Only digits are allowed, so it is out of range
+                    if exponent.starts_with('-') {
+                        -EXPONENT_LIMIT
+                    } else {
+                        EXPONENT_LIMIT
+                    }
+                });
+                (
+                    &repr[..idx],
+                    exponent.clamp(-EXPONENT_LIMIT, EXPONENT_LIMIT),
+                )
+            }
+            None => (repr, 0),
+        };
+        let (integer, fractional) = match mantissa.find('.') {
+            Some(idx) => (&mantissa[..idx], &mantissa[idx + 1..]),
+            None => (mantissa, ""),
+        };
+        let digits = format!("{}{}", integer, fractional);
+        let significant = digits.trim_start_matches('0').trim_end_matches('0');
+        if significant.is_empty() {
+            return Decimal {
+                is_negative: false,
+                digits: String::new(),
+                scale: 0,
+            };
+        }
+        let trailing_zeros = digits.len() - digits.trim_end_matches('0').len();
+        #[allow(clippy::cast_possible_wrap)]
+        let scale = exponent
+            .saturating_sub(fractional.len() as i64)
+            .saturating_add(trailing_zeros as i64);
+        Decimal {
+            is_negative,
+            digits: significant.to_string(),
+            scale,
+        }
+    }
+
+    pub(crate) const fn is_integer(&self) -> bool {
+        self.scale >= 0
+    }
+
+    /// The exact value if it is an integer that fits into `i64`.
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        if !self.is_integer() || self.magnitude() > 19 {
+            return None;
+        }
+        // Bounded by the check above
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let zeros = "0".repeat(self.scale as usize);
+        let sign = if self.is_negative { "-" } else { "" };
+        format!("{}{}{}", sign, self.digits, zeros).parse().ok()
+    }
+
+    /// Whether `self / other` is an integer.
+    pub(crate) fn is_multiple_of(&self, other: &Decimal) -> bool {
+        if self.digits.is_empty() || other.digits.is_empty() {
+            // Division by zero is not defined, consider it valid
+            return true;
+        }
+        let numerator = Decimal::parse_digits(&self.digits);
+        let denominator = Decimal::parse_digits(&other.digits);
+        // `self / other = numerator / denominator * 10^shift`
+        let shift = self.scale.saturating_sub(other.scale);
+        if shift >= 0 {
+            let power =
+                BigUint::from(10_u8).modpow(&BigUint::from(shift.unsigned_abs()), &denominator);
+            (numerator * power) % denominator == BigUint::from(0_u8)
+        } else if shift.unsigned_abs() > self.digits.len() as u64 {
+            // `denominator * 10^-shift` has more digits than `numerator`
+            false
+        } else {
+            // Bounded by the number of digits in the instance
+            #[allow(clippy::cast_possible_truncation)]
+            let power =
+                Decimal::parse_digits(&format!("1{}", "0".repeat(shift.unsigned_abs() as usize)));
+            numerator % (denominator * power) == BigUint::from(0_u8)
+        }
+    }
+
+    fn parse_digits(digits: &str) -> BigUint {
+        BigUint::parse_bytes(digits.as_bytes(), 10).expect("Contains only decimal digits")
+    }
+
+    /// Position of the most significant digit.
+    #[allow(clippy::cast_possible_wrap)]
+    const fn magnitude(&self) -> i64 {
+        self.scale.saturating_add(self.digits.len() as i64)
+    }
+
+    fn cmp_abs(&self, other: &Decimal) -> Ordering {
+        match (self.digits.is_empty(), other.digits.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            // Digits are compared as if they were after the decimal point
+            (false, false) => self
+                .magnitude()
+                .cmp(&other.magnitude())
+                .then_with(|| self.digits.cmp(&other.digits)),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        match (self.is_negative, other.is_negative) {
+            (false, false) => self.cmp_abs(other),
+            (true, true) => other.cmp_abs(self),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[inline]
 pub(crate) fn equal_arrays(left: &[Value], right: &[Value]) -> bool {
     left.len() == right.len() && left.iter().zip(right.iter()).all(|(a, b)| equal(a, b))
@@ -70,4 +248,19 @@ mod tests {
     fn are_not_equal(left: &Value, right: &Value) {
         assert!(!equal(left, right))
     }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case("1e400", "10E399", true)]
+    #[test_case("-0", "0.0", true)]
+    #[test_case("100000000000000000000000000000", "1e29", true)]
+    #[test_case("0.1", "0.10000000000000000001", false)]
+    #[test_case("18446744073709551616", "18446744073709551617", false)]
+    #[test_case("1e300000000", "10e299999999", true)]
+    #[test_case("1e300000000", "1e300000001", false)]
+    #[test_case("-1e-300000000", "-0.1e-299999999", true)]
+    fn arbitrary_precision(left: &str, right: &str, expected: bool) {
+        let left: Value = serde_json::from_str(left).unwrap();
+        let right: Value = serde_json::from_str(right).unwrap();
+        assert_eq!(equal(&left, &right), expected)
+    }
 }
@@ -34,6 +34,10 @@ impl IfThenValidator {
 }
 
 impl Validate for IfThenValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.schema, &self.then_schema]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if self.schema.is_valid(schema, instance) {
             self.then_schema.is_valid(schema, instance)
@@ -114,6 +118,10 @@ impl IfElseValidator {
 }
 
 impl Validate for IfElseValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.schema, &self.else_schema]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if !self.schema.is_valid(schema, instance) {
             self.else_schema.is_valid(schema, instance)
@@ -200,6 +208,10 @@ impl IfThenElseValidator {
 }
 
 impl Validate for IfThenElseValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.schema, &self.then_schema, &self.else_schema]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if self.schema.is_valid(schema, instance) {
             self.then_schema.is_valid(schema, instance)
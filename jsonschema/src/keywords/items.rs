@@ -28,6 +28,10 @@ impl ItemsArrayValidator {
     }
 }
 impl Validate for ItemsArrayValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.items.iter().collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items
@@ -87,6 +91,10 @@ impl ItemsObjectValidator {
     }
 }
 impl Validate for ItemsObjectValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             items.iter().all(|i| self.node.is_valid(schema, i))
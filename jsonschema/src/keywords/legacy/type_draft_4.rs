@@ -127,7 +127,15 @@ impl core::fmt::Display for IntegerTypeValidator {
 }
 
 fn is_integer(num: &Number) -> bool {
-    num.is_u64() || num.is_i64()
+    #[cfg(feature = "arbitrary-precision")]
+    {
+        // Integers that don't fit into 64 bits are still integers
+        num.is_u64() || num.is_i64() || !num.to_string().contains(&['.', 'e', 'E'][..])
+    }
+    #[cfg(not(feature = "arbitrary-precision"))]
+    {
+        num.is_u64() || num.is_i64()
+    }
 }
 
 #[inline]
@@ -1,3 +1,5 @@
+#[cfg(feature = "arbitrary-precision")]
+use crate::keywords::helpers::Decimal;
 use crate::{
     compilation::{context::CompilationContext, JSONSchema},
     error::{error, no_error, ErrorIterator, ValidationError},
@@ -5,25 +7,30 @@ use crate::{
     paths::{InstancePath, JSONPointer},
     validator::Validate,
 };
+#[cfg(not(feature = "arbitrary-precision"))]
 use num_cmp::NumCmp;
 use serde_json::{Map, Value};
 
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MaximumU64Validator {
     limit: u64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MaximumI64Validator {
     limit: i64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MaximumF64Validator {
     limit: f64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 macro_rules! validate {
     ($validator: ty) => {
         impl Validate for $validator {
@@ -67,9 +74,12 @@ macro_rules! validate {
     };
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 validate!(MaximumU64Validator);
+#[cfg(not(feature = "arbitrary-precision"))]
 validate!(MaximumI64Validator);
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl Validate for MaximumF64Validator {
     fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
@@ -103,12 +113,14 @@ impl Validate for MaximumF64Validator {
         }
     }
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 impl core::fmt::Display for MaximumF64Validator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "maximum: {}", self.limit)
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 #[inline]
 pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
@@ -142,6 +154,68 @@ pub(crate) fn compile<'a>(
     }
 }
 
+/// Compares numbers exactly, without converting them to `f64`.
+#[cfg(feature = "arbitrary-precision")]
+pub(crate) struct MaximumBigValidator {
+    limit: Decimal,
+    limit_val: Value,
+    schema_path: JSONPointer,
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl Validate for MaximumBigValidator {
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        if let Value::Number(item) = instance {
+            Decimal::new(item) <= self.limit
+        } else {
+            true
+        }
+    }
+
+    fn validate<'a, 'b>(
+        &self,
+        schema: &'a JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        if self.is_valid(schema, instance) {
+            no_error()
+        } else {
+            error(ValidationError::maximum(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                self.limit_val.clone(),
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl core::fmt::Display for MaximumBigValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "maximum: {}", self.limit_val)
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[inline]
+pub(crate) fn compile<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    context: &CompilationContext,
+) -> Option<CompilationResult<'a>> {
+    if let Value::Number(limit) = schema {
+        Some(Ok(Box::new(MaximumBigValidator {
+            limit: Decimal::new(limit),
+            limit_val: schema.clone(),
+            schema_path: context.as_pointer_with("maximum"),
+        })))
+    } else {
+        Some(Err(ValidationError::schema(schema)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
@@ -160,4 +234,28 @@ mod tests {
     fn schema_path(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_path(schema, instance, expected)
     }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case(
+        r#"{"maximum": 18446744073709551615}"#,
+        "18446744073709551615.5",
+        false
+    )]
+    #[test_case(
+        r#"{"maximum": 100000000000000000000000000000}"#,
+        "100000000000000000000000000001",
+        false
+    )]
+    #[test_case(r#"{"maximum": 100000000000000000000000000000}"#, "1e29", true)]
+    #[test_case(r#"{"maximum": 0.1}"#, "0.10000000000000000001", false)]
+    #[test_case(r#"{"maximum": 1e400}"#, "1e399", true)]
+    fn arbitrary_precision(schema: &str, instance: &str, expected: bool) {
+        let schema = serde_json::from_str(schema).unwrap();
+        let instance = serde_json::from_str(instance).unwrap();
+        if expected {
+            tests_util::is_valid(&schema, &instance)
+        } else {
+            tests_util::is_not_valid(&schema, &instance)
+        }
+    }
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "arbitrary-precision")]
+use crate::keywords::helpers::Decimal;
 use crate::{
     compilation::{context::CompilationContext, JSONSchema},
     error::{error, no_error, ErrorIterator, ValidationError},
@@ -5,25 +7,30 @@ use crate::{
     paths::{InstancePath, JSONPointer},
     validator::Validate,
 };
+#[cfg(not(feature = "arbitrary-precision"))]
 use num_cmp::NumCmp;
 use serde_json::{Map, Value};
 
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MinimumU64Validator {
     limit: u64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MinimumI64Validator {
     limit: i64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MinimumF64Validator {
     limit: f64,
     limit_val: Value,
     schema_path: JSONPointer,
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 macro_rules! validate {
     ($validator: ty) => {
         impl Validate for $validator {
@@ -67,9 +74,12 @@ macro_rules! validate {
     };
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 validate!(MinimumU64Validator);
+#[cfg(not(feature = "arbitrary-precision"))]
 validate!(MinimumI64Validator);
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl Validate for MinimumF64Validator {
     fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
@@ -103,12 +113,14 @@ impl Validate for MinimumF64Validator {
         }
     }
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 impl core::fmt::Display for MinimumF64Validator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "minimum: {}", self.limit)
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 #[inline]
 pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
@@ -142,6 +154,68 @@ pub(crate) fn compile<'a>(
     }
 }
 
+/// Compares numbers exactly, without converting them to `f64`.
+#[cfg(feature = "arbitrary-precision")]
+pub(crate) struct MinimumBigValidator {
+    limit: Decimal,
+    limit_val: Value,
+    schema_path: JSONPointer,
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl Validate for MinimumBigValidator {
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        if let Value::Number(item) = instance {
+            Decimal::new(item) >= self.limit
+        } else {
+            true
+        }
+    }
+
+    fn validate<'a, 'b>(
+        &self,
+        schema: &'a JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        if self.is_valid(schema, instance) {
+            no_error()
+        } else {
+            error(ValidationError::minimum(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                self.limit_val.clone(),
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl core::fmt::Display for MinimumBigValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "minimum: {}", self.limit_val)
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[inline]
+pub(crate) fn compile<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    context: &CompilationContext,
+) -> Option<CompilationResult<'a>> {
+    if let Value::Number(limit) = schema {
+        Some(Ok(Box::new(MinimumBigValidator {
+            limit: Decimal::new(limit),
+            limit_val: schema.clone(),
+            schema_path: context.as_pointer_with("minimum"),
+        })))
+    } else {
+        Some(Err(ValidationError::schema(schema)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
@@ -160,4 +234,24 @@ mod tests {
     fn schema_path(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_path(schema, instance, expected)
     }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case("1", true)]
+    #[test_case("10e-1", true)]
+    #[test_case("1.00000000000000000001", true)]
+    #[test_case("0.99999999999999999999", false)]
+    #[test_case("1e300000000", true)]
+    #[test_case("-1e300000000", false)]
+    #[test_case("1e-300000000", false)]
+    #[test_case("1e100000000000000000000", true)]
+    #[test_case("1e-100000000000000000000", false)]
+    fn arbitrary_precision(instance: &str, expected: bool) {
+        let schema = json!({"minimum": 1});
+        let instance = serde_json::from_str(instance).unwrap();
+        if expected {
+            tests_util::is_valid(&schema, &instance)
+        } else {
+            tests_util::is_not_valid(&schema, &instance)
+        }
+    }
 }
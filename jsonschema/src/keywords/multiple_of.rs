@@ -1,3 +1,5 @@
+#[cfg(feature = "arbitrary-precision")]
+use crate::keywords::helpers::Decimal;
 use crate::{
     compilation::{context::CompilationContext, JSONSchema},
     error::{error, no_error, ErrorIterator, ValidationError},
@@ -5,25 +7,36 @@ use crate::{
     paths::{InstancePath, JSONPointer},
     validator::Validate,
 };
+#[cfg(not(feature = "arbitrary-precision"))]
 use fraction::{BigFraction, BigUint};
 use serde_json::{Map, Value};
+#[cfg(not(feature = "arbitrary-precision"))]
 use std::f64::EPSILON;
 
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MultipleOfFloatValidator {
     multiple_of: f64,
+    multiple_of_val: Value,
     schema_path: JSONPointer,
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl MultipleOfFloatValidator {
     #[inline]
-    pub(crate) fn compile<'a>(multiple_of: f64, schema_path: JSONPointer) -> CompilationResult<'a> {
+    pub(crate) fn compile<'a>(
+        multiple_of: f64,
+        multiple_of_val: Value,
+        schema_path: JSONPointer,
+    ) -> CompilationResult<'a> {
         Ok(Box::new(MultipleOfFloatValidator {
             multiple_of,
+            multiple_of_val,
             schema_path,
         }))
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl Validate for MultipleOfFloatValidator {
     fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
@@ -53,34 +66,44 @@ impl Validate for MultipleOfFloatValidator {
                 self.schema_path.clone(),
                 instance_path.into(),
                 instance,
-                self.multiple_of,
+                self.multiple_of_val.clone(),
             ));
         }
         no_error()
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl core::fmt::Display for MultipleOfFloatValidator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "multipleOf: {}", self.multiple_of)
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 pub(crate) struct MultipleOfIntegerValidator {
     multiple_of: f64,
+    multiple_of_val: Value,
     schema_path: JSONPointer,
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl MultipleOfIntegerValidator {
     #[inline]
-    pub(crate) fn compile<'a>(multiple_of: f64, schema_path: JSONPointer) -> CompilationResult<'a> {
+    pub(crate) fn compile<'a>(
+        multiple_of: f64,
+        multiple_of_val: Value,
+        schema_path: JSONPointer,
+    ) -> CompilationResult<'a> {
         Ok(Box::new(MultipleOfIntegerValidator {
             multiple_of,
+            multiple_of_val,
             schema_path,
         }))
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl Validate for MultipleOfIntegerValidator {
     fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
         if let Value::Number(item) = instance {
@@ -109,18 +132,20 @@ impl Validate for MultipleOfIntegerValidator {
                 self.schema_path.clone(),
                 instance_path.into(),
                 instance,
-                self.multiple_of,
+                self.multiple_of_val.clone(),
             ));
         }
         no_error()
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 impl core::fmt::Display for MultipleOfIntegerValidator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "multipleOf: {}", self.multiple_of)
     }
 }
+#[cfg(not(feature = "arbitrary-precision"))]
 #[inline]
 pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
@@ -133,16 +158,81 @@ pub(crate) fn compile<'a>(
         if multiple_of.fract() == 0. {
             Some(MultipleOfIntegerValidator::compile(
                 multiple_of,
+                schema.clone(),
                 schema_path,
             ))
         } else {
-            Some(MultipleOfFloatValidator::compile(multiple_of, schema_path))
+            Some(MultipleOfFloatValidator::compile(
+                multiple_of,
+                schema.clone(),
+                schema_path,
+            ))
         }
     } else {
         Some(Err(ValidationError::schema(schema)))
     }
 }
 
+/// Checks divisibility exactly, without converting numbers to `f64`.
+#[cfg(feature = "arbitrary-precision")]
+pub(crate) struct MultipleOfBigValidator {
+    multiple_of: Decimal,
+    multiple_of_val: Value,
+    schema_path: JSONPointer,
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl Validate for MultipleOfBigValidator {
+    fn is_valid(&self, _: &JSONSchema, instance: &Value) -> bool {
+        if let Value::Number(item) = instance {
+            return Decimal::new(item).is_multiple_of(&self.multiple_of);
+        }
+        true
+    }
+
+    fn validate<'a, 'b>(
+        &self,
+        schema: &'a JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        if !self.is_valid(schema, instance) {
+            return error(ValidationError::multiple_of(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                self.multiple_of_val.clone(),
+            ));
+        }
+        no_error()
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+impl core::fmt::Display for MultipleOfBigValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "multipleOf: {}", self.multiple_of_val)
+    }
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[inline]
+pub(crate) fn compile<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    context: &CompilationContext,
+) -> Option<CompilationResult<'a>> {
+    if let Value::Number(multiple_of) = schema {
+        Some(Ok(Box::new(MultipleOfBigValidator {
+            multiple_of: Decimal::new(multiple_of),
+            multiple_of_val: schema.clone(),
+            schema_path: context.as_pointer_with("multipleOf"),
+        })))
+    } else {
+        Some(Err(ValidationError::schema(schema)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
@@ -154,4 +244,27 @@ mod tests {
     fn schema_path(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_path(schema, instance, expected)
     }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test_case(r#"{"multipleOf": 0.01}"#, "19.99", true)]
+    #[test_case(r#"{"multipleOf": 3}"#, "100000000000000000000000000002", true)]
+    #[test_case(r#"{"multipleOf": 3}"#, "100000000000000000000000000001", false)]
+    #[test_case(r#"{"multipleOf": 1e-30}"#, "1.000000000000000000000000000001", true)]
+    #[test_case(r#"{"multipleOf": 1e399}"#, "1e400", true)]
+    #[test_case(r#"{"multipleOf": 1e399}"#, "1.5e399", false)]
+    #[test_case(r#"{"multipleOf": 5}"#, "1e300000000", true)]
+    #[test_case(r#"{"multipleOf": 3}"#, "1e300000000", false)]
+    #[test_case(r#"{"multipleOf": 2}"#, "1e100000000000000000000", true)]
+    #[test_case(r#"{"multipleOf": 1}"#, "1e-300000000", false)]
+    #[test_case(r#"{"multipleOf": 1e-300000000}"#, "1", true)]
+    #[test_case(r#"{"multipleOf": 0.5}"#, "0", true)]
+    fn arbitrary_precision(schema: &str, instance: &str, expected: bool) {
+        let schema = serde_json::from_str(schema).unwrap();
+        let instance = serde_json::from_str(instance).unwrap();
+        if expected {
+            tests_util::is_valid(&schema, &instance)
+        } else {
+            tests_util::is_not_valid(&schema, &instance)
+        }
+    }
 }
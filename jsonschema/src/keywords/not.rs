@@ -31,6 +31,10 @@ impl NotValidator {
 }
 
 impl Validate for NotValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         !self.node.is_valid(schema, instance)
     }
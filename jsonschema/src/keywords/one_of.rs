@@ -61,6 +61,10 @@ impl OneOfValidator {
 }
 
 impl Validate for OneOfValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.schemas.iter().collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         let first_valid_idx = self.get_first_valid(schema, instance);
         first_valid_idx.map_or(false, |idx| !self.are_others_valid(schema, instance, idx))
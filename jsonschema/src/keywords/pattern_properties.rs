@@ -1,14 +1,15 @@
 use crate::{
     compilation::{compile_validators, context::CompilationContext, JSONSchema},
-    error::{no_error, ErrorIterator, ValidationError},
+    ecma::Regex,
+    error::{error, no_error, ErrorIterator, ValidationError},
     keywords::CompilationResult,
     output::BasicOutput,
     paths::InstancePath,
     schema_node::SchemaNode,
     validator::{format_validators, PartialApplication, Validate},
 };
-use fancy_regex::Regex;
 use serde_json::{Map, Value};
+use std::collections::VecDeque;
 
 pub(crate) struct PatternPropertiesValidator {
     patterns: Vec<(Regex, SchemaNode)>,
@@ -25,10 +26,7 @@ impl PatternPropertiesValidator {
         for (pattern, subschema) in map {
             let pattern_context = keyword_context.with_path(pattern.to_string());
             patterns.push((
-                match Regex::new(pattern) {
-                    Ok(r) => r,
-                    Err(_) => return Err(ValidationError::schema(subschema)),
-                },
+                compile_pattern(pattern, &pattern_context)?,
                 compile_validators(subschema, &pattern_context)?,
             ));
         }
@@ -37,12 +35,16 @@ impl PatternPropertiesValidator {
 }
 
 impl Validate for PatternPropertiesValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.patterns.iter().map(|(_, node)| node).collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             self.patterns.iter().all(move |(re, node)| {
-                item.iter()
-                    .filter(move |(key, _)| re.is_match(key).unwrap_or(false))
-                    .all(move |(_key, value)| node.is_valid(schema, value))
+                item.iter().all(move |(key, value)| {
+                    is_valid_matched(re, node, schema, key, value).unwrap_or(true)
+                })
             })
         } else {
             true
@@ -61,12 +63,11 @@ impl Validate for PatternPropertiesValidator {
                 .patterns
                 .iter()
                 .flat_map(move |(re, node)| {
-                    item.iter()
-                        .filter(move |(key, _)| re.is_match(key).unwrap_or(false))
-                        .flat_map(move |(key, value)| {
-                            let instance_path = instance_path.push(key.clone());
-                            node.validate(schema, value, &instance_path)
-                        })
+                    item.iter().flat_map(move |(key, value)| {
+                        validate_matched(re, node, schema, key, value, instance_path)
+                            .into_iter()
+                            .flatten()
+                    })
                 })
                 .collect();
             Box::new(errors.into_iter())
@@ -86,10 +87,10 @@ impl Validate for PatternPropertiesValidator {
             let mut sub_results = BasicOutput::default();
             for (pattern, node) in &self.patterns {
                 for (key, value) in item {
-                    if pattern.is_match(key).unwrap_or(false) {
-                        let path = instance_path.push(key.clone());
+                    let path = instance_path.push(key.clone());
+                    if let Some(output) = apply_matched(pattern, node, schema, key, value, &path) {
                         matched_propnames.push(key.clone());
-                        sub_results += node.apply_rooted(schema, value, &path);
+                        sub_results += output;
                     }
                 }
             }
@@ -131,21 +132,22 @@ impl SingleValuePatternPropertiesValidator {
         let keyword_context = context.with_path("patternProperties");
         let pattern_context = keyword_context.with_path(pattern.to_string());
         Ok(Box::new(SingleValuePatternPropertiesValidator {
-            pattern: match Regex::new(pattern) {
-                Ok(r) => r,
-                Err(_) => return Err(ValidationError::schema(schema)),
-            },
+            pattern: compile_pattern(pattern, &pattern_context)?,
             node: compile_validators(schema, &pattern_context)?,
         }))
     }
 }
 
 impl Validate for SingleValuePatternPropertiesValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        vec![&self.node]
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
-            item.iter()
-                .filter(move |(key, _)| self.pattern.is_match(key).unwrap_or(false))
-                .all(move |(_key, value)| self.node.is_valid(schema, value))
+            item.iter().all(move |(key, value)| {
+                is_valid_matched(&self.pattern, &self.node, schema, key, value).unwrap_or(true)
+            })
         } else {
             true
         }
@@ -161,10 +163,10 @@ impl Validate for SingleValuePatternPropertiesValidator {
         if let Value::Object(item) = instance {
             let errors: Vec<_> = item
                 .iter()
-                .filter(move |(key, _)| self.pattern.is_match(key).unwrap_or(false))
                 .flat_map(move |(key, value)| {
-                    let instance_path = instance_path.push(key.clone());
-                    self.node.validate(schema, value, &instance_path)
+                    validate_matched(&self.pattern, &self.node, schema, key, value, instance_path)
+                        .into_iter()
+                        .flatten()
                 })
                 .collect();
             Box::new(errors.into_iter())
@@ -183,10 +185,12 @@ impl Validate for SingleValuePatternPropertiesValidator {
             let mut matched_propnames = Vec::with_capacity(item.len());
             let mut outputs = BasicOutput::default();
             for (key, value) in item {
-                if self.pattern.is_match(key).unwrap_or(false) {
-                    let path = instance_path.push(key.clone());
+                let path = instance_path.push(key.clone());
+                if let Some(output) =
+                    apply_matched(&self.pattern, &self.node, schema, key, value, &path)
+                {
                     matched_propnames.push(key.clone());
-                    outputs += self.node.apply_rooted(schema, value, &path);
+                    outputs += output;
                 }
             }
             let mut result: PartialApplication = outputs.into();
@@ -209,6 +213,90 @@ impl core::fmt::Display for SingleValuePatternPropertiesValidator {
     }
 }
 
+/// Compile a `patternProperties` regex. `context` should point to the pattern itself.
+#[inline]
+pub(crate) fn compile_pattern<'a>(
+    pattern: &str,
+    context: &CompilationContext,
+) -> Result<Regex, ValidationError<'a>> {
+    Regex::with_options(pattern, context.config.regex_options()).map_err(|error| {
+        ValidationError::invalid_regex(context.clone().into_pointer(), pattern, error)
+    })
+}
+
+// A property name may exceed the backtrack limit while being matched against a pattern. Such a
+// property is considered matched & invalid, so the validation fails instead of skipping the
+// sub-schema. `node` is compiled from the pattern's sub-schema, so its location points to it.
+
+/// Whether `value` is valid against `node`, or `None` if `property` doesn't match `pattern`.
+#[inline]
+pub(crate) fn is_valid_matched(
+    pattern: &Regex,
+    node: &SchemaNode,
+    schema: &JSONSchema,
+    property: &str,
+    value: &Value,
+) -> Option<bool> {
+    match pattern.is_match(property) {
+        Ok(true) => Some(node.is_valid(schema, value)),
+        Ok(false) => None,
+        Err(_) => Some(false),
+    }
+}
+
+/// Errors of `value` against `node`, or `None` if `property` doesn't match `pattern`.
+#[inline]
+pub(crate) fn validate_matched<'b>(
+    pattern: &Regex,
+    node: &SchemaNode,
+    schema: &JSONSchema,
+    property: &str,
+    value: &'b Value,
+    instance_path: &InstancePath,
+) -> Option<ErrorIterator<'b>> {
+    match pattern.is_match(property) {
+        Ok(true) => {
+            let instance_path = instance_path.push(property.to_string());
+            Some(node.validate(schema, value, &instance_path))
+        }
+        Ok(false) => None,
+        Err(e) => Some(error(ValidationError::backtrack_limit(
+            node.relative_path().clone(),
+            instance_path.push(property.to_string()).into(),
+            value,
+            e,
+        ))),
+    }
+}
+
+/// Output of `value` located at `path` against `node`, or `None` if `property` doesn't match
+/// `pattern`.
+#[inline]
+pub(crate) fn apply_matched<'a>(
+    pattern: &Regex,
+    node: &'a SchemaNode,
+    schema: &JSONSchema,
+    property: &str,
+    value: &Value,
+    path: &InstancePath,
+) -> Option<BasicOutput<'a>> {
+    match pattern.is_match(property) {
+        Ok(true) => Some(node.apply_rooted(schema, value, path)),
+        Ok(false) => None,
+        Err(e) => {
+            let error = ValidationError::backtrack_limit(
+                node.relative_path().clone(),
+                path.into(),
+                value,
+                e,
+            );
+            let mut errors = VecDeque::new();
+            errors.push_back(node.error_at(path, error.into()));
+            Some(BasicOutput::Invalid(errors))
+        }
+    }
+}
+
 #[inline]
 pub(crate) fn compile<'a>(
     parent: &'a Map<String, Value>,
@@ -237,7 +325,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util, JSONSchema};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -246,4 +334,55 @@ mod tests {
     fn schema_path(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_path(schema, instance, expected)
     }
+
+    #[test_case(&json!({"patternProperties": {"^(?!f)": {}}}), "/patternProperties/^(?!f)")]
+    #[test_case(&json!({"patternProperties": {"^(?!f)": {}, "^x": {}}}), "/patternProperties/^(?!f)")]
+    #[test_case(&json!({"patternProperties": {"^(?!f)": {}}, "additionalProperties": false}), "/patternProperties/^(?!f)")]
+    #[test_case(&json!({"properties": {"a": {"pattern": "(a)\\1"}}}), "/properties/a/pattern")]
+    fn linear_time_regex(schema: &Value, expected: &str) {
+        let error = JSONSchema::options()
+            .with_linear_time_regex()
+            .compile(schema)
+            .expect_err("Requires backtracking");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::InvalidRegex { .. }
+        ));
+        assert_eq!(error.schema_path.to_string(), expected);
+    }
+
+    #[test_case(&json!({"patternProperties": {"^(a|ab)*(?=c)": {}}}); "single pattern")]
+    #[test_case(&json!({"patternProperties": {"^(a|ab)*(?=c)": {}, "^x": {}}}); "multiple patterns")]
+    #[test_case(&json!({"patternProperties": {"^(a|ab)*(?=c)": {}}, "additionalProperties": false}); "additional properties false")]
+    #[test_case(&json!({"patternProperties": {"^(a|ab)*(?=c)": {}}, "additionalProperties": {}}); "additional properties")]
+    #[test_case(&json!({"properties": {"b": {}}, "patternProperties": {"^(a|ab)*(?=c)": {}}, "additionalProperties": false}); "properties & additional properties false")]
+    #[test_case(&json!({"properties": {"b": {}}, "patternProperties": {"^(a|ab)*(?=c)": {}}, "additionalProperties": {}}); "properties & additional properties")]
+    fn backtrack_limit(schema: &Value) {
+        // Exceeding the limit while matching a property name must not skip its sub-schema
+        let compiled = JSONSchema::options()
+            .with_regex_backtrack_limit(10)
+            .compile(schema)
+            .expect("Valid schema");
+        let property = "ab".repeat(100);
+        let instance = json!({ property.clone(): 1 });
+        assert!(!compiled.is_valid(&instance));
+        let errors: Vec<_> = compiled
+            .validate(&instance)
+            .expect_err("Should fail")
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::BacktrackLimitExceeded { .. }
+        ));
+        assert_eq!(
+            errors[0].schema_path.to_string(),
+            "/patternProperties/^(a|ab)*(?=c)"
+        );
+        assert_eq!(
+            errors[0].instance_path.to_string(),
+            format!("/{}", property)
+        );
+        assert!(!compiled.apply(&instance).basic().is_valid());
+    }
 }
@@ -38,6 +38,10 @@ impl PropertiesValidator {
 }
 
 impl Validate for PropertiesValidator {
+    fn children(&self) -> Vec<&SchemaNode> {
+        self.properties.iter().map(|(_, node)| node).collect()
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             self.properties.iter().all(move |(name, node)| {
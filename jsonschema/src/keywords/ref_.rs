@@ -1,23 +1,19 @@
 use crate::{
-    compilation::{compile_validators, context::CompilationContext, JSONSchema},
-    error::{error, ErrorIterator},
+    compilation::{context::CompilationContext, JSONSchema},
+    error::{error, no_error, ErrorIterator, ValidationError},
     keywords::CompilationResult,
+    limits,
     paths::{InstancePath, JSONPointer},
-    schema_node::SchemaNode,
-    validator::Validate,
+    profile, trace,
+    validator::{PartialApplication, Validate},
 };
-use parking_lot::RwLock;
 use serde_json::Value;
 use url::Url;
 
 pub(crate) struct RefValidator {
     reference: Url,
-    /// Precomputed validators.
-    /// They are behind a RwLock as is not possible to compute them
-    /// at compile time without risking infinite loops of references
-    /// and at the same time during validation we iterate over shared
-    /// references (&self) and not owned references (&mut self).
-    sub_nodes: RwLock<Option<SchemaNode>>,
+    /// Index of the compiled target in the arena of the schema.
+    target: usize,
     schema_path: JSONPointer,
 }
 
@@ -28,31 +24,83 @@ impl RefValidator {
         context: &CompilationContext,
     ) -> CompilationResult<'a> {
         let reference = context.build_url(reference)?;
+        let target = context.compile_reference(&reference);
         Ok(Box::new(RefValidator {
             reference,
-            sub_nodes: RwLock::new(None),
+            target,
             schema_path: context.schema_path.clone().into(),
         }))
     }
+
+    fn validate_target<'b>(
+        &self,
+        schema: &JSONSchema,
+        instance: &'b Value,
+        instance_path: &InstancePath,
+    ) -> ErrorIterator<'b> {
+        match schema.references.get(self.target) {
+            Some(node) => Box::new(
+                node.err_iter(schema, instance, instance_path)
+                    .map(move |mut error| {
+                        error.schema_path =
+                            self.schema_path.extend_with(error.schema_path.as_slice());
+                        error
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+            None => error(self.unresolved(schema)),
+        }
+    }
+
+    /// Why the target was not compiled.
+    fn unresolved(&self, schema: &JSONSchema) -> ValidationError<'static> {
+        match schema.references.error(self.target) {
+            Some(error) => error.duplicate(),
+            None => ValidationError::invalid_reference(self.reference.to_string()),
+        }
+    }
 }
 
 impl Validate for RefValidator {
+    fn reference_target(&self) -> Option<usize> {
+        Some(self.target)
+    }
+
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool {
-        if let Some(sub_nodes) = self.sub_nodes.read().as_ref() {
-            return sub_nodes.is_valid(schema, instance);
-        }
-        if let Ok((scope, resolved)) = schema
-            .resolver
-            .resolve_fragment(schema.draft(), &self.reference)
-        {
-            let context = CompilationContext::new(scope.into(), schema.config());
-            if let Ok(node) = compile_validators(&resolved, &context) {
-                let result = node.is_valid(schema, instance);
-                *self.sub_nodes.write() = Some(node);
-                return result;
+        let _reference = match limits::enter_reference() {
+            Some(reference) => reference,
+            None => return false,
+        };
+        match schema.references.get(self.target) {
+            Some(node) if profile::is_active() => {
+                profile::within_reference(&self.reference, || node.is_valid(schema, instance))
             }
+            Some(node) => node.is_valid(schema, instance),
+            None => false,
+        }
+    }
+
+    fn apply<'a>(
+        &'a self,
+        schema: &JSONSchema,
+        instance: &Value,
+        instance_path: &InstancePath,
+    ) -> PartialApplication<'a> {
+        let _reference = match limits::enter_reference() {
+            Some(reference) => reference,
+            None => return PartialApplication::valid_empty(),
         };
-        false
+        match schema.references.get(self.target) {
+            Some(node) => {
+                // Locations within the target are reported under the `$ref` keyword
+                let prefix = self.schema_path.extend_with(&["$ref".into()]);
+                let result = node.apply(schema, instance, instance_path);
+                trace::relocate(&prefix);
+                result.relocate(&prefix)
+            }
+            None => PartialApplication::invalid_empty(vec![self.unresolved(schema).into()]),
+        }
     }
 
     fn validate<'a, 'b>(
@@ -61,40 +109,16 @@ impl Validate for RefValidator {
         instance: &'b Value,
         instance_path: &InstancePath,
     ) -> ErrorIterator<'b> {
-        if let Some(node) = self.sub_nodes.read().as_ref() {
-            return Box::new(
-                node.validate(schema, instance, instance_path)
-                    .collect::<Vec<_>>()
-                    .into_iter(),
-            );
-        }
-        match schema
-            .resolver
-            .resolve_fragment(schema.draft(), &self.reference)
-        {
-            Ok((scope, resolved)) => {
-                let context = CompilationContext::new(scope.into(), schema.config());
-                match compile_validators(&resolved, &context) {
-                    Ok(node) => {
-                        let result = Box::new(
-                            node.err_iter(schema, instance, instance_path)
-                                .map(move |mut error| {
-                                    let schema_path = self.schema_path.clone();
-                                    error.schema_path =
-                                        schema_path.extend_with(error.schema_path.as_slice());
-                                    error
-                                })
-                                .collect::<Vec<_>>()
-                                .into_iter(),
-                        );
-                        *self.sub_nodes.write() = Some(node);
-                        result
-                    }
-                    Err(err) => error(err.into_owned()),
-                }
-            }
-            Err(err) => error(err.into_owned()),
+        let _reference = match limits::enter_reference() {
+            Some(reference) => reference,
+            None => return no_error(),
+        };
+        if profile::is_active() {
+            return profile::within_reference(&self.reference, || {
+                self.validate_target(schema, instance, instance_path)
+            });
         }
+        self.validate_target(schema, instance, instance_path)
     }
 }
 
@@ -365,10 +365,16 @@ impl core::fmt::Display for IntegerTypeValidator {
     }
 }
 
+#[cfg(not(feature = "arbitrary-precision"))]
 fn is_integer(num: &Number) -> bool {
     num.is_u64() || num.is_i64() || num.as_f64().expect("Always valid").fract() == 0.
 }
 
+#[cfg(feature = "arbitrary-precision")]
+fn is_integer(num: &Number) -> bool {
+    num.is_u64() || num.is_i64() || crate::keywords::helpers::Decimal::new(num).is_integer()
+}
+
 #[inline]
 pub(crate) fn compile<'a>(
     _: &'a Map<String, Value>,
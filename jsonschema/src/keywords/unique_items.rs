@@ -10,10 +10,18 @@ use serde_json::{Map, Value};
 use crate::paths::{InstancePath, JSONPointer};
 use std::hash::{Hash, Hasher};
 
+#[cfg(feature = "arbitrary-precision")]
+use crate::keywords::helpers::Decimal;
+
 // Based on implementation proposed by Sven Marnach:
 // https://stackoverflow.com/questions/60882381/what-is-the-fastest-correct-way-to-detect-that-there-are-no-duplicates-in-a-json
-#[derive(PartialEq)]
-pub(crate) struct HashedValue<'a>(&'a Value);
+pub(crate) struct HashedValue<'a>(pub(crate) &'a Value);
+
+impl PartialEq for HashedValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        equal(self.0, other.0)
+    }
+}
 
 impl Eq for HashedValue<'_> {}
 
@@ -22,13 +30,30 @@ impl Hash for HashedValue<'_> {
         match self.0 {
             Value::Null => state.write_u32(3_221_225_473), // chosen randomly
             Value::Bool(ref item) => item.hash(state),
+            #[cfg(feature = "arbitrary-precision")]
+            Value::Number(ref item) => {
+                // `as_f64` is lossy for numbers kept in their textual form, while `Decimal` is
+                // exact & equal numbers have the same representation
+                Decimal::new(item).hash(state)
+            }
+            #[cfg(not(feature = "arbitrary-precision"))]
             Value::Number(ref item) => {
                 if let Some(number) = item.as_u64() {
                     number.hash(state);
                 } else if let Some(number) = item.as_i64() {
                     number.hash(state);
                 } else if let Some(number) = item.as_f64() {
-                    number.to_bits().hash(state)
+                    // Integral floats are equal to the corresponding integers (see `helpers::equal`),
+                    // therefore they should have the same hash
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    if number.fract() == 0. && (0. ..18_446_744_073_709_551_616.).contains(&number)
+                    {
+                        (number as u64).hash(state)
+                    } else if number.fract() == 0. && number.abs() < 9_223_372_036_854_775_808. {
+                        (number as i64).hash(state)
+                    } else {
+                        number.to_bits().hash(state)
+                    }
                 }
             }
             Value::String(ref item) => item.hash(state),
@@ -149,6 +174,13 @@ mod tests {
     use crate::tests_util;
     use serde_json::json;
 
+    #[test]
+    fn integral_floats_are_not_unique() {
+        let mut items: Vec<_> = (0..20).map(|i| json!(i)).collect();
+        items.push(json!(5.0));
+        tests_util::is_not_valid(&json!({"uniqueItems": true}), &json!(items))
+    }
+
     #[test]
     fn schema_path() {
         tests_util::assert_schema_path(
@@ -54,6 +54,15 @@
 //! ```
 //! Each error has an `instance_path` attribute that indicates the path to the erroneous part within the validated instance.
 //! It could be transformed to JSON Pointer via `.to_string()` or to `Vec<String>` via `.into_vec()`.
+//!
+//! ## Regular expressions
+//! Patterns in `pattern`, `patternProperties` and the `regex` format are interpreted as ECMA-262
+//! regular expressions (as if the `u` flag is set) and translated to the `fancy_regex` syntax.
+//! Not supported:
+//!   - look-behind assertions of variable length;
+//!   - ASCII-only `\b` and `\B` - Unicode word characters are used instead;
+//!   - Unicode property escapes unknown to the `regex` crate;
+//!   - Annex B extensions, e.g. octal escapes.
 #![warn(
     clippy::cast_possible_truncation,
     clippy::doc_markdown,
@@ -83,24 +92,44 @@
     clippy::needless_collect
 )]
 #![cfg_attr(not(test), allow(clippy::integer_arithmetic, clippy::unwrap_used))]
+pub mod batch;
+mod bundle;
+pub mod codegen;
+pub mod compatibility;
 mod compilation;
 mod content_encoding;
 mod content_media_type;
+pub mod dereference;
+pub mod diagnostics;
+mod ecma;
 pub mod error;
+pub mod generate;
+pub mod infer;
+pub mod interrupt;
+pub mod introspection;
 mod keywords;
+mod limits;
+pub mod meta;
 mod output;
 pub use output::{BasicOutput, Output};
 pub mod paths;
 pub mod primitive_type;
+pub mod profile;
+pub mod references;
 mod resolver;
 mod schema_node;
 mod schemas;
+mod streaming;
+pub mod trace;
 mod validator;
+mod vocabularies;
 
 pub use compilation::{options::CompilationOptions, JSONSchema};
+pub use dereference::dereference;
 pub use error::{ErrorIterator, ValidationError};
 pub use schemas::Draft;
 use serde_json::Value;
+pub use streaming::StreamingValidator;
 
 /// A shortcut for validating `instance` against `schema`. Draft version is detected automatically.
 /// ```rust
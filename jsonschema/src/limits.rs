@@ -0,0 +1,348 @@
+//! Limits on resources used by a single validation.
+//!
+//! The instance depth is checked before the validation starts. The number of evaluated nodes
+//! and the `$ref` expansion depth are tracked per thread while the validation is running:
+//! once a limit is exceeded, all remaining nodes are skipped and the whole validation fails.
+//! An [`Interrupt`] is checked the same way, every [`INTERRUPT_CHECK_INTERVAL`] nodes, and
+//! additionally before every `pattern` & `$ref` evaluation, as they may take long even in small
+//! schemas.
+//!
+//! Validations without limits & interrupts don't touch the thread-local state: nodes only check a
+//! process-wide counter of running validations that enforce something, which stays zero unless
+//! such validations are used.
+use crate::{
+    error::{Interruption, Limit, ValidationError},
+    interrupt::Interrupt,
+};
+use serde_json::Value;
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// How many nodes are evaluated between checks of the deadline & the cancellation token.
+const INTERRUPT_CHECK_INTERVAL: usize = 64;
+
+/// Configured limits. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Limits {
+    pub(crate) instance_depth: Option<usize>,
+    pub(crate) evaluated_nodes: Option<usize>,
+    pub(crate) ref_depth: Option<usize>,
+}
+
+impl Limits {
+    pub(crate) const fn is_set(&self) -> bool {
+        self.instance_depth.is_some() || self.evaluated_nodes.is_some() || self.ref_depth.is_some()
+    }
+}
+
+/// Why a validation was stopped early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Abort {
+    Limit(Limit),
+    Interrupted(Interruption),
+}
+
+impl Abort {
+    pub(crate) fn into_error(self, instance: &Value) -> ValidationError<'_> {
+        match self {
+            Abort::Limit(limit) => ValidationError::limit_exceeded(instance, limit),
+            Abort::Interrupted(reason) => ValidationError::interrupted(instance, reason),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    limits: Limits,
+    interrupt: Option<Interrupt>,
+    evaluated_nodes: usize,
+    ref_depth: usize,
+    aborted: Option<Abort>,
+}
+
+impl State {
+    fn check_interrupt(&mut self) {
+        if let Some(reason) = self.interrupt.as_ref().and_then(Interrupt::check) {
+            self.aborted = Some(Abort::Interrupted(reason));
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// The number of validations with a thread-local state, across all threads.
+static ENFORCED: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether any validation may have a thread-local state. If not, there is nothing to account for.
+#[inline]
+fn is_enforced() -> bool {
+    ENFORCED.load(Ordering::Relaxed) != 0
+}
+
+/// Restores the state of an outer validation, even if the inner one panics.
+struct Scope {
+    previous: Option<State>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        STATE.with(|state| *state.borrow_mut() = previous);
+        ENFORCED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Run `validate` with `limits` and `interrupt` enforced on the current thread.
+pub(crate) fn enforce<T>(
+    limits: Limits,
+    interrupt: Option<&Interrupt>,
+    instance: &Value,
+    validate: impl FnOnce() -> T,
+) -> Result<T, Abort> {
+    if let Some(reason) = interrupt.and_then(Interrupt::check) {
+        return Err(Abort::Interrupted(reason));
+    }
+    if let Some(limit) = limits.instance_depth {
+        if depth_exceeds(instance, limit) {
+            return Err(Abort::Limit(Limit::InstanceDepth(limit)));
+        }
+    }
+    if limits.evaluated_nodes.is_none() && limits.ref_depth.is_none() && interrupt.is_none() {
+        return Ok(validate());
+    }
+    ENFORCED.fetch_add(1, Ordering::Relaxed);
+    let scope = Scope {
+        previous: STATE.with(|state| {
+            state.replace(Some(State {
+                limits,
+                interrupt: interrupt.cloned(),
+                evaluated_nodes: 0,
+                ref_depth: 0,
+                aborted: None,
+            }))
+        }),
+    };
+    let result = validate();
+    let aborted = STATE.with(|state| state.borrow().as_ref().and_then(|state| state.aborted));
+    drop(scope);
+    match aborted {
+        Some(abort) => Err(abort),
+        None => Ok(result),
+    }
+}
+
+/// Account for a node evaluation. Returns `false` if the evaluation should stop.
+#[inline]
+pub(crate) fn enter_node() -> bool {
+    if !is_enforced() {
+        return true;
+    }
+    STATE.with(|cell| match cell.borrow_mut().as_mut() {
+        None => true,
+        Some(state) => {
+            if state.aborted.is_none() {
+                state.evaluated_nodes += 1;
+                if let Some(limit) = state.limits.evaluated_nodes {
+                    if state.evaluated_nodes > limit {
+                        state.aborted = Some(Abort::Limit(Limit::EvaluatedNodes(limit)));
+                    }
+                }
+                if state.evaluated_nodes % INTERRUPT_CHECK_INTERVAL == 0 {
+                    state.check_interrupt();
+                }
+            }
+            state.aborted.is_none()
+        }
+    })
+}
+
+/// Check the interrupt before evaluating an expensive keyword. Returns `false` if the evaluation
+/// should stop.
+#[inline]
+pub(crate) fn enter_expensive() -> bool {
+    if !is_enforced() {
+        return true;
+    }
+    STATE.with(|cell| match cell.borrow_mut().as_mut() {
+        None => true,
+        Some(state) => {
+            if state.aborted.is_none() {
+                state.check_interrupt();
+            }
+            state.aborted.is_none()
+        }
+    })
+}
+
+/// Leaves a `$ref` target when dropped.
+pub(crate) struct Reference {
+    // Whether the target was entered in the thread-local state
+    is_counted: bool,
+}
+
+impl Drop for Reference {
+    fn drop(&mut self) {
+        if self.is_counted {
+            STATE.with(|cell| {
+                if let Some(state) = cell.borrow_mut().as_mut() {
+                    state.ref_depth = state.ref_depth.saturating_sub(1);
+                }
+            });
+        }
+    }
+}
+
+/// Enter a `$ref` target. Returns `None` if the evaluation should stop.
+#[inline]
+pub(crate) fn enter_reference() -> Option<Reference> {
+    if !is_enforced() {
+        return Some(Reference { is_counted: false });
+    }
+    STATE.with(|cell| match cell.borrow_mut().as_mut() {
+        None => Some(Reference { is_counted: false }),
+        Some(state) => {
+            if state.aborted.is_none() {
+                state.check_interrupt();
+            }
+            if state.aborted.is_some() {
+                return None;
+            }
+            if let Some(limit) = state.limits.ref_depth {
+                if state.ref_depth >= limit {
+                    state.aborted = Some(Abort::Limit(Limit::RefDepth(limit)));
+                    return None;
+                }
+            }
+            state.ref_depth += 1;
+            Some(Reference { is_counted: true })
+        }
+    })
+}
+
+/// Whether arrays & objects in `instance` are nested deeper than `limit` levels.
+/// Implemented without recursion, as the instance may be arbitrarily deep.
+fn depth_exceeds(instance: &Value, limit: usize) -> bool {
+    let mut stack = vec![(instance, 0_usize)];
+    while let Some((value, depth)) = stack.pop() {
+        match value {
+            Value::Array(items) => {
+                if depth >= limit {
+                    return true;
+                }
+                stack.extend(items.iter().map(|item| (item, depth + 1)));
+            }
+            Value::Object(object) => {
+                if depth >= limit {
+                    return true;
+                }
+                stack.extend(object.values().map(|value| (value, depth + 1)));
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::{Limit, ValidationErrorKind},
+        JSONSchema,
+    };
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn nested(depth: usize) -> Value {
+        (0..depth).fold(json!(1), |value, _| json!({ "child": value }))
+    }
+
+    fn assert_exceeded(schema: &JSONSchema, instance: &Value, expected: Limit) {
+        assert!(!schema.is_valid(instance));
+        let errors: Vec<_> = schema
+            .validate(instance)
+            .expect_err("Limit is exceeded")
+            .collect();
+        assert_eq!(errors.len(), 1);
+        match &errors[0].kind {
+            ValidationErrorKind::LimitExceeded { limit } => assert_eq!(*limit, expected),
+            kind => panic!("Unexpected error: {:?}", kind),
+        }
+        assert!(!schema.apply(instance).basic().is_valid());
+    }
+
+    #[test_case(&json!(1), 0, true)]
+    #[test_case(&json!([]), 0, false)]
+    #[test_case(&json!([[1], {"a": 1}]), 2, true)]
+    #[test_case(&json!([[1], {"a": [1]}]), 2, false)]
+    fn instance_depth(instance: &Value, limit: usize, expected: bool) {
+        let schema = JSONSchema::options()
+            .with_max_instance_depth(limit)
+            .compile(&json!({}))
+            .expect("Valid schema");
+        if expected {
+            assert!(schema.is_valid(instance));
+            assert!(schema.validate(instance).is_ok());
+        } else {
+            assert_exceeded(&schema, instance, Limit::InstanceDepth(limit));
+        }
+    }
+
+    #[test]
+    fn evaluated_nodes() {
+        let schema = JSONSchema::options()
+            .with_max_evaluated_nodes(10)
+            .compile(&json!({"items": {"anyOf": [{"type": "string"}, {"type": "integer"}]}}))
+            .expect("Valid schema");
+        assert!(schema.is_valid(&json!([1, "a"])));
+        assert!(schema.validate(&json!([1, "a"])).is_ok());
+        assert_exceeded(
+            &schema,
+            &Value::from(vec![1; 10]),
+            Limit::EvaluatedNodes(10),
+        );
+        // The budget is per validation
+        assert!(schema.is_valid(&json!([1, "a"])));
+    }
+
+    #[test]
+    fn evaluated_nodes_with_errors() {
+        let schema = JSONSchema::options()
+            .with_max_evaluated_nodes(5)
+            .compile(&json!({"items": {"type": "string"}}))
+            .expect("Valid schema");
+        // Errors within the limit are reported as usual
+        let errors: Vec<_> = schema
+            .validate(&json!([1, 2]))
+            .expect_err("Invalid")
+            .map(|error| error.instance_path.to_string())
+            .collect();
+        assert_eq!(errors, vec!["/0", "/1"]);
+    }
+
+    #[test]
+    fn ref_depth() {
+        let schema = JSONSchema::options()
+            .with_max_ref_depth(5)
+            .compile(&json!({"properties": {"child": {"$ref": "#"}}, "required": ["child"]}))
+            .expect("Valid schema");
+        assert!(schema.is_valid(&nested(3)));
+        assert!(schema.validate(&nested(3)).is_ok());
+        assert!(schema.validate(&json!({"child": {}})).is_err());
+        assert_exceeded(&schema, &nested(10), Limit::RefDepth(5));
+    }
+
+    #[test_case(&json!({"$ref": "#"}))]
+    #[test_case(&json!({"allOf": [{"$ref": "#"}]}))]
+    #[test_case(&json!({"anyOf": [{"$ref": "#/definitions/a"}], "definitions": {"a": {"not": {"$ref": "#"}}}}))]
+    fn infinite_recursion(schema: &Value) {
+        let schema = JSONSchema::options()
+            .with_max_ref_depth(100)
+            .compile(schema)
+            .expect("Valid schema");
+        assert_exceeded(&schema, &json!(1), Limit::RefDepth(100));
+    }
+}
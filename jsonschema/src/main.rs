@@ -1,4 +1,4 @@
-use std::{error::Error, fs, path::PathBuf, process};
+use std::{error::Error, fs, io::BufReader, path::PathBuf, process};
 
 use jsonschema::JSONSchema;
 use structopt::StructOpt;
@@ -16,6 +16,39 @@ struct Cli {
     #[structopt(parse(from_os_str), required_unless("version"))]
     schema: Option<PathBuf>,
 
+    /// Treat instances as JSON Lines files and validate every line separately.
+    /// Lines are validated in parallel if built with the `rayon` feature.
+    #[structopt(short = "l", long = "jsonl")]
+    json_lines: bool,
+
+    /// Print an evaluation trace of every instance as a `tree` or as `json`.
+    /// Not supported together with `--jsonl` and the options that print the schema.
+    #[structopt(
+        long = "trace",
+        possible_values = &["tree", "json"],
+        conflicts_with_all = &["json-lines", "bundle", "dereference", "rust-types", "compatible-with"]
+    )]
+    trace: Option<String>,
+
+    /// Print the schema with all external `$ref` documents inlined and exit.
+    #[structopt(short = "b", long = "bundle")]
+    bundle: bool,
+
+    /// Print the schema with every `$ref` replaced by its target and exit.
+    /// Circular references are reported to stderr.
+    #[structopt(short = "d", long = "dereference")]
+    dereference: bool,
+
+    /// Print Rust types with `serde` attributes that mirror the schema and exit.
+    /// The root type is named after the schema file.
+    #[structopt(long = "rust-types")]
+    rust_types: bool,
+
+    /// Compare the schema with its previous version (i.e. old.json), print all changes and exit.
+    /// Exits with a non-zero status if the schema is not backward-compatible.
+    #[structopt(long = "compatible-with", parse(from_os_str))]
+    compatible_with: Option<PathBuf>,
+
     /// Show program's version number and exit.
     #[structopt(short = "v", long = "version")]
     version: bool,
@@ -31,8 +64,28 @@ pub fn main() -> BoxErrorResult<()> {
 
     let mut success = true;
     if let Some(schema) = config.schema {
+        if config.bundle {
+            return bundle(schema);
+        }
+        if config.dereference {
+            return dereference(schema);
+        }
+        if config.rust_types {
+            return rust_types(schema);
+        }
+        if let Some(old) = config.compatible_with {
+            if !compatibility(old, schema)? {
+                process::exit(1);
+            }
+            return Ok(());
+        }
         if let Some(instances) = config.instances {
-            success = validate_instances(&instances, schema)?;
+            success = validate_instances(
+                &instances,
+                schema,
+                config.json_lines,
+                config.trace.as_deref(),
+            )?;
         }
     }
 
@@ -43,7 +96,67 @@ pub fn main() -> BoxErrorResult<()> {
     Ok(())
 }
 
-fn validate_instances(instances: &[PathBuf], schema: PathBuf) -> BoxErrorResult<bool> {
+fn bundle(schema: PathBuf) -> BoxErrorResult<()> {
+    let schema_json = fs::read_to_string(schema)?;
+    let schema_json = serde_json::from_str(&schema_json)?;
+    let bundled = JSONSchema::options().bundle(&schema_json)?;
+    println!("{}", serde_json::to_string_pretty(&bundled)?);
+    Ok(())
+}
+
+fn dereference(schema: PathBuf) -> BoxErrorResult<()> {
+    let schema_json = fs::read_to_string(schema)?;
+    let schema_json = serde_json::from_str(&schema_json)?;
+    let dereferenced = jsonschema::dereference(&schema_json, &JSONSchema::options())?;
+    for circular in &dereferenced.circular_references {
+        eprintln!(
+            "Circular reference to {} at {}",
+            circular.reference, circular.location
+        );
+    }
+    println!("{}", serde_json::to_string_pretty(&dereferenced.schema)?);
+    Ok(())
+}
+
+fn rust_types(schema: PathBuf) -> BoxErrorResult<()> {
+    let root = schema
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.split('.').next())
+        .unwrap_or("Root")
+        .to_string();
+    let schema_json = fs::read_to_string(schema)?;
+    let schema_json = serde_json::from_str(&schema_json)?;
+    let code = jsonschema::codegen::rust_types(&schema_json, &JSONSchema::options(), &root)?;
+    print!("{}", code);
+    Ok(())
+}
+
+fn compatibility(old: PathBuf, new: PathBuf) -> BoxErrorResult<bool> {
+    let old_json = serde_json::from_str(&fs::read_to_string(old)?)?;
+    let new_json = serde_json::from_str(&fs::read_to_string(new)?)?;
+    let report = jsonschema::compatibility::check(&old_json, &new_json)?;
+    for change in &report.changes {
+        println!("{}", change);
+    }
+    let answer = |compatible| if compatible { "yes" } else { "no" };
+    println!(
+        "Backward compatible: {}",
+        answer(report.is_backward_compatible())
+    );
+    println!(
+        "Forward compatible: {}",
+        answer(report.is_forward_compatible())
+    );
+    Ok(report.is_backward_compatible())
+}
+
+fn validate_instances(
+    instances: &[PathBuf],
+    schema: PathBuf,
+    json_lines: bool,
+    trace: Option<&str>,
+) -> BoxErrorResult<bool> {
     let mut success = true;
 
     let schema_json = fs::read_to_string(schema)?;
@@ -52,6 +165,21 @@ fn validate_instances(instances: &[PathBuf], schema: PathBuf) -> BoxErrorResult<
         Ok(schema) => {
             for instance in instances {
                 let instance_path_name = instance.to_str().unwrap();
+                if json_lines {
+                    let reader = BufReader::new(fs::File::open(&instance)?);
+                    schema.validate_json_lines(reader, |report| {
+                        if report.is_valid() {
+                            println!("{}:{} - VALID", instance_path_name, report.line);
+                        } else {
+                            success = false;
+                            println!("{}:{} - INVALID. Errors:", instance_path_name, report.line);
+                            for (i, e) in report.errors.iter().enumerate() {
+                                println!("{}. {}", i + 1, e);
+                            }
+                        }
+                    })?;
+                    continue;
+                }
                 let instance_json = fs::read_to_string(&instance)?;
                 let instance_json = serde_json::from_str(&instance_json)?;
                 let validation = schema.validate(&instance_json);
@@ -66,6 +194,14 @@ fn validate_instances(instances: &[PathBuf], schema: PathBuf) -> BoxErrorResult<
                         }
                     }
                 }
+                match trace {
+                    Some("json") => {
+                        let trace = schema.apply(&instance_json).trace();
+                        println!("{}", serde_json::to_string_pretty(&trace)?);
+                    }
+                    Some(_) => print!("{}", schema.apply(&instance_json).trace()),
+                    None => {}
+                }
             }
         }
         Err(error) => {
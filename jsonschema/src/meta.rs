@@ -0,0 +1,80 @@
+//! Validation of schemas against the meta-schemas of JSON Schema drafts.
+//!
+//! The draft is detected from the `$schema` keyword. `Draft::default()` is used if it is missing
+//! or not recognized.
+//!
+//! ```rust
+//! use serde_json::json;
+//!
+//! let schema = json!({"type": "unknown", "minLength": -1});
+//! assert!(!jsonschema::meta::is_valid(&schema));
+//! let errors = jsonschema::meta::validate(&schema).expect_err("Invalid schema");
+//! assert_eq!(errors.len(), 2);
+//! for error in errors {
+//!     // `instance_path` points to the invalid part of the schema
+//!     println!("{}: {}", error.instance_path, error);
+//! }
+//! ```
+use crate::{compilation::options::META_SCHEMA_VALIDATORS, schemas, JSONSchema, ValidationError};
+use serde_json::Value;
+
+/// Validate `schema` against the meta-schema of its draft and return all violations.
+pub fn validate(schema: &Value) -> Result<(), Vec<ValidationError<'_>>> {
+    meta_schema_validator(schema)
+        .validate(schema)
+        .map_err(Iterator::collect)
+}
+
+/// Check whether `schema` is valid against the meta-schema of its draft.
+#[must_use]
+pub fn is_valid(schema: &Value) -> bool {
+    meta_schema_validator(schema).is_valid(schema)
+}
+
+fn meta_schema_validator(schema: &Value) -> &'static JSONSchema {
+    let draft = schemas::draft_from_schema(schema).unwrap_or_default();
+    META_SCHEMA_VALIDATORS.get(&draft).expect("Existing draft")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid, validate};
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(&json!({"type": "string"}))]
+    #[test_case(&json!(true))]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "minimum": 0, "exclusiveMinimum": true}))]
+    fn valid(schema: &Value) {
+        assert!(is_valid(schema));
+        assert!(validate(schema).is_ok());
+    }
+
+    #[test]
+    fn all_errors() {
+        let schema = json!({
+            "type": "unknown",
+            "properties": {"name": {"minLength": -1}},
+            "required": "name"
+        });
+        assert!(!is_valid(&schema));
+        let errors = validate(&schema).expect_err("Invalid schema");
+        let mut paths: Vec<_> = errors
+            .iter()
+            .map(|error| error.instance_path.to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["/properties/name/minLength", "/required", "/type"]
+        );
+    }
+
+    #[test]
+    fn detects_draft() {
+        // Boolean `exclusiveMinimum` is only valid in Draft 4
+        let schema =
+            json!({"$schema": "http://json-schema.org/draft-07/schema#", "exclusiveMinimum": true});
+        assert!(!is_valid(&schema));
+    }
+}
@@ -9,8 +9,11 @@ use ahash::AHashMap;
 use serde::ser::SerializeMap;
 
 use crate::{
+    interrupt::Interrupt,
+    limits,
     paths::{AbsolutePath, InstancePath, JSONPointer},
     schema_node::SchemaNode,
+    trace::{self, Trace},
     JSONSchema,
 };
 
@@ -24,6 +27,7 @@ pub struct Output<'a, 'b> {
     schema: &'a JSONSchema,
     root_node: &'a SchemaNode,
     instance: &'b serde_json::Value,
+    interrupt: Option<Interrupt>,
 }
 
 impl<'a, 'b> Output<'a, 'b> {
@@ -31,26 +35,53 @@ impl<'a, 'b> Output<'a, 'b> {
         schema: &'c JSONSchema,
         root_node: &'c SchemaNode,
         instance: &'d serde_json::Value,
+        interrupt: Option<Interrupt>,
     ) -> Output<'c, 'd> {
         Output {
             schema,
             root_node,
             instance,
+            interrupt,
         }
     }
 
     /// Indicates whether the schema was valid, corresponds to the "flag" output
     /// format
     pub fn flag(&self) -> bool {
-        self.schema.is_valid(self.instance)
+        self.schema
+            .is_valid_impl(self.instance, self.interrupt.as_ref())
     }
 
     /// Output a list of errors and annotations for each element in the schema
     /// according to the basic output format
     pub fn basic(&self) -> BasicOutput<'a> {
+        let limits = self.schema.config().limits();
+        if limits.is_set() || self.interrupt.is_some() {
+            return limits::enforce(limits, self.interrupt.as_ref(), self.instance, || {
+                self.root_node
+                    .apply_rooted(self.schema, self.instance, &InstancePath::new())
+            })
+            .unwrap_or_else(|abort| {
+                let error = abort.into_error(self.instance);
+                let mut errors = VecDeque::new();
+                errors.push_back(OutputUnit::<ErrorDescription>::error(
+                    JSONPointer::default(),
+                    JSONPointer::default(),
+                    None,
+                    error.into(),
+                ));
+                BasicOutput::Invalid(errors)
+            });
+        }
         self.root_node
             .apply_rooted(self.schema, self.instance, &InstancePath::new())
     }
+
+    /// Evaluate the schema like `basic` and record every evaluated keyword.
+    /// See the [`trace`](crate::trace) module for details.
+    pub fn trace(&self) -> Trace {
+        trace::record(|| self.basic())
+    }
 }
 
 /// The "basic" output format
@@ -171,9 +202,42 @@ impl<T> OutputUnit<T> {
     }
 }
 
+impl<T> OutputUnit<T> {
+    /// Move this unit under `prefix`, e.g. from a `$ref` target to the `$ref` keyword.
+    pub(crate) fn with_prefix(mut self, prefix: &JSONPointer) -> Self {
+        self.keyword_location = prefix.extend_with(self.keyword_location.as_slice());
+        self
+    }
+}
+
+impl OutputUnit<Annotations<'_>> {
+    pub(crate) fn into_owned(self) -> OutputUnit<Annotations<'static>> {
+        OutputUnit {
+            keyword_location: self.keyword_location,
+            instance_location: self.instance_location,
+            absolute_keyword_location: self.absolute_keyword_location,
+            value: self.value.into_owned(),
+        }
+    }
+}
+
 #[derive(serde::Serialize, Debug, Clone, PartialEq)]
 pub struct Annotations<'a>(AnnotationsInner<'a>);
 
+impl Annotations<'_> {
+    pub(crate) fn into_owned(self) -> Annotations<'static> {
+        let value = match self.0 {
+            AnnotationsInner::UnmatchedKeywords(annotations) => annotations
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            AnnotationsInner::ValueRef(value) => value.clone(),
+            AnnotationsInner::Value(value) => *value,
+        };
+        Annotations(AnnotationsInner::Value(Box::new(value)))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum AnnotationsInner<'a> {
     UnmatchedKeywords(&'a AHashMap<String, serde_json::Value>),
@@ -202,6 +266,12 @@ impl<'a> From<serde_json::Value> for Annotations<'a> {
 #[derive(serde::Serialize, Debug, Clone, PartialEq)]
 pub struct ErrorDescription(String);
 
+impl ErrorDescription {
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl From<ValidationError<'_>> for ErrorDescription {
     fn from(e: ValidationError<'_>) -> Self {
         ErrorDescription(e.to_string())
@@ -0,0 +1,456 @@
+//! Aggregated evaluation time per schema location.
+//!
+//! A [`Profiler`] runs validations of a single compiled schema and accumulates how many times
+//! every keyword was evaluated and how long it took, across all validations. The report is
+//! sorted by the total time, so the slowest parts of a schema are at the top.
+//!
+//! ```rust
+//! use jsonschema::JSONSchema;
+//! use serde_json::json;
+//!
+//! let schema = json!({
+//!     "properties": {
+//!         "name": {"type": "string", "pattern": "^[a-z]+$"},
+//!         "tags": {"items": {"maxLength": 10}}
+//!     }
+//! });
+//! let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+//! let instances = vec![json!({"name": "foo", "tags": ["a", "b"]}), json!({"name": "Bar"})];
+//! let mut profiler = compiled.profiler();
+//! for instance in &instances {
+//!     profiler.is_valid(instance);
+//! }
+//! let report = profiler.report();
+//! assert_eq!(report.validations, 2);
+//! let pattern = report
+//!     .entries
+//!     .iter()
+//!     .find(|entry| entry.schema_path == "#/properties/name/pattern")
+//!     .expect("Evaluated");
+//! assert_eq!(pattern.count, 2);
+//! println!("{}", report);
+//! ```
+//!
+//! The total time of a keyword includes the time spent in its sub-schemas, the self time does
+//! not. Validation is noticeably slower while profiling, so the absolute numbers are only useful
+//! for comparing keywords with each other. While no profiler is running, nodes only check a
+//! process-wide counter and don't touch the thread-local state.
+use crate::{
+    compilation::DEFAULT_ROOT_URL, error::ValidationError, schema_node::SchemaNode, JSONSchema,
+};
+use ahash::AHashMap;
+use serde_json::Value;
+use std::{
+    cell::{Cell, RefCell},
+    fmt, mem,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Statistics of a single keyword. Keyed by the address of its node & its position in the node.
+#[derive(Debug, Clone)]
+struct Stats {
+    schema_path: String,
+    count: u64,
+    total: Duration,
+    self_time: Duration,
+}
+
+type StatsMap = AHashMap<(usize, usize), Stats>;
+
+#[derive(Debug)]
+struct Collector {
+    stats: StatsMap,
+    /// Time spent in sub-schemas of the keywords being evaluated, innermost last.
+    children: Vec<Duration>,
+    /// Locations of the `$ref` targets being evaluated, innermost last.
+    references: Vec<String>,
+}
+
+thread_local! {
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+    static COLLECTOR: RefCell<Option<Collector>> = const { RefCell::new(None) };
+}
+
+/// The number of running profiled validations, across all threads.
+static PROFILED: AtomicUsize = AtomicUsize::new(0);
+
+/// Restores the state of an outer profiling, even if the validation panics.
+struct Scope {
+    active: bool,
+    previous: Option<Collector>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        COLLECTOR.with(|collector| *collector.borrow_mut() = previous);
+        ACTIVE.with(|active| active.set(self.active));
+        PROFILED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Whether the current validation is profiled.
+#[inline]
+pub(crate) fn is_active() -> bool {
+    PROFILED.load(Ordering::Relaxed) != 0 && ACTIVE.with(Cell::get)
+}
+
+/// Evaluate a keyword of `node` and account for the time it took.
+/// `keyword` is `None` for array-valued & boolean schemas.
+pub(crate) fn measure<T>(
+    node: &SchemaNode,
+    index: usize,
+    keyword: Option<&str>,
+    evaluate: impl FnOnce() -> T,
+) -> T {
+    COLLECTOR.with(|collector| {
+        if let Some(collector) = collector.borrow_mut().as_mut() {
+            collector.children.push(Duration::default());
+        }
+    });
+    let start = Instant::now();
+    let result = evaluate();
+    let elapsed = start.elapsed();
+    COLLECTOR.with(|collector| {
+        if let Some(collector) = collector.borrow_mut().as_mut() {
+            let children = collector.children.pop().unwrap_or_default();
+            if let Some(parent) = collector.children.last_mut() {
+                *parent += elapsed;
+            }
+            let Collector {
+                stats, references, ..
+            } = collector;
+            let address: *const SchemaNode = node;
+            let stats = stats
+                .entry((address as usize, index))
+                .or_insert_with(|| Stats {
+                    schema_path: schema_path(references.last(), node, index, keyword),
+                    count: 0,
+                    total: Duration::default(),
+                    self_time: Duration::default(),
+                });
+            stats.count += 1;
+            stats.total += elapsed;
+            stats.self_time += elapsed.saturating_sub(children);
+        }
+    });
+    result
+}
+
+/// Evaluate the target of `reference`. Locations within the target are reported relative to the
+/// target, e.g. `#/definitions/item/minimum`, so recursive references have a finite number of
+/// locations.
+pub(crate) fn within_reference<T>(reference: &Url, evaluate: impl FnOnce() -> T) -> T {
+    COLLECTOR.with(|collector| {
+        if let Some(collector) = collector.borrow_mut().as_mut() {
+            let fragment = reference.fragment().unwrap_or("");
+            let mut document = reference.clone();
+            document.set_fragment(None);
+            let prefix = if document.as_str() == DEFAULT_ROOT_URL {
+                format!("#{}", fragment)
+            } else {
+                format!("{}#{}", document, fragment)
+            };
+            collector.references.push(prefix);
+        }
+    });
+    let result = evaluate();
+    COLLECTOR.with(|collector| {
+        if let Some(collector) = collector.borrow_mut().as_mut() {
+            collector.references.pop();
+        }
+    });
+    result
+}
+
+fn schema_path(
+    reference: Option<&String>,
+    node: &SchemaNode,
+    index: usize,
+    keyword: Option<&str>,
+) -> String {
+    let path = node.relative_path();
+    let path = match keyword {
+        Some(keyword) => path.clone_with(keyword.to_string()),
+        None if node.is_array() => path.clone_with(index),
+        None => path.clone(),
+    };
+    match reference {
+        Some(reference) => format!("{}{}", reference, path),
+        None => format!("#{}", path),
+    }
+}
+
+/// Collects evaluation statistics for a compiled schema.
+/// Created via [`JSONSchema::profiler`].
+#[derive(Debug)]
+pub struct Profiler<'a> {
+    schema: &'a JSONSchema,
+    stats: StatsMap,
+    validations: u64,
+}
+
+impl<'a> Profiler<'a> {
+    pub(crate) fn new(schema: &'a JSONSchema) -> Profiler<'a> {
+        Profiler {
+            schema,
+            stats: AHashMap::new(),
+            validations: 0,
+        }
+    }
+
+    fn run<T>(&mut self, validate: impl FnOnce() -> T) -> T {
+        PROFILED.fetch_add(1, Ordering::Relaxed);
+        let scope = Scope {
+            active: ACTIVE.with(|active| active.replace(true)),
+            previous: COLLECTOR.with(|collector| {
+                collector.replace(Some(Collector {
+                    stats: mem::take(&mut self.stats),
+                    children: Vec::new(),
+                    references: Vec::new(),
+                }))
+            }),
+        };
+        let result = validate();
+        if let Some(collector) = COLLECTOR.with(|collector| collector.borrow_mut().take()) {
+            self.stats = collector.stats;
+        }
+        drop(scope);
+        self.validations += 1;
+        result
+    }
+
+    /// Profile [`JSONSchema::is_valid`].
+    pub fn is_valid(&mut self, instance: &Value) -> bool {
+        let schema = self.schema;
+        self.run(|| schema.is_valid(instance))
+    }
+
+    /// Profile [`JSONSchema::validate`]. All errors are collected.
+    pub fn validate<'b>(&mut self, instance: &'b Value) -> Result<(), Vec<ValidationError<'b>>>
+    where
+        'a: 'b,
+    {
+        let schema = self.schema;
+        self.run(|| {
+            schema
+                .validate(instance)
+                .map_err(|errors| errors.collect::<Vec<_>>())
+        })
+    }
+
+    /// Add statistics collected by another profiler of the same schema, e.g. in another thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` profiles a different schema, as statistics are keyed by compiled nodes.
+    pub fn merge(&mut self, other: Profiler<'_>) {
+        assert!(
+            std::ptr::eq(self.schema, other.schema),
+            "Can not merge profilers of different schemas"
+        );
+        self.validations += other.validations;
+        for (key, stats) in other.stats {
+            let entry = self.stats.entry(key).or_insert_with(|| Stats {
+                count: 0,
+                total: Duration::default(),
+                self_time: Duration::default(),
+                ..stats.clone()
+            });
+            entry.count += stats.count;
+            entry.total += stats.total;
+            entry.self_time += stats.self_time;
+        }
+    }
+
+    /// Statistics per schema location, the slowest first.
+    #[must_use]
+    pub fn report(&self) -> ProfileReport {
+        // The same location may be compiled more than once, e.g. as a `$ref` target
+        let mut by_path: AHashMap<&str, ProfileEntry> = AHashMap::new();
+        for stats in self.stats.values() {
+            let entry = by_path
+                .entry(&stats.schema_path)
+                .or_insert_with(|| ProfileEntry {
+                    schema_path: stats.schema_path.clone(),
+                    count: 0,
+                    total: Duration::default(),
+                    self_time: Duration::default(),
+                });
+            entry.count += stats.count;
+            entry.total += stats.total;
+            entry.self_time += stats.self_time;
+        }
+        let mut entries: Vec<_> = by_path.into_iter().map(|(_, entry)| entry).collect();
+        entries.sort_by(|left, right| {
+            right
+                .total
+                .cmp(&left.total)
+                .then_with(|| left.schema_path.cmp(&right.schema_path))
+        });
+        ProfileReport {
+            validations: self.validations,
+            entries,
+        }
+    }
+}
+
+/// Statistics collected by a [`Profiler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileReport {
+    /// Number of profiled validations.
+    pub validations: u64,
+    /// Statistics per schema location, sorted by the total time in descending order.
+    pub entries: Vec<ProfileEntry>,
+}
+
+/// Statistics of a single schema location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileEntry {
+    /// Location of the keyword, e.g. `#/properties/items/items/$ref`.
+    pub schema_path: String,
+    /// How many times the keyword was evaluated.
+    pub count: u64,
+    /// Time spent evaluating the keyword, including its sub-schemas.
+    pub total: Duration,
+    /// Time spent evaluating the keyword, excluding its sub-schemas.
+    pub self_time: Duration,
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Validations: {}", self.validations)?;
+        writeln!(
+            f,
+            "{:>14} {:>14} {:>10}  schema path",
+            "total", "self", "count"
+        )?;
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:>14} {:>14} {:>10}  {}",
+                format!("{:?}", entry.total),
+                format!("{:?}", entry.self_time),
+                entry.count,
+                entry.schema_path
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn counts(schema: &Value, instances: &[Value]) -> Vec<(String, u64)> {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        let mut profiler = compiled.profiler();
+        for instance in instances {
+            assert_eq!(profiler.is_valid(instance), compiled.is_valid(instance));
+        }
+        let mut counts: Vec<_> = profiler
+            .report()
+            .entries
+            .into_iter()
+            .map(|entry| (entry.schema_path, entry.count))
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    fn count(path: &str, count: u64) -> (String, u64) {
+        (path.to_string(), count)
+    }
+
+    #[test_case(
+        &json!({"type": "integer", "minimum": 1}),
+        &[json!(1), json!(2)],
+        vec![count("#/minimum", 2), count("#/type", 2)]
+    )]
+    #[test_case(
+        &json!({"items": {"maxLength": 2}}),
+        &[json!(["a", "b", "c"]), json!(["a"])],
+        vec![count("#/items", 2), count("#/items/maxLength", 4)]
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"$ref": "#/definitions/a"}}, "definitions": {"a": {"minimum": 1}}}),
+        &[json!({"a": 1}), json!({"b": 1})],
+        vec![count("#/definitions/a/minimum", 1), count("#/properties", 2), count("#/properties/a/$ref", 1)];
+        "reference"
+    )]
+    #[test_case(
+        &json!({"properties": {"child": {"$ref": "#"}}, "minimum": 1}),
+        &[json!({"child": {"child": 1}})],
+        vec![count("#/minimum", 3), count("#/properties", 3), count("#/properties/child/$ref", 2)];
+        "recursive reference"
+    )]
+    fn evaluation_counts(schema: &Value, instances: &[Value], expected: Vec<(String, u64)>) {
+        assert_eq!(counts(schema, instances), expected);
+    }
+
+    #[test]
+    fn validate() {
+        let compiled =
+            JSONSchema::compile(&json!({"items": {"type": "string"}})).expect("Valid schema");
+        let mut profiler = compiled.profiler();
+        let instance = json!([1, "a", 2]);
+        let errors = profiler.validate(&instance).expect_err("Invalid");
+        assert_eq!(errors.len(), 2);
+        let report = profiler.report();
+        assert_eq!(report.validations, 1);
+        assert_eq!(report.entries[0].schema_path, "#/items");
+        assert_eq!(report.entries[1].count, 3);
+        assert!(report.entries[0].total >= report.entries[1].total);
+        assert!(report.entries[0].self_time <= report.entries[0].total);
+    }
+
+    #[test]
+    fn merge() {
+        let compiled = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+        let mut first = compiled.profiler();
+        first.is_valid(&json!("a"));
+        let mut second = compiled.profiler();
+        second.is_valid(&json!(1));
+        second.is_valid(&json!(2));
+        first.merge(second);
+        let report = first.report();
+        assert_eq!(report.validations, 3);
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].count, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can not merge profilers of different schemas")]
+    fn merge_different_schemas() {
+        let first = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+        let second = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+        first.profiler().merge(second.profiler());
+    }
+
+    #[test]
+    fn not_active_outside_of_profiler() {
+        let compiled = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+        let mut profiler = compiled.profiler();
+        profiler.is_valid(&json!("a"));
+        assert!(!super::is_active());
+        assert!(compiled.is_valid(&json!("a")));
+        assert_eq!(profiler.report().entries[0].count, 1);
+    }
+
+    #[test]
+    fn display() {
+        let compiled = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+        let mut profiler = compiled.profiler();
+        profiler.is_valid(&json!("a"));
+        let report = profiler.report().to_string();
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines[0], "Validations: 1");
+        assert!(lines[1].ends_with("count  schema path"));
+        assert!(lines[2].ends_with("1  #/type"));
+    }
+}
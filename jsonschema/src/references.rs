@@ -0,0 +1,361 @@
+//! Introspection of identifiers (`$id`) & references (`$ref`) across a schema and all documents
+//! it depends on.
+use crate::{
+    compilation::{JSONSchema, DEFAULT_SCOPE},
+    paths::{JSONPointer, PathChunk},
+    schemas::{self, id_keyword, Draft},
+};
+use ahash::AHashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use url::Url;
+
+/// An `$id` (`id` in Draft 4) found in one of the documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identifier {
+    /// The absolute URI that the identifier resolves to.
+    pub uri: String,
+    /// URI of the document that contains the identifier.
+    pub document: String,
+    /// Location of the identified schema within the document.
+    pub location: JSONPointer,
+}
+
+/// A `$ref` found in one of the documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// The value of `$ref` as it is written in the document.
+    pub reference: String,
+    /// The absolute URI of the reference target.
+    pub uri: String,
+    /// URI of the document that contains the reference.
+    pub document: String,
+    /// Location of the `$ref` keyword within the document.
+    pub location: JSONPointer,
+    /// Whether the reference target exists.
+    pub resolved: bool,
+}
+
+/// All identifiers & references of a schema and the documents it depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceGraph {
+    /// Identifiers in the order they appear in the documents.
+    pub identifiers: Vec<Identifier>,
+    /// References in the order they appear in the documents.
+    pub references: Vec<Reference>,
+    /// Document URI -> URIs of other documents it references.
+    /// Every visited document is present, even if it has no dependencies.
+    pub dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ReferenceGraph {
+    /// URIs of all documents that depend on `document`, directly or transitively.
+    /// Useful to find which schemas are affected by a change of a shared document.
+    #[must_use]
+    pub fn dependents(&self, document: &str) -> BTreeSet<String> {
+        let mut dependents = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(document);
+        while let Some(current) = queue.pop_front() {
+            for (dependent, dependencies) in &self.dependencies {
+                if dependencies.contains(current) && dependents.insert(dependent.clone()) {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+        dependents.remove(document);
+        dependents
+    }
+}
+
+pub(crate) fn collect(schema: &JSONSchema) -> ReferenceGraph {
+    let draft = schema.draft();
+    let scope = match schemas::id_of(draft, &schema.schema) {
+        Some(url) => Url::parse(url).unwrap_or_else(|_| DEFAULT_SCOPE.clone()),
+        None => DEFAULT_SCOPE.clone(),
+    };
+    let root = resource_of(&scope);
+    let root_uri = root.to_string();
+    let mut collector = Collector {
+        draft,
+        identifiers: Vec::new(),
+        references: Vec::new(),
+        resources: AHashMap::new(),
+    };
+    collector
+        .resources
+        .insert(root_uri.clone(), root_uri.clone());
+    let mut queue = VecDeque::new();
+    let mut visited = vec![root_uri.clone()];
+    let mut path = Vec::new();
+    collector.walk(&schema.schema, &scope, &root_uri, &mut path);
+    let mut next_reference = 0;
+    loop {
+        // External documents are visited once all references of the previous ones are known
+        while next_reference < collector.references.len() {
+            let reference = &mut collector.references[next_reference];
+            next_reference += 1;
+            if let Ok(url) = Url::parse(&reference.uri) {
+                reference.resolved = schema.resolver.resolve_fragment(draft, &url).is_ok();
+                let resource = url_without_fragment(&url);
+                if reference.resolved
+                    && !collector.resources.contains_key(&resource)
+                    && !visited.contains(&resource)
+                {
+                    visited.push(resource.clone());
+                    queue.push_back(url);
+                }
+            }
+        }
+        match queue.pop_front() {
+            Some(url) => {
+                let resource = resource_of(&url);
+                if let Ok(document) = schema.resolver.resolve_url(&resource) {
+                    let document_uri = resource.to_string();
+                    collector
+                        .resources
+                        .insert(document_uri.clone(), document_uri.clone());
+                    collector.walk(&document, &resource, &document_uri, &mut path);
+                }
+            }
+            None => break,
+        }
+    }
+    let mut dependencies: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for document in collector.resources.values() {
+        dependencies.entry(document.clone()).or_default();
+    }
+    for reference in &collector.references {
+        if let Ok(url) = Url::parse(&reference.uri) {
+            let resource = url_without_fragment(&url);
+            let target = collector
+                .resources
+                .get(&resource)
+                .cloned()
+                .unwrap_or(resource);
+            if target != reference.document {
+                dependencies
+                    .entry(reference.document.clone())
+                    .or_default()
+                    .insert(target);
+            }
+        }
+    }
+    ReferenceGraph {
+        identifiers: collector.identifiers,
+        references: collector.references,
+        dependencies,
+    }
+}
+
+struct Collector {
+    draft: Draft,
+    identifiers: Vec<Identifier>,
+    references: Vec<Reference>,
+    // Resource URI -> URI of the document that defines it
+    resources: AHashMap<String, String>,
+}
+
+impl Collector {
+    fn walk(&mut self, schema: &Value, base: &Url, document: &str, path: &mut Vec<PathChunk>) {
+        match schema {
+            Value::Object(object) => {
+                let mut base = base.clone();
+                if let Some(id) = object.get(id_keyword(self.draft)).and_then(Value::as_str) {
+                    if let Ok(url) = base.join(id) {
+                        let uri = normalize(&url);
+                        self.resources
+                            .insert(url_without_fragment(&url), document.to_string());
+                        self.identifiers.push(Identifier {
+                            uri,
+                            document: document.to_string(),
+                            location: JSONPointer::from(path.as_slice()),
+                        });
+                        base = url;
+                    }
+                }
+                for (key, value) in object {
+                    match (key.as_str(), value) {
+                        ("enum", _) | ("const", _) => {}
+                        ("$ref", Value::String(reference)) => {
+                            path.push(key.clone().into());
+                            self.references.push(Reference {
+                                reference: reference.clone(),
+                                uri: base
+                                    .join(reference)
+                                    .map_or_else(|_| reference.clone(), |url| normalize(&url)),
+                                document: document.to_string(),
+                                location: JSONPointer::from(path.as_slice()),
+                                resolved: false,
+                            });
+                            path.pop();
+                        }
+                        (_, value) => {
+                            path.push(key.clone().into());
+                            self.walk(value, &base, document, path);
+                            path.pop();
+                        }
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(idx.into());
+                    self.walk(item, base, document, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resource_of(url: &Url) -> Url {
+    let mut resource = url.clone();
+    resource.set_fragment(None);
+    resource
+}
+
+fn url_without_fragment(url: &Url) -> String {
+    resource_of(url).to_string()
+}
+
+/// Empty fragments are not distinguishable from absent ones.
+fn normalize(url: &Url) -> String {
+    match url.fragment() {
+        Some("") => url_without_fragment(url),
+        _ => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JSONSchema;
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn reference_graph() {
+        let schema = json!({
+            "$id": "http://example.com/root.json",
+            "properties": {
+                "person": {"$ref": "person.json"},
+                "local": {"$ref": "#/definitions/local"},
+                "missing": {"$ref": "#/definitions/missing"},
+                "nested": {"$ref": "nested.json#/definitions/a"}
+            },
+            "definitions": {
+                "local": {},
+                "nested": {"$id": "nested.json", "definitions": {"a": {}}}
+            }
+        });
+        let compiled = JSONSchema::options()
+            .with_document(
+                "http://example.com/person.json".to_string(),
+                json!({"properties": {"address": {"$ref": "address.json"}}}),
+            )
+            .with_document("http://example.com/address.json".to_string(), json!({}))
+            .compile(&schema)
+            .expect("Valid schema");
+        let graph = compiled.references();
+
+        let identifiers: Vec<_> = graph
+            .identifiers
+            .iter()
+            .map(|id| (id.uri.as_str(), id.location.to_string()))
+            .collect();
+        assert_eq!(
+            identifiers,
+            vec![
+                ("http://example.com/root.json", "".to_string()),
+                (
+                    "http://example.com/nested.json",
+                    "/definitions/nested".to_string()
+                )
+            ]
+        );
+
+        let references: Vec<_> = graph
+            .references
+            .iter()
+            .map(|reference| {
+                (
+                    reference.uri.as_str(),
+                    reference.document.as_str(),
+                    reference.location.to_string(),
+                    reference.resolved,
+                )
+            })
+            .collect();
+        assert_eq!(
+            references,
+            vec![
+                (
+                    "http://example.com/root.json#/definitions/local",
+                    "http://example.com/root.json",
+                    "/properties/local/$ref".to_string(),
+                    true
+                ),
+                (
+                    "http://example.com/root.json#/definitions/missing",
+                    "http://example.com/root.json",
+                    "/properties/missing/$ref".to_string(),
+                    false
+                ),
+                (
+                    "http://example.com/nested.json#/definitions/a",
+                    "http://example.com/root.json",
+                    "/properties/nested/$ref".to_string(),
+                    true
+                ),
+                (
+                    "http://example.com/person.json",
+                    "http://example.com/root.json",
+                    "/properties/person/$ref".to_string(),
+                    true
+                ),
+                (
+                    "http://example.com/address.json",
+                    "http://example.com/person.json",
+                    "/properties/address/$ref".to_string(),
+                    true
+                ),
+            ]
+        );
+
+        let dependencies: Vec<_> = graph
+            .dependencies
+            .iter()
+            .map(|(document, dependencies)| {
+                (
+                    document.as_str(),
+                    dependencies.iter().map(String::as_str).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            dependencies,
+            vec![
+                ("http://example.com/address.json", vec![]),
+                (
+                    "http://example.com/person.json",
+                    vec!["http://example.com/address.json"]
+                ),
+                (
+                    "http://example.com/root.json",
+                    vec!["http://example.com/person.json"]
+                ),
+            ]
+        );
+        let expected: BTreeSet<_> = vec![
+            "http://example.com/person.json".to_string(),
+            "http://example.com/root.json".to_string(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            graph.dependents("http://example.com/address.json"),
+            expected
+        );
+    }
+}
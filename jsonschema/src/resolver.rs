@@ -46,7 +46,7 @@ impl Resolver {
     ///   - the root document (`DEFAULT_ROOT_URL`) case;
     ///   - named subschema that is stored in `self.schemas`;
     ///   - document from a remote location;
-    fn resolve_url<'a>(&'a self, url: &Url) -> Result<Arc<Value>, ValidationError> {
+    pub(crate) fn resolve_url<'a>(&'a self, url: &Url) -> Result<Arc<Value>, ValidationError> {
         match url.as_str() {
             DEFAULT_ROOT_URL => Ok(self.root_schema.clone()),
             url_str => {
@@ -79,6 +79,11 @@ impl Resolver {
         }
     }
 
+    /// All documents from the store, including ones loaded from remote locations.
+    pub(crate) fn documents(&self) -> AHashMap<String, Arc<Value>> {
+        self.store.read().clone()
+    }
+
     /// Resolve a URL possibly containing a fragment to a `serde_json::Value`.
     ///
     /// Note that this copies the fragment from the underlying schema, so if
@@ -111,7 +116,13 @@ impl Resolver {
         let document = self.resolve_url(&resource)?;
         match pointer(draft, &document, fragment.as_ref()) {
             Some((folders, resolved)) => {
-                let joined_folders = join_folders(resource, &folders)?;
+                // `resource` already accounts for the `$id` of the document itself
+                let folders = if id_of(draft, &document).is_some() {
+                    folders.get(1..).unwrap_or_default()
+                } else {
+                    &folders[..]
+                };
+                let joined_folders = join_folders(resource, folders)?;
                 Ok((joined_folders, Arc::new(resolved.clone())))
             }
             None => Err(ValidationError::invalid_reference(url.as_str().to_string())),
@@ -120,10 +131,8 @@ impl Resolver {
 }
 
 fn join_folders(mut resource: Url, folders: &[&str]) -> Result<Url, url::ParseError> {
-    if folders.len() > 1 {
-        for i in folders.iter().skip(1) {
-            resource = resource.join(i)?;
-        }
+    for folder in folders {
+        resource = resource.join(folder)?;
     }
     Ok(resource)
 }
@@ -237,6 +246,7 @@ mod tests {
     use crate::JSONSchema;
     use serde_json::json;
     use std::sync::Arc;
+    use test_case::test_case;
     use url::Url;
 
     fn make_resolver(schema: &Value) -> Resolver {
@@ -495,6 +505,33 @@ mod tests {
         assert_eq!(resolved.as_ref(), schema.pointer("/definitions/a").unwrap());
     }
 
+    #[test_case(
+        &json!({"definitions": {"a": {"$id": "folder/", "items": {"type": "integer"}}}}),
+        "json-schema:///#/definitions/a/items",
+        "json-schema:///folder/";
+        "sub-schema id"
+    )]
+    #[test_case(
+        &json!({"$id": "folder/", "definitions": {"a": {"type": "integer"}}}),
+        "json-schema:///folder/#/definitions/a",
+        "json-schema:///folder/";
+        "document id"
+    )]
+    #[test_case(
+        &json!({"$id": "folder/", "definitions": {"a": {"$id": "nested/", "items": {}}}}),
+        "json-schema:///folder/#/definitions/a/items",
+        "json-schema:///folder/nested/";
+        "document and sub-schema ids"
+    )]
+    fn resolved_scope(schema: &Value, url: &str, expected: &str) {
+        // `$id`s passed on the way to the target change the scope, except for the one of the
+        // document itself
+        let resolver = make_resolver(schema);
+        let url = Url::parse(url).unwrap();
+        let (resource, _) = resolver.resolve_fragment(Draft::Draft7, &url).unwrap();
+        assert_eq!(resource.as_str(), expected);
+    }
+
     #[test]
     fn id_value_is_cleaned() {
         let schema = json!({
@@ -2,8 +2,10 @@ use crate::{
     compilation::context::CompilationContext,
     error::ErrorIterator,
     keywords::BoxedValidator,
+    limits,
     output::{Annotations, BasicOutput, ErrorDescription, OutputUnit},
     paths::{AbsolutePath, InstancePath, JSONPointer},
+    profile, trace,
     validator::{format_validators, PartialApplication, Validate},
     JSONSchema,
 };
@@ -106,6 +108,39 @@ impl SchemaNode {
         }
     }
 
+    /// Location of this node within the root schema.
+    pub(crate) const fn relative_path(&self) -> &JSONPointer {
+        &self.relative_path
+    }
+
+    /// `Some(true)` / `Some(false)` for boolean schemas, `None` otherwise.
+    pub(crate) const fn as_boolean(&self) -> Option<bool> {
+        match &self.validators {
+            NodeValidators::Boolean { validator } => Some(validator.is_none()),
+            _ => None,
+        }
+    }
+
+    pub(crate) const fn is_array(&self) -> bool {
+        matches!(self.validators, NodeValidators::Array { .. })
+    }
+
+    /// Validators of this node with the keywords they were compiled from.
+    /// Validators of array-valued schemas & boolean schemas have no keywords.
+    pub(crate) fn keyword_validators(&self) -> Vec<(Option<&str>, &BoxedValidator)> {
+        match &self.validators {
+            NodeValidators::Keyword(kvals) => kvals
+                .validators
+                .iter()
+                .map(|(keyword, validator)| (Some(keyword.as_str()), validator))
+                .collect(),
+            _ => self
+                .validators()
+                .map(|validator| (None, validator))
+                .collect(),
+        }
+    }
+
     fn format_validators(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", format_validators(self.validators()))
     }
@@ -179,6 +214,25 @@ impl SchemaNode {
         instance: &'b serde_json::Value,
         instance_path: &InstancePath,
     ) -> NodeValidatorsErrIter<'b> {
+        if !limits::enter_node() {
+            return NodeValidatorsErrIter::NoErrs;
+        }
+        if profile::is_active() {
+            return NodeValidatorsErrIter::Multiple(
+                self.keyword_validators()
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(index, (keyword, validator))| {
+                        profile::measure(self, index, keyword, || {
+                            validator
+                                .validate(schema, instance, instance_path)
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            );
+        }
         match &self.validators {
             NodeValidators::Keyword(kvs) if kvs.validators.len() == 1 => {
                 NodeValidatorsErrIter::Single(kvs.validators[0].1.validate(
@@ -230,12 +284,17 @@ impl SchemaNode {
         let mut success_results: VecDeque<OutputUnit<Annotations>> = VecDeque::new();
         let mut error_results = VecDeque::new();
         for (path, validator) in path_and_validators {
+            let span = trace::enter(&path);
             let path = self.relative_path.extend_with(&[path.into()]);
             let absolute_path = self
                 .absolute_path
                 .clone()
                 .map(|p| p.with_path(path.to_string().as_str()));
-            match validator.apply(schema, instance, instance_path) {
+            let result = validator.apply(schema, instance, instance_path);
+            if let Some(span) = span {
+                span.exit(&path, instance_path, &result);
+            }
+            match result {
                 PartialApplication::Valid {
                     annotations,
                     child_results,
@@ -297,6 +356,18 @@ impl Validate for SchemaNode {
     }
 
     fn is_valid(&self, schema: &JSONSchema, instance: &serde_json::Value) -> bool {
+        if !limits::enter_node() {
+            return false;
+        }
+        if profile::is_active() {
+            return self.keyword_validators().into_iter().enumerate().all(
+                |(index, (keyword, validator))| {
+                    profile::measure(self, index, keyword, || {
+                        validator.is_valid(schema, instance)
+                    })
+                },
+            );
+        }
         match &self.validators {
             // If we only have one validator then calling it's `is_valid` directly does
             // actually save the 20 or so instructions required to call the `slice::Iter::all`
@@ -327,6 +398,9 @@ impl Validate for SchemaNode {
         instance: &serde_json::Value,
         instance_path: &InstancePath,
     ) -> PartialApplication<'a> {
+        if !limits::enter_node() {
+            return PartialApplication::valid_empty();
+        }
         match self.validators {
             NodeValidators::Array { ref validators } => self.apply_subschemas(
                 schema,
@@ -22,7 +22,7 @@ impl Default for Draft {
     }
 }
 
-type CompileFunc<'a> = fn(
+pub(crate) type CompileFunc<'a> = fn(
     &'a Map<String, Value>,
     &'a Value,
     &CompilationContext,
@@ -140,6 +140,18 @@ pub(crate) fn draft_from_url(url: &str) -> Option<Draft> {
     }
 }
 
+/// The meta-schema URL of `draft`.
+#[inline]
+pub(crate) const fn url_of(draft: Draft) -> &'static str {
+    match draft {
+        #[cfg(feature = "draft201909")]
+        Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema#",
+        Draft::Draft7 => "http://json-schema.org/draft-07/schema#",
+        Draft::Draft6 => "http://json-schema.org/draft-06/schema#",
+        Draft::Draft4 => "http://json-schema.org/draft-04/schema#",
+    }
+}
+
 /// Get the `Draft` from a JSON Schema.
 #[inline]
 pub(crate) fn draft_from_schema(schema: &Value) -> Option<Draft> {
@@ -152,17 +164,41 @@ pub(crate) fn draft_from_schema(schema: &Value) -> Option<Draft> {
 #[inline]
 pub(crate) fn id_of(draft: Draft, schema: &Value) -> Option<&str> {
     if let Value::Object(object) = schema {
-        if draft == Draft::Draft4 {
-            object.get("id")
-        } else {
-            object.get("$id")
-        }
-        .and_then(Value::as_str)
+        object.get(id_keyword(draft)).and_then(Value::as_str)
     } else {
         None
     }
 }
 
+/// The keyword that changes the base URI in the given draft.
+#[inline]
+pub(crate) const fn id_keyword(draft: Draft) -> &'static str {
+    match draft {
+        Draft::Draft4 => "id",
+        _ => "$id",
+    }
+}
+
+/// Keywords whose values are instance data, so `$ref` inside them is not a reference.
+#[inline]
+pub(crate) fn is_data_keyword(keyword: &str) -> bool {
+    matches!(keyword, "enum" | "const" | "default" | "examples")
+}
+
+/// Keywords whose values map arbitrary names to schemas.
+#[inline]
+pub(crate) fn is_schema_map(keyword: &str) -> bool {
+    matches!(
+        keyword,
+        "properties"
+            | "patternProperties"
+            | "definitions"
+            | "$defs"
+            | "dependencies"
+            | "dependentSchemas"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
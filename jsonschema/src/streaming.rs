@@ -0,0 +1,617 @@
+//! Streaming validation of large JSON documents.
+//!
+//! A top-level JSON array is consumed element by element, so the whole document is never
+//! materialized as a `serde_json::Value`. Every item is validated against `items` (and
+//! `additionalItems` / `contains`) as soon as it is parsed and dropped afterwards. Only the state
+//! required by the keywords that need to look at the whole array is retained:
+//!   - `minItems` / `maxItems` - the number of items seen so far;
+//!   - `contains` - whether at least one item matched;
+//!   - `uniqueItems` - the items seen so far, bucketed by their hash.
+//!
+//! Documents that are not arrays at the top level are buffered and validated as usual.
+use crate::{
+    compilation::{
+        arena::ArenaBuilder, compile_validators, context::CompilationContext, JSONSchema,
+        DEFAULT_SCOPE,
+    },
+    keywords::{helpers::equal, unique_items::HashedValue},
+    paths::{InstancePath, JSONPointer},
+    schema_node::SchemaNode,
+    schemas,
+    validator::Validate,
+    ValidationError,
+};
+use ahash::{AHashMap, AHasher};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    io, mem,
+};
+
+/// Keywords that are always valid for arrays. They are only relevant if the document turns out not
+/// to be an array, and then the whole document is validated by the underlying `JSONSchema`.
+const NON_ARRAY_KEYWORDS: &[&str] = &[
+    "additionalProperties",
+    "contentEncoding",
+    "contentMediaType",
+    "dependencies",
+    "exclusiveMaximum",
+    "exclusiveMinimum",
+    "format",
+    "maxLength",
+    "maxProperties",
+    "maximum",
+    "minLength",
+    "minProperties",
+    "minimum",
+    "multipleOf",
+    "pattern",
+    "patternProperties",
+    "properties",
+    "propertyNames",
+    "required",
+];
+
+/// Keywords applied to the top-level array by `StreamingValidator` itself.
+const ARRAY_KEYWORDS: &[&str] = &[
+    "additionalItems",
+    "contains",
+    "items",
+    "maxItems",
+    "minItems",
+    "type",
+    "uniqueItems",
+];
+
+/// An empty array used to check the `type` keyword of the root schema. The type check does not
+/// depend on the array content.
+static EMPTY_ARRAY: Value = Value::Array(Vec::new());
+
+#[derive(Debug)]
+enum Items {
+    /// `items` is not specified - items are not validated.
+    Any,
+    /// `items` is a single schema.
+    Single(SchemaNode),
+    /// `items` is an array of schemas, with an optional `additionalItems` schema.
+    Tuple {
+        items: Vec<SchemaNode>,
+        additional: Option<SchemaNode>,
+    },
+}
+
+/// A validator that consumes a JSON document from a reader (or any `serde` deserializer) and
+/// validates a top-level array item by item.
+///
+/// Errors related to the array as a whole (`type`, `minItems`, `maxItems` and `contains`) are
+/// reported with an empty `instance_path` and `null` as the `instance`, because the array itself
+/// is not retained.
+/// Errors from `uniqueItems` point to the first repeated item.
+///
+/// The root schema may only contain keywords that can be checked without having the whole array
+/// in memory: `type`, `items`, `additionalItems`, `contains`, `minItems`, `maxItems`,
+/// `uniqueItems` and keywords that do not apply to arrays (e.g. `properties` or `minLength`).
+/// Subschemas of `items` and other keywords are not restricted.
+///
+/// ```rust
+/// # use jsonschema::StreamingValidator;
+/// # use serde_json::json;
+/// let schema = json!({"items": {"type": "integer"}, "maxItems": 3});
+/// let validator = StreamingValidator::compile(&schema).expect("A valid schema");
+/// let mut errors = Vec::new();
+/// let is_valid = validator
+///     .validate_reader(&b"[1, 2, \"3\"]"[..], |error| errors.push(error))
+///     .expect("A valid JSON document");
+/// assert!(!is_valid);
+/// assert_eq!(errors[0].instance_path.to_string(), "/2");
+/// ```
+#[derive(Debug)]
+pub struct StreamingValidator {
+    schema: JSONSchema,
+    type_: Option<crate::keywords::BoxedValidator>,
+    items: Items,
+    contains: Option<(SchemaNode, JSONPointer)>,
+    min_items: Option<(u64, JSONPointer)>,
+    max_items: Option<(u64, JSONPointer)>,
+    unique_items: Option<JSONPointer>,
+}
+
+impl StreamingValidator {
+    /// Compile the input schema into a streaming validator.
+    ///
+    /// The method is equivalent to `StreamingValidator::new(JSONSchema::compile(schema)?)`
+    pub fn compile(schema: &Value) -> Result<StreamingValidator, ValidationError<'_>> {
+        let compiled = JSONSchema::compile(schema)?;
+        StreamingValidator::new(compiled)
+    }
+
+    /// Build a streaming validator from an already compiled schema.
+    ///
+    /// Returns an error if the root schema contains keywords that require the whole array to be
+    /// loaded into memory (e.g. `enum` or `oneOf`).
+    pub fn new(mut schema: JSONSchema) -> Result<StreamingValidator, ValidationError<'static>> {
+        let root = schema.schema.clone();
+        let draft = schema.draft();
+        let scope = match schemas::id_of(draft, &root) {
+            Some(url) => url::Url::parse(url)?,
+            None => DEFAULT_SCOPE.clone(),
+        };
+        let object = match root.as_ref() {
+            Value::Object(object) => object,
+            // Boolean schemas do not have keywords that need streaming support
+            _ => {
+                return Ok(StreamingValidator {
+                    type_: None,
+                    items: Items::Any,
+                    contains: None,
+                    min_items: None,
+                    max_items: None,
+                    unique_items: None,
+                    schema,
+                })
+            }
+        };
+        for (keyword, value) in object {
+            if schema.config().get_validator(keyword).is_some()
+                && !ARRAY_KEYWORDS.contains(&keyword.as_str())
+                && !NON_ARRAY_KEYWORDS.contains(&keyword.as_str())
+            {
+                return Err(ValidationError::schema(value).into_owned());
+            }
+        }
+        if object.contains_key("$ref") {
+            return Err(ValidationError::schema(root.as_ref()).into_owned());
+        }
+        // `$ref` targets of the compiled sub-schemas are added to the arena of the schema
+        let references = ArenaBuilder::new(&schema.resolver, mem::take(&mut schema.references));
+        let context = CompilationContext::new(scope.into(), schema.config(), &references);
+        let type_ = match object.get("type") {
+            Some(value) => schema
+                .config()
+                .get_validator("type")
+                .and_then(|f| f(object, value, &context))
+                .transpose()
+                .map_err(ValidationError::into_owned)?,
+            None => None,
+        };
+        let items = match object.get("items") {
+            Some(Value::Array(items)) => {
+                let keyword_context = context.with_path("items");
+                let mut nodes = Vec::with_capacity(items.len());
+                for (idx, item) in items.iter().enumerate() {
+                    let item_context = keyword_context.with_path(idx);
+                    nodes.push(
+                        compile_validators(item, &item_context)
+                            .map_err(ValidationError::into_owned)?,
+                    );
+                }
+                let additional = match object.get("additionalItems") {
+                    Some(additional) => Some(
+                        compile_validators(additional, &context.with_path("additionalItems"))
+                            .map_err(ValidationError::into_owned)?,
+                    ),
+                    None => None,
+                };
+                Items::Tuple {
+                    items: nodes,
+                    additional,
+                }
+            }
+            Some(items) => Items::Single(
+                compile_validators(items, &context.with_path("items"))
+                    .map_err(ValidationError::into_owned)?,
+            ),
+            None => Items::Any,
+        };
+        let contains = match object.get("contains") {
+            Some(subschema) if schema.config().get_validator("contains").is_some() => {
+                let keyword_context = context.with_path("contains");
+                let node = compile_validators(subschema, &keyword_context)
+                    .map_err(ValidationError::into_owned)?;
+                Some((node, keyword_context.into_pointer()))
+            }
+            _ => None,
+        };
+        let limit = |keyword: &'static str| -> Result<_, ValidationError<'static>> {
+            match object.get(keyword) {
+                Some(value) => match value.as_u64() {
+                    Some(limit) => Ok(Some((limit, context.as_pointer_with(keyword)))),
+                    None => Err(ValidationError::schema(value).into_owned()),
+                },
+                None => Ok(None),
+            }
+        };
+        let min_items = limit("minItems")?;
+        let max_items = limit("maxItems")?;
+        let unique_items = match object.get("uniqueItems") {
+            Some(Value::Bool(true)) => Some(context.as_pointer_with("uniqueItems")),
+            _ => None,
+        };
+        schema.references = references.finish();
+        Ok(StreamingValidator {
+            type_,
+            items,
+            contains,
+            min_items,
+            max_items,
+            unique_items,
+            schema,
+        })
+    }
+
+    /// The underlying compiled schema.
+    pub const fn schema(&self) -> &JSONSchema {
+        &self.schema
+    }
+
+    /// Validate a JSON document read from `reader`.
+    ///
+    /// Every validation error is passed to `on_error` as soon as it is found. Returns whether the
+    /// document is valid, or an error if the document could not be read or parsed.
+    pub fn validate_reader<R, F>(
+        &self,
+        reader: R,
+        on_error: F,
+    ) -> Result<bool, ValidationError<'static>>
+    where
+        R: io::Read,
+        F: FnMut(ValidationError<'static>),
+    {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let is_valid = self.validate_deserializer(&mut deserializer, on_error)?;
+        deserializer.end()?;
+        Ok(is_valid)
+    }
+
+    /// Check whether a JSON document read from `reader` is valid. It is faster than
+    /// `validate_reader`, because no errors are constructed.
+    pub fn is_valid_reader<R: io::Read>(
+        &self,
+        reader: R,
+    ) -> Result<bool, ValidationError<'static>> {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let mut state = State::new(self, None);
+        (&mut state).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(state.finish())
+    }
+
+    /// Validate a document from an arbitrary `serde` deserializer (i.e. a token stream).
+    ///
+    /// Works the same way as `validate_reader`, but leaves checking for trailing data to the
+    /// caller.
+    pub fn validate_deserializer<'de, D, F>(
+        &self,
+        deserializer: D,
+        mut on_error: F,
+    ) -> Result<bool, D::Error>
+    where
+        D: Deserializer<'de>,
+        F: FnMut(ValidationError<'static>),
+    {
+        let mut state = State::new(self, Some(&mut on_error));
+        (&mut state).deserialize(deserializer)?;
+        Ok(state.finish())
+    }
+}
+
+type ErrorCallback<'f> = &'f mut dyn FnMut(ValidationError<'static>);
+
+/// Validation state for a single document.
+struct State<'v, 'f> {
+    validator: &'v StreamingValidator,
+    /// Where to report errors. If absent, only the validity is tracked.
+    on_error: Option<ErrorCallback<'f>>,
+    is_valid: bool,
+    /// `true` if the top-level value is an array. The array-level keywords are checked only then.
+    is_array: bool,
+    count: usize,
+    contains: bool,
+    /// Items seen so far, keyed by their hash. Only populated when `uniqueItems` is set.
+    seen: AHashMap<u64, Vec<Value>>,
+}
+
+impl<'v, 'f> State<'v, 'f> {
+    fn new(validator: &'v StreamingValidator, on_error: Option<ErrorCallback<'f>>) -> Self {
+        State {
+            validator,
+            on_error,
+            is_valid: true,
+            is_array: false,
+            count: 0,
+            contains: false,
+            seen: AHashMap::new(),
+        }
+    }
+
+    /// Whether errors should be constructed or only the validity is required.
+    const fn collects_errors(&self) -> bool {
+        self.on_error.is_some()
+    }
+
+    fn report(&mut self, error: ValidationError<'_>) {
+        self.is_valid = false;
+        if let Some(on_error) = self.on_error.as_mut() {
+            on_error(error.into_owned())
+        }
+    }
+
+    fn start_array(&mut self) {
+        let validator = self.validator;
+        self.is_array = true;
+        if let Some(type_) = &validator.type_ {
+            if self.collects_errors() {
+                let errors: Vec<_> = type_
+                    .validate(&validator.schema, &EMPTY_ARRAY, &InstancePath::new())
+                    .collect();
+                for error in errors {
+                    let mut error = error.into_owned();
+                    error.instance = std::borrow::Cow::Owned(Value::Null);
+                    self.report(error);
+                }
+            } else if !type_.is_valid(&validator.schema, &EMPTY_ARRAY) {
+                self.is_valid = false;
+            }
+        }
+    }
+
+    fn node_for(&self, idx: usize) -> Option<&'v SchemaNode> {
+        match &self.validator.items {
+            Items::Any => None,
+            Items::Single(node) => Some(node),
+            Items::Tuple { items, additional } => items.get(idx).or(additional.as_ref()),
+        }
+    }
+
+    fn item(&mut self, item: Value) {
+        let validator = self.validator;
+        let schema = &validator.schema;
+        let idx = self.count;
+        self.count += 1;
+        if !self.collects_errors() && !self.is_valid {
+            // The result is already known, only the rest of the input needs to be consumed
+            return;
+        }
+        if let Some((limit, schema_path)) = &validator.max_items {
+            if self.count as u64 == limit + 1 {
+                self.report(ValidationError::max_items(
+                    schema_path.clone(),
+                    JSONPointer::default(),
+                    &Value::Null,
+                    *limit,
+                ));
+            }
+        }
+        if let Some(node) = self.node_for(idx) {
+            if self.collects_errors() {
+                let instance_path = InstancePath::new();
+                let instance_path = instance_path.push(idx);
+                let errors: Vec<_> = node
+                    .validate(schema, &item, &instance_path)
+                    .map(ValidationError::into_owned)
+                    .collect();
+                for error in errors {
+                    self.report(error);
+                }
+            } else if !node.is_valid(schema, &item) {
+                self.is_valid = false;
+            }
+        }
+        if let Some((node, _)) = &validator.contains {
+            if !self.contains && node.is_valid(schema, &item) {
+                self.contains = true;
+            }
+        }
+        if let Some(schema_path) = &validator.unique_items {
+            let mut hasher = AHasher::default();
+            HashedValue(&item).hash(&mut hasher);
+            let bucket = self.seen.entry(hasher.finish()).or_default();
+            if bucket.iter().any(|seen| equal(seen, &item)) {
+                let schema_path = schema_path.clone();
+                let instance_path = InstancePath::new();
+                self.report(ValidationError::unique_items(
+                    schema_path,
+                    instance_path.push(idx).into(),
+                    &item,
+                ));
+            } else {
+                bucket.push(item);
+            }
+        }
+    }
+
+    fn end_array(&mut self) {
+        let validator = self.validator;
+        if let Some((limit, schema_path)) = &validator.min_items {
+            if (self.count as u64) < *limit {
+                self.report(ValidationError::min_items(
+                    schema_path.clone(),
+                    JSONPointer::default(),
+                    &Value::Null,
+                    *limit,
+                ));
+            }
+        }
+        if let Some((_, schema_path)) = &validator.contains {
+            if !self.contains {
+                self.report(ValidationError::contains(
+                    schema_path.clone(),
+                    JSONPointer::default(),
+                    &Value::Null,
+                ));
+            }
+        }
+    }
+
+    /// The document is not an array - validate it as a whole.
+    fn document(&mut self, document: &Value) {
+        let schema = &self.validator.schema;
+        if self.collects_errors() {
+            if let Err(errors) = schema.validate(document) {
+                let errors: Vec<_> = errors.map(ValidationError::into_owned).collect();
+                for error in errors {
+                    self.report(error);
+                }
+            }
+        } else if !schema.is_valid(document) {
+            self.is_valid = false;
+        }
+    }
+
+    const fn finish(&self) -> bool {
+        self.is_valid
+    }
+}
+
+impl<'de, 'a, 'v, 'f> DeserializeSeed<'de> for &'a mut State<'v, 'f> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a, 'v, 'f> Visitor<'de> for &'a mut State<'v, 'f> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.start_array();
+        while let Some(item) = seq.next_element::<Value>()? {
+            self.item(item);
+        }
+        self.end_array();
+        Ok(())
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let document = serde::Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        self.document(&document);
+        Ok(())
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        self.document(&Value::Bool(value));
+        Ok(())
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        self.document(&Value::from(value));
+        Ok(())
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        self.document(&Value::from(value));
+        Ok(())
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        self.document(&Value::from(value));
+        Ok(())
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        self.document(&Value::String(value.to_string()));
+        Ok(())
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+        self.document(&Value::String(value));
+        Ok(())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.document(&Value::Null);
+        Ok(())
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.document(&Value::Null);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamingValidator;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn errors(schema: &Value, document: &str) -> Vec<(String, String)> {
+        let validator = StreamingValidator::compile(schema).expect("Valid schema");
+        let mut errors = Vec::new();
+        let is_valid = validator
+            .validate_reader(document.as_bytes(), |error| {
+                errors.push((
+                    error.instance_path.to_string(),
+                    error.schema_path.to_string(),
+                ))
+            })
+            .expect("Valid JSON");
+        assert_eq!(is_valid, errors.is_empty());
+        assert_eq!(
+            validator
+                .is_valid_reader(document.as_bytes())
+                .expect("Valid JSON"),
+            is_valid
+        );
+        errors
+    }
+
+    #[test_case(&json!({"items": {"type": "integer"}}), "[1, 2, 3]")]
+    #[test_case(&json!({"items": [{"type": "integer"}], "additionalItems": {"type": "string"}}), r#"[1, "a", "b"]"#)]
+    #[test_case(&json!({"type": "array", "minItems": 2, "maxItems": 3}), "[1, 2]")]
+    #[test_case(&json!({"contains": {"const": 2}}), "[1, 2, 3]")]
+    #[test_case(&json!({"uniqueItems": true}), r#"[1, 2.5, "1", [1], {"a": 1}]"#)]
+    #[test_case(&json!({"minLength": 3, "items": {"type": "integer"}}), r#""foo""#)]
+    fn valid(schema: &Value, document: &str) {
+        assert_eq!(errors(schema, document), vec![])
+    }
+
+    #[test_case(&json!({"items": {"type": "integer"}}), r#"[1, "2", 3]"#, &[("/1", "/items/type")])]
+    #[test_case(&json!({"items": [{"type": "integer"}], "additionalItems": false}), "[1, 2, 3]", &[("/1", "/additionalItems"), ("/2", "/additionalItems")])]
+    #[test_case(&json!({"type": "object"}), "[]", &[("", "/type")])]
+    #[test_case(&json!({"minItems": 2}), "[1]", &[("", "/minItems")])]
+    #[test_case(&json!({"maxItems": 1}), "[1, 2, 3]", &[("", "/maxItems")])]
+    #[test_case(&json!({"contains": {"const": 5}}), "[1, 2]", &[("", "/contains")])]
+    #[test_case(&json!({"uniqueItems": true}), "[1, 2, 1.0]", &[("/2", "/uniqueItems")])]
+    #[test_case(&json!({"items": {"$ref": "#/definitions/a"}, "definitions": {"a": {"minimum": 2}}}), "[1]", &[("/0", "/items/minimum")])]
+    #[test_case(&json!({"minLength": 4}), r#""foo""#, &[("", "/minLength")])]
+    fn invalid(schema: &Value, document: &str, expected: &[(&str, &str)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(instance_path, schema_path)| {
+                (instance_path.to_string(), schema_path.to_string())
+            })
+            .collect();
+        assert_eq!(errors(schema, document), expected)
+    }
+
+    #[test_case(&json!({"enum": [[1]]}))]
+    #[test_case(&json!({"oneOf": [{"items": {"type": "integer"}}]}))]
+    #[test_case(&json!({"$ref": "#/definitions/a", "definitions": {"a": {}}}))]
+    fn unsupported_keywords(schema: &Value) {
+        assert!(StreamingValidator::compile(schema).is_err())
+    }
+
+    #[test]
+    fn invalid_json() {
+        let validator = StreamingValidator::compile(&json!({})).expect("Valid schema");
+        assert!(validator.validate_reader(&b"[1, "[..], |_| {}).is_err());
+        assert!(validator.is_valid_reader(&b"[1] 2"[..]).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! Evaluation traces for debugging schemas.
+//!
+//! A trace records every keyword evaluated by [`Output::trace`](crate::Output::trace) with its
+//! locations, result, direct errors and the time it took. Keywords with sub-schemas (e.g. `oneOf`
+//! or `properties`) contain the keywords of their sub-schemas as children, so it is possible to
+//! see which branches matched and why the others did not.
+//!
+//! ```rust
+//! use jsonschema::JSONSchema;
+//! use serde_json::json;
+//!
+//! let schema = json!({"oneOf": [{"type": "string"}, {"minLength": 2}]});
+//! let compiled = JSONSchema::compile(&schema).expect("Valid schema");
+//! let instance = json!("abc");
+//! let trace = compiled.apply(&instance).trace();
+//! assert!(!trace.valid);
+//! let one_of = &trace.entries[0];
+//! assert_eq!(one_of.keyword, "oneOf");
+//! assert_eq!(one_of.errors, vec!["more than one subschema succeeded"]);
+//! // Both branches matched
+//! assert!(one_of.children.iter().all(|branch| branch.valid));
+//! // Print as a tree
+//! println!("{}", trace);
+//! // Or as JSON
+//! println!("{}", serde_json::to_string_pretty(&trace).expect("Valid JSON"));
+//! ```
+//!
+//! Tracing is disabled unless a trace is requested. Other evaluations only check a process-wide
+//! counter of running traces, which stays zero unless traces are recorded.
+//! Keywords of `$ref` targets are children of the `$ref` entry. Keywords without the
+//! annotation-collecting evaluation are recorded as single entries without children.
+use crate::{
+    output::BasicOutput,
+    paths::{InstancePath, JSONPointer},
+    validator::PartialApplication,
+};
+use serde::ser::SerializeMap;
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The result of a traced evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// Whether the instance is valid.
+    pub valid: bool,
+    /// Keywords of the root schema, in the evaluation order.
+    pub entries: Vec<TraceEntry>,
+}
+
+/// A single evaluated keyword.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    /// Keyword name, or an index for array-valued schemas.
+    pub keyword: String,
+    /// Location of the keyword within the schema.
+    pub keyword_location: JSONPointer,
+    /// Location of the evaluated value within the instance.
+    pub instance_location: JSONPointer,
+    /// Whether the value is valid against the keyword.
+    pub valid: bool,
+    /// Errors reported by the keyword itself. Errors from sub-schemas are in `children`.
+    pub errors: Vec<String>,
+    /// Time spent evaluating the keyword, including its children.
+    pub duration: Duration,
+    /// Keywords of the sub-schemas evaluated by this keyword.
+    pub children: Vec<TraceEntry>,
+}
+
+thread_local! {
+    /// Entries of the keywords being evaluated, innermost last. `None` if tracing is disabled.
+    static STACK: RefCell<Option<Vec<Vec<TraceEntry>>>> = const { RefCell::new(None) };
+}
+
+/// The number of running traces, across all threads.
+static TRACED: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether any evaluation may be traced. If not, there is nothing to record.
+#[inline]
+fn is_traced() -> bool {
+    TRACED.load(Ordering::Relaxed) != 0
+}
+
+/// Restores the state of an outer trace, even if the evaluation panics.
+struct Scope {
+    previous: Option<Vec<Vec<TraceEntry>>>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        STACK.with(|stack| *stack.borrow_mut() = previous);
+        TRACED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Trace the evaluation done by `evaluate`.
+pub(crate) fn record<'a>(evaluate: impl FnOnce() -> BasicOutput<'a>) -> Trace {
+    TRACED.fetch_add(1, Ordering::Relaxed);
+    let scope = Scope {
+        previous: STACK.with(|stack| stack.replace(Some(vec![Vec::new()]))),
+    };
+    let valid = evaluate().is_valid();
+    let entries = STACK
+        .with(|stack| stack.borrow_mut().as_mut().and_then(Vec::pop))
+        .unwrap_or_default();
+    drop(scope);
+    Trace { valid, entries }
+}
+
+/// A keyword evaluation in progress.
+pub(crate) struct Span {
+    keyword: String,
+    start: Instant,
+}
+
+/// Start a keyword evaluation. Returns `None` if tracing is disabled.
+#[inline]
+pub(crate) fn enter(keyword: &impl fmt::Display) -> Option<Span> {
+    if !is_traced() {
+        return None;
+    }
+    STACK.with(|stack| {
+        stack.borrow_mut().as_mut().map(|stack| {
+            stack.push(Vec::new());
+            Span {
+                keyword: keyword.to_string(),
+                start: Instant::now(),
+            }
+        })
+    })
+}
+
+impl Span {
+    /// Finish the keyword evaluation and attach it to the enclosing one.
+    pub(crate) fn exit(
+        self,
+        keyword_location: &JSONPointer,
+        instance_path: &InstancePath,
+        result: &PartialApplication,
+    ) {
+        let duration = self.start.elapsed();
+        let (valid, errors) = match result {
+            PartialApplication::Valid { .. } => (true, Vec::new()),
+            PartialApplication::Invalid { errors, .. } => (
+                false,
+                errors
+                    .iter()
+                    .map(|error| error.as_str().to_string())
+                    .collect(),
+            ),
+        };
+        STACK.with(|stack| {
+            if let Some(stack) = stack.borrow_mut().as_mut() {
+                let children = stack.pop().unwrap_or_default();
+                if let Some(parent) = stack.last_mut() {
+                    parent.push(TraceEntry {
+                        keyword: self.keyword,
+                        keyword_location: keyword_location.clone(),
+                        instance_location: instance_path.into(),
+                        valid,
+                        errors,
+                        duration,
+                        children,
+                    });
+                }
+            }
+        });
+    }
+}
+
+/// Move the entries recorded so far for the current keyword under `prefix`, e.g. from a `$ref`
+/// target to the `$ref` keyword.
+pub(crate) fn relocate(prefix: &JSONPointer) {
+    fn relocate_all(entries: &mut [TraceEntry], prefix: &JSONPointer) {
+        for entry in entries {
+            entry.keyword_location = prefix.extend_with(entry.keyword_location.as_slice());
+            relocate_all(&mut entry.children, prefix);
+        }
+    }
+    if !is_traced() {
+        return;
+    }
+    STACK.with(|stack| {
+        if let Some(entries) = stack
+            .borrow_mut()
+            .as_mut()
+            .and_then(|stack| stack.last_mut())
+        {
+            relocate_all(entries, prefix);
+        }
+    });
+}
+
+impl TraceEntry {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(
+            f,
+            "{}{} {} at \"{}\" ({:?})",
+            indent,
+            if self.valid { "valid" } else { "invalid" },
+            self.keyword_location,
+            self.instance_location,
+            self.duration
+        )?;
+        for error in &self.errors {
+            writeln!(f, "{}  - {}", indent, error)?;
+        }
+        for child in &self.children {
+            child.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", if self.valid { "VALID" } else { "INVALID" })?;
+        for entry in &self.entries {
+            entry.fmt_indented(f, 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::Serialize for Trace {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map_ser = serializer.serialize_map(Some(2))?;
+        map_ser.serialize_entry("valid", &self.valid)?;
+        map_ser.serialize_entry("entries", &self.entries)?;
+        map_ser.end()
+    }
+}
+
+impl serde::Serialize for TraceEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map_ser = serializer.serialize_map(Some(7))?;
+        map_ser.serialize_entry("keyword", &self.keyword)?;
+        map_ser.serialize_entry("keywordLocation", &self.keyword_location)?;
+        map_ser.serialize_entry("instanceLocation", &self.instance_location)?;
+        map_ser.serialize_entry("valid", &self.valid)?;
+        map_ser.serialize_entry("errors", &self.errors)?;
+        map_ser.serialize_entry(
+            "durationNanos",
+            &u64::try_from(self.duration.as_nanos()).unwrap_or(u64::MAX),
+        )?;
+        map_ser.serialize_entry("children", &self.children)?;
+        map_ser.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Trace, TraceEntry};
+    use crate::JSONSchema;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn trace(schema: &Value, instance: &Value) -> Trace {
+        let compiled = JSONSchema::compile(schema).expect("Valid schema");
+        compiled.apply(instance).trace()
+    }
+
+    /// Keyword locations, instance locations & results in the depth-first order.
+    fn flatten(entries: &[TraceEntry], output: &mut Vec<(String, String, bool)>) {
+        for entry in entries {
+            output.push((
+                entry.keyword_location.to_string(),
+                entry.instance_location.to_string(),
+                entry.valid,
+            ));
+            flatten(&entry.children, output);
+        }
+    }
+
+    #[test_case(&json!({"type": "string"}), &json!("a"), true)]
+    #[test_case(&json!({"type": "string"}), &json!(1), false)]
+    #[test_case(&json!({"properties": {"a": {"minimum": 1}}}), &json!({"a": 0}), false)]
+    fn valid(schema: &Value, instance: &Value, expected: bool) {
+        assert_eq!(trace(schema, instance).valid, expected);
+    }
+
+    #[test]
+    fn nested() {
+        let trace = trace(
+            &json!({
+                "properties": {
+                    "a": {"oneOf": [{"type": "integer"}, {"type": "string"}]}
+                }
+            }),
+            &json!({"a": true}),
+        );
+        let mut entries = Vec::new();
+        flatten(&trace.entries, &mut entries);
+        let entry = |keyword: &str, instance: &str, valid| {
+            (keyword.to_string(), instance.to_string(), valid)
+        };
+        assert_eq!(
+            entries,
+            vec![
+                entry("/properties", "", false),
+                entry("/properties/a/oneOf", "/a", false),
+                entry("/properties/a/oneOf/0/type", "/a", false),
+                entry("/properties/a/oneOf/1/type", "/a", false),
+            ]
+        );
+        let type_ = &trace.entries[0].children[0].children[0];
+        assert_eq!(type_.keyword, "type");
+        assert_eq!(type_.errors, vec![r#"true is not of type "integer""#]);
+    }
+
+    #[test]
+    fn reference() {
+        let trace = trace(
+            &json!({
+                "properties": {"a": {"$ref": "#/definitions/a"}},
+                "definitions": {"a": {"items": {"$ref": "#/definitions/b"}}, "b": {"type": "string"}}
+            }),
+            &json!({"a": ["x", 1]}),
+        );
+        let mut entries = Vec::new();
+        flatten(&trace.entries, &mut entries);
+        let entry = |keyword: &str, instance: &str, valid| {
+            (keyword.to_string(), instance.to_string(), valid)
+        };
+        assert_eq!(
+            entries,
+            vec![
+                entry("/properties", "", false),
+                entry("/properties/a/$ref", "/a", false),
+                entry("/properties/a/$ref/items", "/a", false),
+                entry("/properties/a/$ref/items/$ref", "/a/0", true),
+                entry("/properties/a/$ref/items/$ref/type", "/a/0", true),
+                entry("/properties/a/$ref/items/$ref", "/a/1", false),
+                entry("/properties/a/$ref/items/$ref/type", "/a/1", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn display() {
+        let trace = trace(&json!({"minLength": 2}), &json!("a"));
+        let output = trace.to_string();
+        assert!(output.starts_with("INVALID\n  invalid /minLength at \"\" ("));
+        assert!(output.ends_with("    - \"a\" is shorter than 2 characters\n"));
+    }
+
+    #[test]
+    fn json() {
+        let trace = trace(&json!({"minLength": 2}), &json!("ab"));
+        let mut output = serde_json::to_value(&trace).expect("Valid JSON");
+        assert!(output["entries"][0]["durationNanos"].is_u64());
+        output["entries"][0]
+            .as_object_mut()
+            .expect("An object")
+            .remove("durationNanos");
+        assert_eq!(
+            output,
+            json!({
+                "valid": true,
+                "entries": [{
+                    "keyword": "minLength",
+                    "keywordLocation": "/minLength",
+                    "instanceLocation": "",
+                    "valid": true,
+                    "errors": [],
+                    "children": []
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn disabled_outside_of_trace() {
+        let compiled = JSONSchema::compile(&json!({"type": "string"})).expect("Valid schema");
+        assert_eq!(compiled.apply(&json!("a")).trace().entries.len(), 1);
+        // Nothing is left from the previous trace
+        assert!(!compiled.apply(&json!(1)).basic().is_valid());
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     error::ErrorIterator,
     keywords::BoxedValidator,
     output::{Annotations, ErrorDescription, OutputUnit},
-    paths::InstancePath,
+    paths::{InstancePath, JSONPointer},
     schema_node::SchemaNode,
 };
 use serde_json::Value;
@@ -35,6 +35,17 @@ pub(crate) trait Validate: Send + Sync + core::fmt::Display {
     // not constructed
     fn is_valid(&self, schema: &JSONSchema, instance: &Value) -> bool;
 
+    /// Sub-schemas applied by this validator. Used to walk the compiled tree.
+    /// `$ref` targets are stored in the arena of the schema and are not included.
+    fn children(&self) -> Vec<&SchemaNode> {
+        Vec::new()
+    }
+
+    /// Index of the `$ref` target in the arena of the schema.
+    fn reference_target(&self) -> Option<usize> {
+        None
+    }
+
     /// `apply` applies this validator and any sub-validators it is composed of to the value in
     /// question and collects the resulting annotations or errors. Note that the result of `apply`
     /// is a `PartialApplication`.
@@ -141,6 +152,33 @@ impl<'a> PartialApplication<'a> {
         }
     }
 
+    /// Move the child results under `prefix` and copy everything borrowed from the schema.
+    /// Used to report the results of a `$ref` target at the `$ref` keyword.
+    pub(crate) fn relocate(self, prefix: &JSONPointer) -> PartialApplication<'static> {
+        match self {
+            Self::Valid {
+                annotations,
+                child_results,
+            } => PartialApplication::Valid {
+                annotations: annotations.map(Annotations::into_owned),
+                child_results: child_results
+                    .into_iter()
+                    .map(|result| result.with_prefix(prefix).into_owned())
+                    .collect(),
+            },
+            Self::Invalid {
+                errors,
+                child_results,
+            } => PartialApplication::Invalid {
+                errors,
+                child_results: child_results
+                    .into_iter()
+                    .map(|result| result.with_prefix(prefix))
+                    .collect(),
+            },
+        }
+    }
+
     /// Set the error that will be returned for the current validator. If this
     /// `PartialApplication` is valid then this method converts this application into
     /// `PartialApplication::Invalid`
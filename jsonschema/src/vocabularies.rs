@@ -0,0 +1,168 @@
+//! Vocabularies declared by custom meta-schemas via the `$vocabulary` keyword.
+//!
+//! Only keywords from the vocabularies used by a dialect are validated.
+//! The core vocabulary (`$ref`, `$id`, etc.) is always enabled.
+use serde_json::Value;
+
+/// Vocabularies that affect validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Vocabulary {
+    Core,
+    Applicator,
+    Validation,
+    MetaData,
+    Format,
+    /// `format` is collected as an annotation only (2020-12).
+    FormatAnnotation,
+    Content,
+}
+
+impl Vocabulary {
+    fn from_url(url: &str) -> Option<Vocabulary> {
+        match url {
+            "https://json-schema.org/draft/2019-09/vocab/core"
+            | "https://json-schema.org/draft/2020-12/vocab/core" => Some(Vocabulary::Core),
+            "https://json-schema.org/draft/2019-09/vocab/applicator"
+            | "https://json-schema.org/draft/2020-12/vocab/applicator"
+            | "https://json-schema.org/draft/2020-12/vocab/unevaluated" => {
+                Some(Vocabulary::Applicator)
+            }
+            "https://json-schema.org/draft/2019-09/vocab/validation"
+            | "https://json-schema.org/draft/2020-12/vocab/validation" => {
+                Some(Vocabulary::Validation)
+            }
+            "https://json-schema.org/draft/2019-09/vocab/meta-data"
+            | "https://json-schema.org/draft/2020-12/vocab/meta-data" => Some(Vocabulary::MetaData),
+            "https://json-schema.org/draft/2019-09/vocab/format"
+            | "https://json-schema.org/draft/2020-12/vocab/format-assertion" => {
+                Some(Vocabulary::Format)
+            }
+            "https://json-schema.org/draft/2020-12/vocab/format-annotation" => {
+                Some(Vocabulary::FormatAnnotation)
+            }
+            "https://json-schema.org/draft/2019-09/vocab/content"
+            | "https://json-schema.org/draft/2020-12/vocab/content" => Some(Vocabulary::Content),
+            _ => None,
+        }
+    }
+
+    fn of_keyword(keyword: &str) -> Option<Vocabulary> {
+        match keyword {
+            "additionalItems"
+            | "additionalProperties"
+            | "allOf"
+            | "anyOf"
+            | "contains"
+            | "dependencies"
+            | "dependentSchemas"
+            | "else"
+            | "if"
+            | "items"
+            | "not"
+            | "oneOf"
+            | "patternProperties"
+            | "properties"
+            | "propertyNames"
+            | "then"
+            | "unevaluatedItems"
+            | "unevaluatedProperties" => Some(Vocabulary::Applicator),
+            "const" | "dependentRequired" | "enum" | "exclusiveMaximum" | "exclusiveMinimum"
+            | "maxContains" | "maximum" | "maxItems" | "maxLength" | "maxProperties"
+            | "minContains" | "minimum" | "minItems" | "minLength" | "minProperties"
+            | "multipleOf" | "pattern" | "required" | "type" | "uniqueItems" => {
+                Some(Vocabulary::Validation)
+            }
+            "format" => Some(Vocabulary::Format),
+            "contentEncoding" | "contentMediaType" | "contentSchema" => Some(Vocabulary::Content),
+            _ => None,
+        }
+    }
+}
+
+/// A set of vocabularies used by a dialect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Vocabularies(Vec<Vocabulary>);
+
+impl Vocabularies {
+    /// Collect vocabularies from the `$vocabulary` keyword of a meta-schema.
+    ///
+    /// Returns `Ok(None)` if the meta-schema doesn't declare vocabularies, so that all keywords
+    /// are available. Unknown vocabularies that are required (`true`) are returned as errors, as
+    /// the dialect can't be processed without them. Unknown optional vocabularies are ignored.
+    pub(crate) fn from_meta_schema(meta_schema: &Value) -> Result<Option<Vocabularies>, &str> {
+        match meta_schema.get("$vocabulary").and_then(Value::as_object) {
+            Some(declared) => {
+                let mut vocabularies = vec![Vocabulary::Core];
+                for (url, is_required) in declared {
+                    match Vocabulary::from_url(url) {
+                        Some(vocabulary) => vocabularies.push(vocabulary),
+                        None if is_required == &Value::Bool(true) => return Err(url),
+                        None => {}
+                    }
+                }
+                Ok(Some(Vocabularies(vocabularies)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Whether the keyword belongs to one of the vocabularies.
+    /// Keywords that are not a part of any known vocabulary are always available.
+    pub(crate) fn contains_keyword(&self, keyword: &str) -> bool {
+        match Vocabulary::of_keyword(keyword) {
+            Some(vocabulary) => self.0.contains(&vocabulary),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Vocabularies;
+    use serde_json::json;
+    use test_case::test_case;
+
+    #[test_case("type", false)]
+    #[test_case("properties", true)]
+    #[test_case("format", true)]
+    #[test_case("contentMediaType", false)]
+    #[test_case("$ref", true)]
+    #[test_case("x-custom", true)]
+    fn contains_keyword(keyword: &str, expected: bool) {
+        let vocabularies = Vocabularies::from_meta_schema(&json!({
+            "$vocabulary": {
+                "https://json-schema.org/draft/2019-09/vocab/core": true,
+                "https://json-schema.org/draft/2019-09/vocab/applicator": true,
+                "https://json-schema.org/draft/2019-09/vocab/format": false,
+                "https://example.com/vocab/optional": false
+            }
+        }))
+        .expect("Known vocabularies")
+        .expect("Vocabularies are declared");
+        assert_eq!(vocabularies.contains_keyword(keyword), expected)
+    }
+
+    #[test]
+    fn format_annotation() {
+        let vocabularies = Vocabularies::from_meta_schema(&json!({
+            "$vocabulary": {"https://json-schema.org/draft/2020-12/vocab/format-annotation": true}
+        }))
+        .expect("Known vocabularies")
+        .expect("Vocabularies are declared");
+        assert!(!vocabularies.contains_keyword("format"))
+    }
+
+    #[test]
+    fn unknown_required_vocabulary() {
+        let meta_schema = json!({"$vocabulary": {"https://example.com/vocab/custom": true}});
+        assert_eq!(
+            Vocabularies::from_meta_schema(&meta_schema),
+            Err("https://example.com/vocab/custom")
+        )
+    }
+
+    #[test]
+    fn no_vocabularies() {
+        assert_eq!(Vocabularies::from_meta_schema(&json!({})), Ok(None))
+    }
+}
@@ -580,6 +580,25 @@ use test_case::test_case;
         ]
     }); "valid propertyNames"
 }]
+#[test_case{
+    &json!({
+        "properties": {"name": {"$ref": "#/definitions/name"}},
+        "definitions": {"name": {"type": "string", "minLength": 2}}
+    }),
+    &json!{{
+        "name": "a",
+    }},
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/properties/name/$ref/minLength",
+                "instanceLocation": "/name",
+                "error": "\"a\" is shorter than 2 characters"
+            },
+        ]
+    }); "invalid $ref"
+}]
 fn test_basic_output(
     schema_json: &serde_json::Value,
     instance: &serde_json::Value,
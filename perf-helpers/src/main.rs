@@ -16,6 +16,15 @@ fn main() -> Result<(), Error> {
     eprintln!("Number of Iterations {}", number_of_iterations);
 
     let compiled = JSONSchema::compile(&schema).unwrap();
+    // Set `PROFILE` to print the time spent per schema location instead of a flamegraph
+    if std::env::var_os("PROFILE").is_some() {
+        let mut profiler = compiled.profiler();
+        for _ in 0..number_of_iterations {
+            profiler.is_valid(&instance);
+        }
+        eprintln!("{}", profiler.report());
+        return Ok(());
+    }
     for _ in 0..number_of_iterations {
         compiled.is_valid(&instance);
     }